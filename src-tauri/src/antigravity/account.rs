@@ -1,29 +1,371 @@
 use base64::Engine;
+use chrono::{DateTime, Utc};
 use prost::Message;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
 use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::antigravity::account_index::{self, CachedSummary};
+use crate::antigravity::{history, restore};
+use crate::constants::database;
+use crate::error::AgentError;
+use crate::state::AntigravityAccount;
+
+/// 保存账户的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedAccount {
+    pub email: String,
+    #[serde(rename = "accountFile")]
+    pub account_file: PathBuf,
+    /// 保存前账户文件不存在
+    #[serde(rename = "isNew")]
+    pub is_new: bool,
+    /// 账户文件内容与本次保存相同（未写入）
+    pub unchanged: bool,
+    /// AuthMetadata.expiry_timestamp（Unix 秒）
+    #[serde(rename = "expiryTimestamp")]
+    pub expiry_timestamp: Option<i64>,
+}
+
+/// 账户概要（不含令牌）
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountSummary {
+    pub email: String,
+    pub plan: Option<String>,
+    /// AuthMetadata.expiry_timestamp（Unix 秒）
+    #[serde(rename = "expiryTimestamp")]
+    pub expiry_timestamp: Option<i64>,
+}
+
+/// 已保存的账户：账户索引中的元数据 + 账户概要
+#[derive(Debug, Clone)]
+pub struct SavedAccountEntry {
+    pub metadata: AntigravityAccount,
+    /// 来自账户索引中的概要缓存，账户文件变化后才重新解码
+    pub summary: AccountSummary,
+    pub account_file: PathBuf,
+}
+
+impl SavedAccountEntry {
+    /// 读取账户文件并解码为完整的 SessionResponse JSON
+    pub fn decode(&self) -> Result<Value, AgentError> {
+        decode_jetski_state_proto(&restore::read_account_agent_state(&self.account_file)?)
+    }
+}
+
+/// 从 Antigravity 状态数据库读取当前的 jetskiStateSync.agentManagerInitState
+pub fn read_current_agent_state() -> Result<String, AgentError> {
+    // 获取 Antigravity 状态数据库路径（主路径不存在时尝试其他可能的位置）
+    let app_data = restore::resolve_antigravity_db_path()?;
+
+    if !app_data.exists() {
+        return Err(AgentError::database_missing(&app_data));
+    }
+
+    let conn = Connection::open(&app_data)
+        .map_err(|e| AgentError::sqlite(format!("连接数据库失败 ({})", app_data.display()), e))?;
+
+    conn.query_row(
+        "SELECT value FROM ItemTable WHERE key = ?",
+        [database::AGENT_STATE],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| AgentError::sqlite("查询 jetskiStateSync.agentManagerInitState 失败", e))?
+    .ok_or_else(|| {
+        AgentError::AccountNotFound("未找到 jetskiStateSync.agentManagerInitState".to_string())
+    })
+}
+
+/// 将 jetskiStateSync.agentManagerInitState 解码为 SessionResponse
+pub fn decode_session(agent_state: &str) -> Result<crate::proto::SessionResponse, AgentError> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(agent_state.trim())
+        .map_err(|e| AgentError::ProtoDecode(format!("jetskiStateSync Base64 解码失败: {}", e)))?;
+    crate::proto::SessionResponse::decode(bytes.as_slice())
+        .map_err(|e| AgentError::ProtoDecode(format!("jetskiStateSync Protobuf 解码失败: {}", e)))
+}
+
+/// 从 jetskiStateSync.agentManagerInitState 中提取邮箱、套餐和令牌过期时间
+pub fn summarize_agent_state(agent_state: &str) -> Result<AccountSummary, AgentError> {
+    let msg = decode_session(agent_state)?;
+    let context = msg.context.unwrap_or_default();
+    let plan = context
+        .plan
+        .map(|p| p.name)
+        .filter(|name| !name.is_empty())
+        .or_else(|| Some(context.plan_name).filter(|name| !name.is_empty()));
+
+    Ok(AccountSummary {
+        email: context.email,
+        plan,
+        expiry_timestamp: msg.auth.and_then(|a| a.meta).map(|m| m.expiry_timestamp),
+    })
+}
+
+/// 更新后的登录令牌
+#[derive(Debug, Clone)]
+pub struct AuthTokens {
+    pub access_token: String,
+    /// 新的 refresh token（Antigravity 存放在 AuthInfo.id_token），None 表示保持不变
+    pub refresh_token: Option<String>,
+    /// 过期时间（Unix 秒）
+    pub expiry_timestamp: i64,
+}
+
+/// SessionResponse.auth
+const SESSION_AUTH_TAG: u32 = 6;
+/// AuthInfo.access_token
+const AUTH_ACCESS_TOKEN_TAG: u32 = 1;
+/// AuthInfo.id_token（实际存放 refresh token）
+const AUTH_REFRESH_TOKEN_TAG: u32 = 3;
+/// AuthInfo.meta
+const AUTH_META_TAG: u32 = 4;
+/// AuthMetadata.expiry_timestamp
+const META_EXPIRY_TAG: u32 = 1;
+
+/// 将新的令牌写入 jetskiStateSync.agentManagerInitState
+///
+/// 直接修改原始字节中的 AuthInfo 字段，未声明的字段按原样保留
+pub fn replace_auth_tokens(agent_state: &str, tokens: &AuthTokens) -> Result<String, AgentError> {
+    use crate::utils::protobuf::{get_bytes_field, set_bytes_field, set_varint_field};
+
+    // 先确认原内容可以解码
+    decode_session(agent_state)?;
+    let session = base64::engine::general_purpose::STANDARD
+        .decode(agent_state.trim())
+        .map_err(|e| AgentError::ProtoDecode(format!("jetskiStateSync Base64 解码失败: {}", e)))?;
+
+    let auth = get_bytes_field(&session, SESSION_AUTH_TAG)?.unwrap_or_default();
+    let mut auth = set_bytes_field(auth, AUTH_ACCESS_TOKEN_TAG, tokens.access_token.as_bytes())?;
+    if let Some(refresh_token) = &tokens.refresh_token {
+        auth = set_bytes_field(&auth, AUTH_REFRESH_TOKEN_TAG, refresh_token.as_bytes())?;
+    }
+
+    let meta = get_bytes_field(&auth, AUTH_META_TAG)?.unwrap_or_default();
+    let meta = set_varint_field(meta, META_EXPIRY_TAG, tokens.expiry_timestamp as u64)?;
+    let auth = set_bytes_field(&auth, AUTH_META_TAG, &meta)?;
+    let session = set_bytes_field(&session, SESSION_AUTH_TAG, &auth)?;
+
+    let updated = base64::engine::general_purpose::STANDARD.encode(session);
+
+    // 确认修改后的内容仍能解码且令牌已更新
+    let check = decode_session(&updated)?;
+    let written = check.auth.unwrap_or_default();
+    if written.access_token != tokens.access_token
+        || written.meta.map(|m| m.expiry_timestamp) != Some(tokens.expiry_timestamp)
+    {
+        return Err(AgentError::Internal("写入令牌后校验失败".to_string()));
+    }
+    Ok(updated)
+}
+
+/// 将 jetskiStateSync.agentManagerInitState 保存为 {email}.json，并记录历史版本与账户索引
+///
+/// 直接保存原始字符串，邮箱仅用于确定文件名
+pub fn save_agent_state(agent_state: &str) -> Result<SavedAccount, AgentError> {
+    let msg = decode_session(agent_state)?;
+
+    let email = msg
+        .context
+        .as_ref()
+        .map(|c| c.email.clone())
+        .filter(|email| !email.is_empty())
+        .ok_or_else(|| {
+            AgentError::ProtoDecode(
+                "jetskiStateSync 中未找到邮箱字段，无法确定备份文件名".to_string(),
+            )
+        })?;
+    let expiry_timestamp = msg.auth.and_then(|a| a.meta).map(|m| m.expiry_timestamp);
+
+    // 邮箱来自快照内容，同样需要校验后才能用作文件名
+    let account_file = crate::directories::get_account_file(&email)?;
+    if let Some(accounts_dir) = account_file.parent() {
+        fs::create_dir_all(accounts_dir).map_err(|e| AgentError::io("创建账户目录失败", e))?;
+    }
+
+    let is_new = !account_file.exists();
+
+    if !is_new {
+        if let Ok(existing) = restore::read_account_agent_state(&account_file) {
+            if existing == agent_state {
+                return Ok(SavedAccount {
+                    email,
+                    account_file,
+                    is_new,
+                    unchanged: true,
+                    expiry_timestamp,
+                });
+            }
+        }
+
+        // 覆盖前确保旧版本已进入历史
+        if let Err(e) = history::seed_from_account_file(&email, &account_file) {
+            tracing::warn!(target: "account::history", error = %e, "记录已有账户文件到历史失败");
+        }
+    }
+
+    let content = serde_json::json!({ database::AGENT_STATE: agent_state });
+    fs::write(&account_file, serde_json::to_string_pretty(&content)?)
+        .map_err(|e| AgentError::io("写入 jetski 状态失败", e))?;
+    tracing::info!(file = %account_file.display(), "✅ 保存 jetski 状态完成");
+
+    if let Err(e) = history::record_snapshot(&email, agent_state) {
+        tracing::warn!(target: "account::history", error = %e, "记录账户历史版本失败");
+    }
+
+    // 索引写入失败不影响账户文件本身
+    if let Err(e) = account_index::record_saved(&email) {
+        tracing::warn!(target: "account::index", error = %e, "更新账户索引失败");
+    }
+
+    Ok(SavedAccount {
+        email,
+        account_file,
+        is_new,
+        unchanged: false,
+        expiry_timestamp,
+    })
+}
+
+/// 仅更新已保存账户文件中的令牌，不记录历史版本也不更新账户索引
+///
+/// 用于后台刷新令牌（如查询配额时 refresh token 被轮换），避免挤掉历史中的有效快照
+pub fn write_account_tokens(email: &str, agent_state: &str) -> Result<(), AgentError> {
+    let account_file = crate::directories::get_account_file(email)?;
+    if !account_file.exists() {
+        return Err(AgentError::AccountNotFound(email.to_string()));
+    }
+
+    let content = serde_json::json!({ database::AGENT_STATE: agent_state });
+    fs::write(&account_file, serde_json::to_string_pretty(&content)?)
+        .map_err(|e| AgentError::io("写入 jetski 状态失败", e))?;
+    tracing::info!(file = %account_file.display(), "✅ 已更新账户令牌");
+    Ok(())
+}
+
+/// 列出所有已保存的账户，按置顶、最近活动时间排序
+///
+/// 套餐与令牌过期时间取自账户索引中的概要缓存，只有缓存缺失或账户文件修改时间变化时才解码快照；
+/// 无法读取的账户文件会被跳过。账户索引中缺失的账户（旧版本保存或导入的文件）会被补齐，
+/// 创建时间取文件修改时间
+pub fn list_saved_accounts() -> Result<Vec<SavedAccountEntry>, AgentError> {
+    let antigravity_dir = crate::directories::get_accounts_directory();
+    let entries =
+        fs::read_dir(&antigravity_dir).map_err(|e| AgentError::io("读取备份目录失败", e))?;
+
+    let cached = account_index::load().summaries;
+    let mut accounts: Vec<(String, PathBuf, DateTime<Utc>, CachedSummary)> = Vec::new();
+    let mut summarized: Vec<(String, CachedSummary)> = Vec::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        // 只处理 JSON 文件
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Some(email) = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+
+        let modified_time: DateTime<Utc> = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+            .into();
+        let file_modified = modified_time.timestamp_millis();
+
+        let summary = match cached
+            .get(&email)
+            .filter(|summary| summary.file_modified == file_modified)
+        {
+            Some(summary) => summary.clone(),
+            None => {
+                tracing::debug!("📄 正在解析备份文件: {}", email);
+                let summary = match restore::read_account_agent_state(&path)
+                    .and_then(|state| summarize_agent_state(&state))
+                {
+                    Ok(summary) => CachedSummary {
+                        plan: summary.plan,
+                        expiry_timestamp: summary.expiry_timestamp,
+                        file_modified,
+                    },
+                    Err(e) => {
+                        tracing::warn!(file = %path.display(), error = %e, "跳过无法读取的账户文件");
+                        continue;
+                    }
+                };
+                summarized.push((email.clone(), summary.clone()));
+                summary
+            }
+        };
+
+        accounts.push((email, path, modified_time, summary));
+    }
+
+    let known: Vec<(String, DateTime<Utc>)> = accounts
+        .iter()
+        .map(|(email, _, modified_time, _)| (email.clone(), *modified_time))
+        .collect();
+    let index = account_index::sync_listed(&known, summarized);
+
+    let mut accounts: Vec<SavedAccountEntry> = accounts
+        .into_iter()
+        .filter_map(|(email, account_file, _, summary)| {
+            let metadata = index.accounts.get(&email)?.clone();
+            Some(SavedAccountEntry {
+                metadata,
+                summary: AccountSummary {
+                    email,
+                    plan: summary.plan,
+                    expiry_timestamp: summary.expiry_timestamp,
+                },
+                account_file,
+            })
+        })
+        .collect();
+
+    // 置顶优先，其次按最近活动时间（最新的在前）
+    accounts.sort_by(|a, b| {
+        b.metadata.pinned.cmp(&a.metadata.pinned).then_with(|| {
+            b.metadata
+                .last_active_at()
+                .cmp(&a.metadata.last_active_at())
+        })
+    });
+
+    Ok(accounts)
+}
 
 /// 将 jetskiStateSync.agentManagerInitState 作为 SessionResponse proto 解码
-pub fn decode_jetski_state_proto(b64: &str) -> Result<Value, String> {
+pub fn decode_jetski_state_proto(b64: &str) -> Result<Value, AgentError> {
     if b64.trim().is_empty() {
-        return Err("jetskiStateSync.agentManagerInitState 为空".to_string());
+        return Err(AgentError::ProtoDecode(
+            "jetskiStateSync.agentManagerInitState 为空".to_string(),
+        ));
     }
 
     let bytes = base64::engine::general_purpose::STANDARD
         .decode(b64)
         .map_err(|e| {
-            format!(
+            AgentError::ProtoDecode(format!(
                 "jetskiStateSync.agentManagerInitState Base64 解码失败(len={}): {}",
                 b64.len(),
                 e
-            )
+            ))
         })?;
 
     let msg = crate::proto::SessionResponse::decode(bytes.as_slice()).map_err(|e| {
-        format!(
+        AgentError::ProtoDecode(format!(
             "jetskiStateSync.agentManagerInitState Protobuf 解码失败(len={}): {}",
             bytes.len(),
             e
-        )
+        ))
     })?;
 
     Ok(session_response_to_json(&msg))
@@ -126,3 +468,102 @@ fn session_response_to_json(msg: &crate::proto::SessionResponse) -> Value {
         "context": context,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::{AuthInfo, AuthMetadata, SessionResponse, UserContext};
+    use crate::utils::protobuf::{get_bytes_field, set_bytes_field};
+
+    /// 未在 .proto 中声明的字段（SessionResponse / AuthInfo 中都未使用）
+    const UNKNOWN_TAG: u32 = 900;
+
+    fn encode_state(session: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(session)
+    }
+
+    fn decode_state(agent_state: &str) -> Vec<u8> {
+        base64::engine::general_purpose::STANDARD
+            .decode(agent_state)
+            .unwrap()
+    }
+
+    /// 构造带有未知字段的会话：SessionResponse 与 AuthInfo 末尾各有一个未知字段
+    fn session_with_unknown_fields(meta: Option<AuthMetadata>) -> Vec<u8> {
+        let auth = AuthInfo {
+            access_token: "old-access".to_string(),
+            r#type: "Bearer".to_string(),
+            id_token: "old-refresh".to_string(),
+            meta,
+        }
+        .encode_to_vec();
+        let auth = set_bytes_field(&auth, UNKNOWN_TAG, b"auth-extra").unwrap();
+
+        let session = SessionResponse {
+            context: Some(UserContext {
+                email: "user@example.com".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        let session = set_bytes_field(&session, SESSION_AUTH_TAG, &auth).unwrap();
+        set_bytes_field(&session, UNKNOWN_TAG, b"session-extra").unwrap()
+    }
+
+    fn tokens(refresh_token: Option<&str>) -> AuthTokens {
+        AuthTokens {
+            access_token: "new-access".to_string(),
+            refresh_token: refresh_token.map(str::to_string),
+            expiry_timestamp: 1_800_000_000,
+        }
+    }
+
+    #[test]
+    fn replace_auth_tokens_keeps_unknown_fields() {
+        let session = session_with_unknown_fields(Some(AuthMetadata {
+            expiry_timestamp: 1_700_000_000,
+        }));
+
+        let updated =
+            replace_auth_tokens(&encode_state(&session), &tokens(Some("new-refresh"))).unwrap();
+        let updated = decode_state(&updated);
+
+        assert_eq!(
+            get_bytes_field(&updated, UNKNOWN_TAG).unwrap(),
+            Some(&b"session-extra"[..])
+        );
+        let auth = get_bytes_field(&updated, SESSION_AUTH_TAG)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            get_bytes_field(auth, UNKNOWN_TAG).unwrap(),
+            Some(&b"auth-extra"[..])
+        );
+
+        let decoded = AuthInfo::decode(auth).unwrap();
+        assert_eq!(decoded.access_token, "new-access");
+        assert_eq!(decoded.id_token, "new-refresh");
+        assert_eq!(decoded.r#type, "Bearer");
+        assert_eq!(decoded.meta.unwrap().expiry_timestamp, 1_800_000_000);
+
+        let context = SessionResponse::decode(updated.as_slice())
+            .unwrap()
+            .context
+            .unwrap();
+        assert_eq!(context.email, "user@example.com");
+    }
+
+    #[test]
+    fn replace_auth_tokens_creates_missing_meta() {
+        let session = session_with_unknown_fields(None);
+
+        let updated = replace_auth_tokens(&encode_state(&session), &tokens(None)).unwrap();
+
+        let auth = decode_session(&updated).unwrap().auth.unwrap();
+        assert_eq!(auth.access_token, "new-access");
+        // 没有新的 refresh token 时保持原值
+        assert_eq!(auth.id_token, "old-refresh");
+        assert_eq!(auth.meta.unwrap().expiry_timestamp, 1_800_000_000);
+    }
+}
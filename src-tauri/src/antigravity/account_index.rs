@@ -0,0 +1,253 @@
+//! 账户索引模块
+//!
+//! 在 antigravity-accounts/meta/index.json 中按邮箱记录每个账户的元数据
+//! （创建时间、最近切换时间、切换次数、标签、备注、置顶等），账户文件本身保持不变。
+//! 索引同时缓存每个账户文件的概要（套餐、令牌过期时间），列出账户时无需逐个解码快照
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::AgentError;
+use crate::state::AntigravityAccount;
+
+/// 索引文件格式版本
+const INDEX_VERSION: u32 = 1;
+
+/// 串行化索引的读-改-写，避免并发命令互相覆盖
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// 账户文件概要的缓存，账户文件的修改时间变化后失效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSummary {
+    #[serde(default)]
+    pub plan: Option<String>,
+    /// AuthMetadata.expiry_timestamp（Unix 秒）
+    #[serde(rename = "expiryTimestamp", default)]
+    pub expiry_timestamp: Option<i64>,
+    /// 生成概要时账户文件的修改时间（Unix 毫秒）
+    #[serde(rename = "fileModified")]
+    pub file_modified: i64,
+}
+
+/// 账户索引文件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountIndex {
+    pub version: u32,
+    /// 邮箱 -> 账户元数据
+    #[serde(default)]
+    pub accounts: BTreeMap<String, AntigravityAccount>,
+    /// 邮箱 -> 账户文件概要
+    #[serde(default)]
+    pub summaries: BTreeMap<String, CachedSummary>,
+}
+
+impl Default for AccountIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            accounts: BTreeMap::new(),
+            summaries: BTreeMap::new(),
+        }
+    }
+}
+
+impl AccountIndex {
+    /// 获取账户元数据，不存在时创建
+    pub fn entry(&mut self, email: &str) -> &mut AntigravityAccount {
+        self.accounts
+            .entry(email.to_string())
+            .or_insert_with(|| AntigravityAccount::new(email))
+    }
+
+    /// 读取索引文件；文件无法解析时先备份再返回空索引
+    ///
+    /// 读取或备份失败时返回错误，调用方不应再写回，以免覆盖原有的标签、备注等数据
+    fn read(path: &Path) -> Result<Self, AgentError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content =
+            fs::read_to_string(path).map_err(|e| AgentError::io("读取账户索引失败", e))?;
+        match serde_json::from_str(&content) {
+            Ok(index) => Ok(index),
+            Err(e) => {
+                // 索引损坏时不影响账户文件本身，先把原文件移走再重建
+                let backup = Self::backup_path(path);
+                fs::rename(path, &backup)
+                    .map_err(|err| AgentError::io("备份损坏的账户索引失败", err))?;
+                tracing::warn!(
+                    target: "account::index",
+                    error = %e,
+                    backup = %backup.display(),
+                    "账户索引无法解析，已备份原文件并重新生成"
+                );
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// 损坏索引的备份路径：index.json.corrupt-{时间}
+    fn backup_path(path: &Path) -> PathBuf {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "index.json".to_string());
+        path.with_file_name(format!(
+            "{}.corrupt-{}",
+            file_name,
+            Utc::now().format("%Y%m%d%H%M%S")
+        ))
+    }
+
+    fn write(&self, path: &Path) -> Result<(), AgentError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| AgentError::io("创建索引目录失败", e))?;
+        }
+
+        // 先写临时文件再替换，避免中途退出留下半个索引
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| AgentError::io("写入账户索引失败", e))?;
+        fs::rename(&tmp_path, path).map_err(|e| AgentError::io("替换账户索引失败", e))?;
+        Ok(())
+    }
+}
+
+/// 读取账户索引
+pub fn load() -> AccountIndex {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    AccountIndex::read(&crate::directories::get_account_index_file()).unwrap_or_else(|e| {
+        tracing::warn!(target: "account::index", error = %e, "读取账户索引失败");
+        AccountIndex::default()
+    })
+}
+
+/// 在锁内修改账户索引并写回
+pub fn update<R>(update_fn: impl FnOnce(&mut AccountIndex) -> R) -> Result<R, AgentError> {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = crate::directories::get_account_index_file();
+
+    let mut index = AccountIndex::read(&path)?;
+    let result = update_fn(&mut index);
+    index.version = INDEX_VERSION;
+    index.write(&path)?;
+    Ok(result)
+}
+
+/// 同步列出的账户：为缺失的账户补齐元数据（创建时间取给定的时间），写入重新生成的概要，
+/// 并移除已不存在的账户的概要缓存，返回同步后的索引
+///
+/// 只在索引确实有变化时写回；写入失败只记录日志，不影响读取
+pub fn sync_listed(
+    accounts: &[(String, DateTime<Utc>)],
+    summaries: Vec<(String, CachedSummary)>,
+) -> AccountIndex {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = crate::directories::get_account_index_file();
+
+    // 读取失败时只在内存中补齐，不写回
+    let (mut index, writable) = match AccountIndex::read(&path) {
+        Ok(index) => (index, true),
+        Err(e) => {
+            tracing::warn!(target: "account::index", error = %e, "读取账户索引失败");
+            (AccountIndex::default(), false)
+        }
+    };
+    let mut added = 0;
+    for (email, created_at) in accounts {
+        if !index.accounts.contains_key(email) {
+            let account = index.entry(email);
+            account.created_at = *created_at;
+            account.updated_at = *created_at;
+            added += 1;
+        }
+    }
+
+    let summarized = summaries.len();
+    index.summaries.extend(summaries);
+    let cached = index.summaries.len();
+    index
+        .summaries
+        .retain(|email, _| accounts.iter().any(|(listed, _)| listed == email));
+    let pruned = cached - index.summaries.len();
+
+    if writable && (added > 0 || summarized > 0 || pruned > 0) {
+        index.version = INDEX_VERSION;
+        match index.write(&path) {
+            Ok(()) => {
+                tracing::info!(target: "account::index", added, summarized, pruned, "📇 已同步账户索引")
+            }
+            Err(e) => tracing::warn!(target: "account::index", error = %e, "写入账户索引失败"),
+        }
+    }
+    index
+}
+
+/// 记录账户被保存（新账户会写入创建时间）
+pub fn record_saved(email: &str) -> Result<(), AgentError> {
+    update(|index| {
+        index.entry(email).updated_at = Utc::now();
+    })
+}
+
+/// 记录一次成功切换
+pub fn record_switched(email: &str) -> Result<(), AgentError> {
+    update(|index| {
+        let account = index.entry(email);
+        account.last_switched_at = Some(Utc::now());
+        account.switch_count += 1;
+    })
+}
+
+/// 删除账户时移除其元数据
+pub fn remove(email: &str) -> Result<(), AgentError> {
+    update(|index| {
+        index.accounts.remove(email);
+        index.summaries.remove(email);
+    })
+}
+
+/// 清空所有账户元数据
+pub fn clear() -> Result<(), AgentError> {
+    update(|index| {
+        index.accounts.clear();
+        index.summaries.clear();
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_backs_up_unparseable_index() {
+        let dir = std::env::temp_dir().join(format!("account-index-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("index.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let index = AccountIndex::read(&path).unwrap();
+        assert!(index.accounts.is_empty());
+        assert!(!path.exists());
+
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("index.json.corrupt-")
+            })
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(backups[0].path()).unwrap(), "{ not json");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
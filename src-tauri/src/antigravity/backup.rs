@@ -0,0 +1,307 @@
+//! 账户导入导出模块
+//!
+//! 收集/写回账户文件，以及导出配置文件的加解密（桌面应用与 antigravity-agent-cli 共用）
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::time::SystemTime;
+
+use crate::error::AgentError;
+
+/// 备份数据收集结构
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccountExportedData {
+    pub filename: String,
+    #[serde(rename = "content")]
+    pub content: Value,
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64,
+}
+
+/// 恢复结果
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RestoreResult {
+    #[serde(rename = "restoredCount")]
+    pub restored_count: u32,
+    pub failed: Vec<FailedAccountExportedData>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FailedAccountExportedData {
+    pub filename: String,
+    pub error: String,
+}
+
+const CONFIG_ENCRYPTION_VERSION: u8 = 2;
+const PBKDF2_ITERATIONS: u32 = 210_000;
+const PBKDF2_SALT_LEN: usize = 16;
+const AES_GCM_NONCE_LEN: usize = 12;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct EncryptedConfigEnvelopeV2 {
+    v: u8,
+    kdf: String,
+    iter: u32,
+    #[serde(rename = "salt")]
+    salt_b64: String,
+    #[serde(rename = "nonce")]
+    nonce_b64: String,
+    #[serde(rename = "ciphertext")]
+    ciphertext_b64: String,
+}
+
+/// 导出配置文件内容（加密前），与前端导出格式一致
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportedConfig {
+    pub version: String,
+    #[serde(rename = "backupCount")]
+    pub backup_count: usize,
+    pub backups: Vec<AccountExportedData>,
+}
+
+/// 导出配置文件格式版本
+pub const EXPORTED_CONFIG_VERSION: &str = "1.1.0";
+
+impl ExportedConfig {
+    pub fn new(backups: Vec<AccountExportedData>) -> Self {
+        Self {
+            version: EXPORTED_CONFIG_VERSION.to_string(),
+            backup_count: backups.len(),
+            backups,
+        }
+    }
+}
+
+/// 收集所有账户文件的完整内容, 用于导出
+pub fn collect_account_contents() -> Result<Vec<AccountExportedData>, AgentError> {
+    let mut backups_with_content = Vec::new();
+
+    // 读取Antigravity账户目录中的JSON文件
+    let antigravity_dir = crate::directories::get_accounts_directory();
+
+    for entry in
+        fs::read_dir(&antigravity_dir).map_err(|e| AgentError::io("读取用户目录失败", e))?
+    {
+        let entry = entry.map_err(|e| AgentError::io("读取目录项失败", e))?;
+        let path = entry.path();
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let filename = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+
+            if filename.is_empty() {
+                continue;
+            }
+
+            match fs::read_to_string(&path) {
+                Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
+                    Ok(json_value) => {
+                        backups_with_content.push(AccountExportedData {
+                            filename,
+                            content: json_value,
+                            timestamp: SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap_or_default()
+                                .as_secs(),
+                        });
+                    }
+                    Err(e) => {
+                        tracing::warn!(target: "backup::scan", filename = %filename, error = %e, "跳过损坏的备份文件");
+                    }
+                },
+                Err(_) => {
+                    tracing::warn!(target: "backup::scan", filename = %filename, "跳过无法读取的文件");
+                }
+            }
+        }
+    }
+
+    Ok(backups_with_content)
+}
+
+/// 将导入的账户文件写回账户目录
+pub fn restore_account_files(
+    account_file_data: Vec<AccountExportedData>,
+) -> Result<RestoreResult, AgentError> {
+    let mut results = RestoreResult {
+        restored_count: 0,
+        failed: Vec::new(),
+    };
+
+    // 获取目标目录
+    let antigravity_dir = crate::directories::get_accounts_directory();
+
+    // 确保目录存在
+    if let Err(e) = fs::create_dir_all(&antigravity_dir) {
+        return Err(AgentError::io("创建目录失败", e));
+    }
+
+    // 遍历每个备份
+    for account_file in account_file_data {
+        let file_path = antigravity_dir.join(&account_file.filename);
+
+        match fs::write(
+            &file_path,
+            serde_json::to_string_pretty(&account_file.content).unwrap_or_default(),
+        )
+        .map_err(|e| format!("写入文件失败: {}", e))
+        {
+            Ok(_) => {
+                results.restored_count += 1;
+            }
+            Err(e) => {
+                results.failed.push(FailedAccountExportedData {
+                    filename: account_file.filename,
+                    error: e,
+                });
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// 使用密码加密导出配置
+pub fn encrypt_config(json_data: &str, password: &str) -> Result<String, AgentError> {
+    if password.is_empty() {
+        return Err(AgentError::InvalidInput("密码不能为空".to_string()));
+    }
+
+    encrypt_config_data_v2(json_data, password)
+}
+
+/// 解密导出配置（兼容 v2 envelope 与旧版 XOR 格式）
+pub fn decrypt_config(encrypted_data: &str, password: &str) -> Result<String, AgentError> {
+    if password.is_empty() {
+        return Err(AgentError::InvalidInput("密码不能为空".to_string()));
+    }
+
+    let trimmed = encrypted_data.trim();
+
+    if trimmed.starts_with('{') {
+        // v2 加密格式：JSON envelope
+        if serde_json::from_str::<EncryptedConfigEnvelopeV2>(trimmed).is_ok() {
+            return decrypt_config_data_v2(trimmed, password);
+        }
+    }
+
+    decrypt_config_data_legacy_xor_base64(encrypted_data.to_string(), password.to_string())
+}
+
+fn derive_config_key_pbkdf2(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    use pbkdf2::pbkdf2_hmac;
+    use sha2::Sha256;
+
+    let mut key = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut key);
+    key
+}
+
+fn encrypt_config_data_v2(json_data: &str, password: &str) -> Result<String, AgentError> {
+    use aes_gcm::aead::Aead;
+    use aes_gcm::KeyInit;
+    use aes_gcm::{Aes256Gcm, Nonce};
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+    use rand::RngCore;
+
+    let mut salt = [0u8; PBKDF2_SALT_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+
+    let mut nonce_bytes = [0u8; AES_GCM_NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
+
+    let key = derive_config_key_pbkdf2(password.as_bytes(), &salt, PBKDF2_ITERATIONS);
+    let cipher =
+        Aes256Gcm::new_from_slice(&key).map_err(|_| AgentError::Crypto("加密失败".to_string()))?;
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), json_data.as_bytes())
+        .map_err(|_| AgentError::Crypto("加密失败".to_string()))?;
+
+    let envelope = EncryptedConfigEnvelopeV2 {
+        v: CONFIG_ENCRYPTION_VERSION,
+        kdf: "pbkdf2-sha256".to_string(),
+        iter: PBKDF2_ITERATIONS,
+        salt_b64: BASE64.encode(salt),
+        nonce_b64: BASE64.encode(nonce_bytes),
+        ciphertext_b64: BASE64.encode(ciphertext),
+    };
+
+    serde_json::to_string(&envelope).map_err(|_| AgentError::Crypto("加密失败".to_string()))
+}
+
+fn decrypt_config_data_v2(encrypted_data: &str, password: &str) -> Result<String, AgentError> {
+    use aes_gcm::aead::Aead;
+    use aes_gcm::KeyInit;
+    use aes_gcm::{Aes256Gcm, Nonce};
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+    let envelope: EncryptedConfigEnvelopeV2 = serde_json::from_str(encrypted_data)
+        .map_err(|_| AgentError::Crypto("解密失败，数据格式无效".to_string()))?;
+
+    if envelope.v != CONFIG_ENCRYPTION_VERSION {
+        return Err(AgentError::Crypto("解密失败，不支持的加密版本".to_string()));
+    }
+
+    if envelope.kdf != "pbkdf2-sha256" {
+        return Err(AgentError::Crypto("解密失败，不支持的 KDF".to_string()));
+    }
+
+    // 防止被构造的极端参数拖慢解密
+    if envelope.iter < 10_000 || envelope.iter > 10_000_000 {
+        return Err(AgentError::Crypto(
+            "解密失败，不支持的 KDF 参数".to_string(),
+        ));
+    }
+
+    let salt = BASE64
+        .decode(envelope.salt_b64)
+        .map_err(|_| AgentError::Crypto("解密失败，salt 无效".to_string()))?;
+    let nonce_bytes = BASE64
+        .decode(envelope.nonce_b64)
+        .map_err(|_| AgentError::Crypto("解密失败，nonce 无效".to_string()))?;
+    let ciphertext = BASE64
+        .decode(envelope.ciphertext_b64)
+        .map_err(|_| AgentError::Crypto("解密失败，密文无效".to_string()))?;
+
+    if salt.len() != PBKDF2_SALT_LEN || nonce_bytes.len() != AES_GCM_NONCE_LEN {
+        return Err(AgentError::Crypto("解密失败，数据格式无效".to_string()));
+    }
+
+    let key = derive_config_key_pbkdf2(password.as_bytes(), &salt, envelope.iter);
+    let cipher =
+        Aes256Gcm::new_from_slice(&key).map_err(|_| AgentError::Crypto("解密失败".to_string()))?;
+
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_ref())
+        .map_err(|_| AgentError::Crypto("解密失败，密码错误或数据已损坏".to_string()))?;
+
+    String::from_utf8(plaintext)
+        .map_err(|_| AgentError::Crypto("解密失败，数据可能已损坏".to_string()))
+}
+
+fn decrypt_config_data_legacy_xor_base64(
+    encrypted_data: String,
+    password: String,
+) -> Result<String, AgentError> {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+    let decoded = BASE64
+        .decode(encrypted_data)
+        .map_err(|_| AgentError::Crypto("Base64 解码失败".to_string()))?;
+
+    let password_bytes = password.as_bytes();
+    let mut result = Vec::with_capacity(decoded.len());
+
+    for (i, byte) in decoded.iter().enumerate() {
+        let key_byte = password_bytes[i % password_bytes.len()];
+        result.push(byte ^ key_byte);
+    }
+
+    String::from_utf8(result)
+        .map_err(|_| AgentError::Crypto("解密失败，数据可能已损坏".to_string()))
+}
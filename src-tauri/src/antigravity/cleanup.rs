@@ -5,11 +5,13 @@ use rusqlite::{params, Connection};
 use std::path::Path;
 
 // 导入 platform_utils 模块
-use crate::platform;
+use crate::antigravity::restore::resolve_antigravity_db_path;
+use crate::error::AgentError;
 
-fn clear_database(db_path: &Path, db_name: &str) -> Result<usize, String> {
+fn clear_database(db_path: &Path, db_name: &str) -> Result<usize, AgentError> {
     tracing::info!(target: "cleanup::database", db_name = %db_name, "开始清理数据库");
-    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    let conn = Connection::open(db_path)
+        .map_err(|e| AgentError::sqlite(format!("连接数据库失败 ({})", db_path.display()), e))?;
 
     // 仅删除 jetskiStateSync.agentManagerInitState
     let key = "jetskiStateSync.agentManagerInitState";
@@ -33,25 +35,13 @@ fn clear_database(db_path: &Path, db_name: &str) -> Result<usize, String> {
     Ok(rows + onboarding_rows)
 }
 
-pub async fn clear_all_antigravity_data() -> Result<String, String> {
+pub async fn clear_all_antigravity_data() -> Result<String, AgentError> {
     tracing::info!(target: "cleanup::main", "开始清除 Antigravity 用户认证数据（保留设备指纹）");
 
-    let app_data = match platform::get_antigravity_db_path() {
-        Some(p) => p,
-        None => {
-            let possible_paths = platform::get_all_antigravity_db_paths();
-            if possible_paths.is_empty() {
-                return Err("未找到 Antigravity 安装位置".to_string());
-            }
-            possible_paths[0].clone()
-        }
-    };
+    let app_data = resolve_antigravity_db_path()?;
 
     if !app_data.exists() {
-        return Err(format!(
-            "Antigravity 状态数据库不存在: {}",
-            app_data.display()
-        ));
+        return Err(AgentError::database_missing(&app_data));
     }
 
     let mut msg = String::new();
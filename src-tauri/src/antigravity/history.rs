@@ -0,0 +1,207 @@
+//! 账户快照历史模块
+//!
+//! 每次保存账户时在 antigravity-accounts/history/{email}/{id}.json 追加一个版本，
+//! 只保留最近 MAX_VERSIONS 个，便于在保存了无效快照（如令牌过期）后回滚到可用版本
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::constants::database;
+use crate::error::AgentError;
+
+/// 每个账户保留的历史版本数
+pub const MAX_VERSIONS: usize = 10;
+
+/// 版本 id 格式（UTC 时间，精确到毫秒，字典序即时间序）
+const VERSION_ID_FORMAT: &str = "%Y%m%d%H%M%S%3f";
+
+/// 历史版本信息（不含账户数据本身）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotVersion {
+    pub id: String,
+    #[serde(rename = "capturedAt")]
+    pub captured_at: DateTime<Utc>,
+    /// AuthMetadata.expiry_timestamp（Unix 秒），解码失败时为 None
+    #[serde(rename = "expiryTimestamp")]
+    pub expiry_timestamp: Option<i64>,
+}
+
+/// 历史版本文件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotFile {
+    #[serde(flatten)]
+    version: SnapshotVersion,
+    #[serde(rename = "jetskiStateSync.agentManagerInitState")]
+    agent_state: String,
+}
+
+/// 账户的历史目录，账户名不合法时返回错误
+fn account_history_dir(email: &str) -> Result<PathBuf, AgentError> {
+    crate::directories::validate_account_name(email)?;
+    Ok(crate::directories::get_accounts_history_directory().join(email))
+}
+
+fn version_file(email: &str, version_id: &str) -> Result<PathBuf, AgentError> {
+    // 版本 id 只包含数字，防止拼接出目录外的路径
+    if version_id.is_empty() || !version_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AgentError::InvalidInput(format!(
+            "无效的版本 id: {}",
+            version_id
+        )));
+    }
+    Ok(account_history_dir(email)?.join(format!("{version_id}.json")))
+}
+
+/// 从 jetskiStateSync.agentManagerInitState 中读取令牌过期时间
+pub fn decode_expiry_timestamp(agent_state: &str) -> Option<i64> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(agent_state.trim())
+        .ok()?;
+    let msg = crate::proto::SessionResponse::decode(bytes.as_slice()).ok()?;
+    msg.auth?.meta.map(|m| m.expiry_timestamp)
+}
+
+fn read_snapshot(path: &Path) -> Result<SnapshotFile, AgentError> {
+    let content = fs::read_to_string(path).map_err(|e| AgentError::io(path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| AgentError::AccountNotFound(format!("{}: {}", path.display(), e)))
+}
+
+/// 按时间倒序列出账户的历史快照文件
+fn snapshot_files(email: &str) -> Result<Vec<PathBuf>, AgentError> {
+    let dir = account_history_dir(email)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| AgentError::io("读取历史目录失败", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+    Ok(files)
+}
+
+/// 追加一个历史版本并清理超出保留数量的旧版本
+///
+/// 与最新版本内容相同时不重复记录，返回 None
+pub fn record_snapshot(
+    email: &str,
+    agent_state: &str,
+) -> Result<Option<SnapshotVersion>, AgentError> {
+    let files = snapshot_files(email)?;
+    if let Some(latest) = files.first() {
+        match read_snapshot(latest) {
+            Ok(snapshot) if snapshot.agent_state == agent_state => return Ok(None),
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(target: "account::history", error = %e, "读取最新历史版本失败")
+            }
+        }
+    }
+
+    let captured_at = Utc::now();
+    let version = SnapshotVersion {
+        id: captured_at.format(VERSION_ID_FORMAT).to_string(),
+        captured_at,
+        expiry_timestamp: decode_expiry_timestamp(agent_state),
+    };
+
+    let path = version_file(email, &version.id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AgentError::io("创建历史目录失败", e))?;
+    }
+    let snapshot = SnapshotFile {
+        version: version.clone(),
+        agent_state: agent_state.to_string(),
+    };
+    fs::write(&path, serde_json::to_string_pretty(&snapshot)?)
+        .map_err(|e| AgentError::io("写入历史版本失败", e))?;
+
+    // 新版本排在最前，保留前 MAX_VERSIONS - 1 个旧版本
+    for old in files.iter().skip(MAX_VERSIONS - 1) {
+        if let Err(e) = fs::remove_file(old) {
+            tracing::warn!(target: "account::history", file = %old.display(), error = %e, "删除旧历史版本失败");
+        }
+    }
+
+    tracing::info!(target: "account::history", version = %version.id, "📚 已记录账户历史版本");
+    Ok(Some(version))
+}
+
+/// 账户还没有历史版本时，先把现有账户文件记为第一个版本，避免被覆盖后丢失
+pub fn seed_from_account_file(email: &str, account_file: &Path) -> Result<(), AgentError> {
+    if !account_file.exists() || !snapshot_files(email)?.is_empty() {
+        return Ok(());
+    }
+
+    let agent_state = crate::antigravity::restore::read_account_agent_state(account_file)?;
+    record_snapshot(email, &agent_state)?;
+    Ok(())
+}
+
+/// 列出账户的历史版本（最新的在前）
+pub fn list_versions(email: &str) -> Result<Vec<SnapshotVersion>, AgentError> {
+    Ok(snapshot_files(email)?
+        .iter()
+        .filter_map(|path| match read_snapshot(path) {
+            Ok(snapshot) => Some(snapshot.version),
+            Err(e) => {
+                tracing::warn!(target: "account::history", error = %e, "跳过损坏的历史版本");
+                None
+            }
+        })
+        .collect())
+}
+
+/// 读取指定历史版本的 jetskiStateSync.agentManagerInitState
+pub fn read_version_agent_state(email: &str, version_id: &str) -> Result<String, AgentError> {
+    let path = version_file(email, version_id)?;
+    if !path.exists() {
+        return Err(AgentError::AccountNotFound(format!(
+            "{} 的历史版本 {} 不存在",
+            email, version_id
+        )));
+    }
+    Ok(read_snapshot(&path)?.agent_state)
+}
+
+/// 将历史版本的内容写回账户文件，使其成为当前版本
+///
+/// 覆盖前先把现有账户文件记为一个历史版本：后台刷新令牌时只更新账户文件
+/// （不记录历史），其中可能有更新的 refresh token
+pub fn restore_version_to_account_file(
+    email: &str,
+    version_id: &str,
+    agent_state: &str,
+    account_file: &Path,
+) -> Result<(), AgentError> {
+    if account_file.exists() {
+        let current = crate::antigravity::restore::read_account_agent_state(account_file)?;
+        record_snapshot(email, &current)?;
+    }
+
+    // 先写临时文件再替换，避免中途退出留下半个账户文件
+    let content = serde_json::json!({ database::AGENT_STATE: agent_state });
+    let tmp_path = account_file.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(&content)?)
+        .map_err(|e| AgentError::io("写入账户文件失败", e))?;
+    fs::rename(&tmp_path, account_file).map_err(|e| AgentError::io("替换账户文件失败", e))?;
+
+    tracing::info!(target: "account::history", version = %version_id, "已将历史版本写回账户文件");
+    Ok(())
+}
+
+/// 删除账户的全部历史版本
+pub fn remove_account_history(email: &str) -> Result<(), AgentError> {
+    let dir = account_history_dir(email)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| AgentError::io("删除历史版本失败", e))?;
+    }
+    Ok(())
+}
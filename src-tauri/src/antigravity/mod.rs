@@ -1,5 +1,11 @@
 pub mod account;
+pub mod account_index;
+pub mod backup;
 pub mod cleanup;
+pub mod history;
 pub mod path_config;
 pub mod restore;
 pub mod starter;
+pub mod switch;
+pub mod targets;
+pub mod token_status;
@@ -1,16 +1,20 @@
 //! Antigravity 路径配置管理模块
-//! 负责保存和读取用户自定义的 Antigravity 可执行文件路径
+//! 负责保存和读取用户自定义的 Antigravity 可执行文件路径与数据目录
 
 use crate::directories;
+use rusqlite::{Connection, OpenFlags};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Antigravity 路径配置
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AntigravityPathConfig {
     /// 用户自定义的 Antigravity 可执行文件路径
     pub custom_executable_path: Option<String>,
+    /// 用户自定义的 Antigravity 数据目录（`--user-data-dir` 或便携版的 data 目录）
+    #[serde(default)]
+    pub custom_data_dir: Option<String>,
 }
 
 /// 获取配置文件路径
@@ -64,6 +68,75 @@ pub fn get_custom_executable_path() -> Result<Option<String>, String> {
     Ok(config.custom_executable_path)
 }
 
+/// 保存用户自定义数据目录
+pub fn save_custom_data_dir(path: String) -> Result<(), String> {
+    let config_file = get_config_file_path();
+    let mut config = read_config().unwrap_or_default();
+
+    config.custom_data_dir = Some(path);
+    write_config(&config_file, &config)?;
+
+    tracing::info!("✅ 已保存自定义 Antigravity 数据目录");
+    Ok(())
+}
+
+/// 清除自定义数据目录，恢复自动检测
+pub fn clear_custom_data_dir() -> Result<(), String> {
+    let config_file = get_config_file_path();
+    let mut config = read_config().unwrap_or_default();
+
+    config.custom_data_dir = None;
+    write_config(&config_file, &config)?;
+
+    tracing::info!("✅ 已清除自定义 Antigravity 数据目录");
+    Ok(())
+}
+
+/// 从配置文件读取自定义数据目录
+pub fn get_custom_data_dir() -> Result<Option<String>, String> {
+    let config = read_config()?;
+    Ok(config.custom_data_dir)
+}
+
+/// 在数据目录中定位 globalStorage 目录
+///
+/// 依次尝试：目录本身、`User/globalStorage`（`--user-data-dir`）、
+/// `user-data/User/globalStorage`（便携版 data 目录）
+pub fn resolve_global_storage_dir(data_dir: &Path) -> Option<PathBuf> {
+    [
+        data_dir.to_path_buf(),
+        data_dir.join("User").join("globalStorage"),
+        data_dir
+            .join("user-data")
+            .join("User")
+            .join("globalStorage"),
+    ]
+    .into_iter()
+    .find(|dir| dir.join("state.vscdb").is_file())
+}
+
+/// 验证数据目录：必须包含可读取且有 ItemTable 表的 state.vscdb，返回 globalStorage 目录
+pub fn validate_data_dir(path: &str) -> Result<PathBuf, String> {
+    let global_storage = resolve_global_storage_dir(Path::new(path))
+        .ok_or_else(|| format!("目录 '{}' 中未找到 state.vscdb", path))?;
+    let db_path = global_storage.join("state.vscdb");
+
+    let conn = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("无法打开 {}: {}", db_path.display(), e))?;
+    let has_item_table: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'ItemTable')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("无法读取 {}: {}", db_path.display(), e))?;
+    if !has_item_table {
+        return Err(format!("{} 中没有 ItemTable 表", db_path.display()));
+    }
+
+    Ok(global_storage)
+}
+
 /// 清除自定义路径配置
 #[allow(dead_code)]
 pub fn clear_custom_path() -> Result<(), String> {
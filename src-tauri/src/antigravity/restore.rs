@@ -1,15 +1,291 @@
 // Antigravity 用户数据恢复模块
 // 负责将备份数据恢复到 Antigravity 应用数据库
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde_json::Value;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 // 导入相关模块
 use crate::constants::database;
+use crate::error::AgentError;
 use crate::platform;
 
+/// 切换账户时需要快照/恢复的 ItemTable 字段
+const SNAPSHOT_KEYS: [&str; 2] = [database::AGENT_STATE, database::AUTH_STATUS];
+
+/// 附加备份库时使用的 schema 名称
+const BACKUP_SCHEMA: &str = "backup";
+
+/// 数据库忙时的等待时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 单事务恢复的结果
+#[derive(Debug, Clone, Copy)]
+pub struct RestoreReport {
+    /// 主库写入的行数
+    pub main_rows: usize,
+    /// 备份库写入的行数（备份库不存在时为 None）
+    pub backup_rows: Option<usize>,
+}
+
+/// 切换前的数据库快照，用于失败时回滚到原账户
+#[derive(Debug, Clone)]
+pub struct DatabaseSnapshot {
+    db_path: PathBuf,
+    /// 主库中 SNAPSHOT_KEYS 对应的原始值（None 表示原本不存在）
+    rows: Vec<(String, Option<String>)>,
+    /// state.vscdb.backup 中 SNAPSHOT_KEYS 对应的原始值（备份库不存在或无法附加时为 None）
+    backup_rows: Option<Vec<(String, Option<String>)>>,
+}
+
+impl DatabaseSnapshot {
+    /// 在修改任何数据前采集快照
+    pub fn capture(db_path: &Path) -> Result<Self, AgentError> {
+        let conn = open_connection(db_path)?;
+        let rows = read_snapshot_rows(&conn, "main")?;
+        let backup_rows = if attach_backup_db(&conn, db_path) {
+            Some(read_snapshot_rows(&conn, BACKUP_SCHEMA)?)
+        } else {
+            None
+        };
+
+        tracing::info!(
+            target: "restore::snapshot",
+            captured_rows = rows.iter().filter(|(_, v)| v.is_some()).count(),
+            has_backup = backup_rows.is_some(),
+            "已采集切换前快照"
+        );
+
+        Ok(Self {
+            db_path: db_path.to_path_buf(),
+            rows,
+            backup_rows,
+        })
+    }
+
+    /// 将数据库恢复到快照时的状态
+    ///
+    /// 与恢复时相同，主库与备份库通过 ATTACH 在同一事务中写回快照中的行
+    pub fn rollback(&self) -> Result<(), AgentError> {
+        tracing::warn!(target: "restore::rollback", "开始回滚到切换前的账户状态");
+
+        let mut conn = open_connection(&self.db_path)?;
+        let backup_rows = match &self.backup_rows {
+            Some(rows) if attach_backup_db(&conn, &self.db_path) => Some(rows),
+            Some(_) => {
+                tracing::warn!(target: "restore::rollback", "无法附加 state.vscdb.backup，仅回滚主库");
+                None
+            }
+            None => None,
+        };
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| AgentError::sqlite("开启回滚事务失败", e))?;
+        restore_snapshot_rows(&tx, "main", &self.rows)?;
+        if let Some(rows) = backup_rows {
+            restore_snapshot_rows(&tx, BACKUP_SCHEMA, rows)?;
+        }
+        tx.commit()
+            .map_err(|e| AgentError::sqlite("提交回滚事务失败", e))?;
+
+        tracing::info!(target: "restore::rollback", "✅ 已回滚到切换前的账户状态");
+        Ok(())
+    }
+}
+
+/// 读取指定 schema 中 SNAPSHOT_KEYS 对应的值
+fn read_snapshot_rows(
+    conn: &Connection,
+    schema: &str,
+) -> Result<Vec<(String, Option<String>)>, AgentError> {
+    let mut rows = Vec::with_capacity(SNAPSHOT_KEYS.len());
+    for key in SNAPSHOT_KEYS {
+        let value: Option<String> = conn
+            .query_row(
+                &format!("SELECT value FROM {}.ItemTable WHERE key = ?", schema),
+                [key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AgentError::sqlite(format!("读取 {}.{} 快照失败", schema, key), e))?;
+        rows.push((key.to_string(), value));
+    }
+    Ok(rows)
+}
+
+/// 将快照中的行写回指定 schema，原本不存在的键会被删除
+fn restore_snapshot_rows(
+    conn: &Connection,
+    schema: &str,
+    rows: &[(String, Option<String>)],
+) -> Result<(), AgentError> {
+    for (key, value) in rows {
+        match value {
+            Some(value) => conn.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {}.ItemTable (key, value) VALUES (?, ?)",
+                    schema
+                ),
+                params![key, value],
+            ),
+            None => conn.execute(
+                &format!("DELETE FROM {}.ItemTable WHERE key = ?", schema),
+                [key],
+            ),
+        }
+        .map_err(|e| AgentError::sqlite(format!("回滚 {}.{} 失败", schema, key), e))?;
+    }
+    Ok(())
+}
+
+/// state.vscdb 对应的备份库路径
+fn backup_db_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("vscdb.backup")
+}
+
+fn open_connection(db_path: &Path) -> Result<Connection, AgentError> {
+    let conn = Connection::open(db_path)
+        .map_err(|e| AgentError::sqlite(format!("连接数据库失败 ({})", db_path.display()), e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| AgentError::sqlite("设置数据库超时失败", e))?;
+    Ok(conn)
+}
+
+/// 附加备份库，备份库不存在或不含 ItemTable 时返回 false
+fn attach_backup_db(conn: &Connection, db_path: &Path) -> bool {
+    let backup_db = backup_db_path(db_path);
+    if !backup_db.exists() {
+        return false;
+    }
+
+    if let Err(e) = conn.execute(
+        &format!("ATTACH DATABASE ? AS {}", BACKUP_SCHEMA),
+        [backup_db.to_string_lossy()],
+    ) {
+        tracing::warn!(target: "restore::database", error = %e, "附加 state.vscdb.backup 失败，跳过备份库");
+        return false;
+    }
+
+    let has_table = conn
+        .query_row(
+            &format!(
+                "SELECT 1 FROM {}.sqlite_master WHERE type = 'table' AND name = 'ItemTable'",
+                BACKUP_SCHEMA
+            ),
+            [],
+            |_| Ok(()),
+        )
+        .optional()
+        .map(|found| found.is_some())
+        .unwrap_or(false);
+
+    if !has_table {
+        tracing::warn!(target: "restore::database", "state.vscdb.backup 中没有 ItemTable，跳过备份库");
+        let _ = conn.execute(&format!("DETACH DATABASE {}", BACKUP_SCHEMA), []);
+    }
+
+    has_table
+}
+
+/// 在单个事务内写回 jetskiStateSync.agentManagerInitState 并删除 antigravityAuthStatus
+///
+/// 主库与备份库（存在时）通过 ATTACH 放在同一事务中，任一写入失败都不会留下半完成的状态
+pub fn restore_agent_state(db_path: &Path, agent_state: &str) -> Result<RestoreReport, AgentError> {
+    tracing::info!(target: "restore::database", "开始恢复数据库（仅 jetskiStateSync.agentManagerInitState，移除 antigravityAuthStatus）");
+
+    let mut conn = open_connection(db_path)?;
+    let has_backup = attach_backup_db(&conn, db_path);
+
+    let mut schemas = vec!["main"];
+    if has_backup {
+        schemas.push(BACKUP_SCHEMA);
+    }
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| AgentError::sqlite("开启恢复事务失败", e))?;
+
+    let mut counts = Vec::with_capacity(schemas.len());
+    for schema in &schemas {
+        let rows = tx
+            .execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {}.ItemTable (key, value) VALUES (?, ?)",
+                    schema
+                ),
+                params![database::AGENT_STATE, agent_state],
+            )
+            .map_err(|e| {
+                AgentError::sqlite(format!("写入 {}.{} 失败", schema, database::AGENT_STATE), e)
+            })?;
+        tx.execute(
+            &format!("DELETE FROM {}.ItemTable WHERE key = ?", schema),
+            [database::AUTH_STATUS],
+        )
+        .map_err(|e| {
+            AgentError::sqlite(format!("删除 {}.{} 失败", schema, database::AUTH_STATUS), e)
+        })?;
+        counts.push(rows);
+    }
+
+    tx.commit()
+        .map_err(|e| AgentError::sqlite("提交恢复事务失败", e))?;
+
+    let report = RestoreReport {
+        main_rows: counts[0],
+        backup_rows: counts.get(1).copied(),
+    };
+    tracing::info!(target: "restore::database", main_rows = report.main_rows, backup_rows = ?report.backup_rows, "✅ 恢复事务已提交");
+    Ok(report)
+}
+
+/// 读取账户文件中的 jetskiStateSync.agentManagerInitState
+pub fn read_account_agent_state(account_file_path: &Path) -> Result<String, AgentError> {
+    if !account_file_path.exists() {
+        return Err(AgentError::AccountNotFound(
+            account_file_path.display().to_string(),
+        ));
+    }
+
+    let content = fs::read_to_string(account_file_path)
+        .map_err(|e| AgentError::io(account_file_path.display(), e))?;
+    let account_data: Value = serde_json::from_str(&content).map_err(|e| {
+        AgentError::AccountNotFound(format!("{}: {}", account_file_path.display(), e))
+    })?;
+
+    account_data
+        .get(database::AGENT_STATE)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| {
+            AgentError::AccountNotFound(format!(
+                "{} 缺少 {} 字段",
+                account_file_path.display(),
+                database::AGENT_STATE
+            ))
+        })
+}
+
+/// 获取 Antigravity 状态数据库路径
+///
+/// 选择了活动目标时只使用该目标；否则使用自动检测的主路径，不可用时回退到候选路径
+pub fn resolve_antigravity_db_path() -> Result<PathBuf, AgentError> {
+    if let Some(target) = crate::antigravity::targets::active_target()? {
+        return Ok(target.db_path());
+    }
+
+    match platform::get_antigravity_db_path() {
+        Some(p) => Ok(p),
+        None => platform::get_all_antigravity_db_paths()
+            .into_iter()
+            .next()
+            .ok_or(AgentError::NotInstalled),
+    }
+}
+
 /// 恢复 Antigravity 状态（精简版）
 ///
 /// 从账户文件恢复 jetskiStateSync.agentManagerInitState，并删除 antigravityAuthStatus
@@ -19,99 +295,35 @@ use crate::platform;
 ///
 /// # 返回
 /// - `Ok(message)`: 成功消息
-/// - `Err(message)`: 错误信息
+/// - `Err(AgentError)`: 错误信息
 pub async fn save_antigravity_account_to_file(
     account_file_path: PathBuf,
-) -> Result<String, String> {
+) -> Result<String, AgentError> {
     println!("📂 账户文件: {}", account_file_path.display());
 
-    if !account_file_path.exists() {
-        return Err(format!("账户文件不存在: {}", account_file_path.display()));
-    }
-
-    let content = fs::read_to_string(&account_file_path).map_err(|e| e.to_string())?;
-    let account_data: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let agent_state = read_account_agent_state(&account_file_path)?;
 
     println!("✅ 账户文件读取成功");
 
-    let app_data = match platform::get_antigravity_db_path() {
-        Some(p) => p,
-        None => {
-            let possible_paths = platform::get_all_antigravity_db_paths();
-            if possible_paths.is_empty() {
-                return Err("未找到 Antigravity 安装位置".to_string());
-            }
-            possible_paths[0].clone()
-        }
-    };
+    restore_agent_state_to_database(&agent_state)
+}
+
+/// 将 jetskiStateSync.agentManagerInitState 写入 Antigravity 数据库（主库与备份库）
+pub fn restore_agent_state_to_database(agent_state: &str) -> Result<String, AgentError> {
+    let app_data = resolve_antigravity_db_path()?;
 
     // 确保数据库目录存在
     if let Some(parent) = app_data.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("创建数据库目录失败: {}", e))?;
+        fs::create_dir_all(parent).map_err(|e| AgentError::io("创建数据库目录失败", e))?;
     }
 
-    let mut msg = String::new();
-
-    // 内联恢复逻辑：仅写回 AGENT_STATE 并删除 AUTH_STATUS
-    let restore_db = |db_path: &PathBuf, db_name: &str| -> Result<usize, String> {
-        tracing::info!(target: "restore::database", db_name = %db_name, "开始恢复数据库（仅 jetskiStateSync.agentManagerInitState，移除 antigravityAuthStatus）");
-        let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
-
-        let mut restored_count = 0;
-
-        if let Some(val) = account_data.get(database::AGENT_STATE) {
-            if let Some(val_str) = val.as_str() {
-                match conn.execute(
-                    "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
-                    params![database::AGENT_STATE, val_str],
-                ) {
-                    Ok(_) => {
-                        tracing::debug!(target: "restore::database", key = %database::AGENT_STATE, "注入数据成功");
-                        restored_count += 1;
-                    }
-                    Err(e) => {
-                        tracing::error!(target: "restore::database", key = %database::AGENT_STATE, error = %e, "写入数据失败");
-                    }
-                }
-            } else {
-                tracing::warn!(target: "restore::database", key = %database::AGENT_STATE, "字段不是字符串类型，跳过");
-            }
-        } else {
-            tracing::debug!(target: "restore::database", key = %database::AGENT_STATE, "备份中未找到字段，跳过");
-        }
+    // 主库与备份库在同一事务内写入，任一失败整体回滚
+    println!("📊 恢复 state.vscdb 与 state.vscdb.backup（单事务）");
+    let report = restore_agent_state(&app_data, agent_state)?;
 
-        if let Err(e) = conn.execute(
-            "DELETE FROM ItemTable WHERE key = ?",
-            [database::AUTH_STATUS],
-        ) {
-            tracing::warn!(target: "restore::database", error = %e, "删除 antigravityAuthStatus 失败（忽略）");
-        } else {
-            tracing::debug!(target: "restore::database", "已删除 antigravityAuthStatus");
-        }
-
-        Ok(restored_count)
-    };
-
-    // 恢复主库
-    println!("📊 步骤1: 恢复 state.vscdb 数据库");
-    match restore_db(&app_data, "state.vscdb") {
-        Ok(count) => {
-            let status = format!("主库恢复 {} 项", count);
-            println!("  ✅ {}", status);
-            msg.push_str(&status);
-        }
-        Err(e) => return Err(e),
-    }
-
-    // 恢复账户库（如果有）
-    println!("💾 步骤2: 恢复 state.vscdb.backup");
-    let backup_db = app_data.with_extension("vscdb.backup");
-    if backup_db.exists() {
-        if let Ok(count) = restore_db(&backup_db, "state.vscdb.backup") {
-            let status = format!("; 账户库恢复 {} 项", count);
-            println!("  ✅ {}", status);
-            msg.push_str(&status);
-        }
+    let mut msg = format!("主库恢复 {} 项", report.main_rows);
+    if let Some(count) = report.backup_rows {
+        msg.push_str(&format!("; 账户库恢复 {} 项", count));
     } else {
         println!("  ℹ️ 账户数据库不存在，跳过");
     }
@@ -2,32 +2,275 @@
 ///
 /// 提供跨平台的 Antigravity 应用程序启动功能
 /// 支持 Windows、macOS 和 Linux 系统
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::antigravity::targets::{self, AntigravityTarget, DisplayServer, LaunchProfile};
+use crate::error::AgentError;
+
+/// 等待 Antigravity 进程出现的超时时间
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(30);
+/// 进程出现后需保持存活的时间
+const LAUNCH_GRACE_PERIOD: Duration = Duration::from_secs(3);
+/// 检查进程的间隔
+const LAUNCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 启动日志文件名（位于日志目录）
+const LAUNCH_LOG_FILE: &str = "antigravity-launch.log";
+/// 错误信息中附带的启动日志行数
+const LAUNCH_LOG_TAIL_LINES: usize = 20;
+
+/// 一次启动使用的参数、环境变量与工作目录（由启动配置生成）
+#[derive(Debug, Clone, Default)]
+struct LaunchOptions {
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    working_dir: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    /// 参数顺序：--user-data-dir、显示协议、自定义参数，工作区文件夹放在最后
+    fn new(profile: &LaunchProfile, user_data_dir: Option<&Path>) -> Self {
+        let mut args = Vec::new();
+        if let Some(dir) = user_data_dir {
+            args.push("--user-data-dir".to_string());
+            args.push(dir.to_string_lossy().to_string());
+        }
+
+        let mut env: Vec<(String, String)> = profile
+            .env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        if cfg!(target_os = "linux") {
+            match profile.display_server {
+                DisplayServer::Auto => {}
+                DisplayServer::Wayland => {
+                    args.push("--ozone-platform=wayland".to_string());
+                    args.push(
+                        "--enable-features=UseOzonePlatform,WaylandWindowDecorations".to_string(),
+                    );
+                    env.push((
+                        "ELECTRON_OZONE_PLATFORM_HINT".to_string(),
+                        "wayland".to_string(),
+                    ));
+                }
+                DisplayServer::X11 => {
+                    args.push("--ozone-platform=x11".to_string());
+                    env.push((
+                        "ELECTRON_OZONE_PLATFORM_HINT".to_string(),
+                        "x11".to_string(),
+                    ));
+                }
+            }
+        }
+
+        args.extend(profile.args.iter().cloned());
+        if let Some(folder) = &profile.workspace_folder {
+            args.push(folder.to_string_lossy().to_string());
+        }
+
+        Self {
+            args,
+            env,
+            working_dir: profile.working_dir.clone(),
+        }
+    }
+
+    /// 应用到直接执行的命令
+    fn apply(&self, cmd: &mut Command) {
+        #[cfg(target_os = "linux")]
+        strip_appimage_env(cmd);
+
+        cmd.args(&self.args)
+            .envs(self.env.iter().map(|(k, v)| (k, v)));
+        if let Some(dir) = &self.working_dir {
+            cmd.current_dir(dir);
+        }
+    }
+}
+
+/// 已启动的进程
+struct Spawned {
+    child: Child,
+    /// 启动的可执行文件（macOS 上为 .app），用于识别由启动器拉起的进程
+    executable: Option<PathBuf>,
+}
+
+/// 启动失败
+#[derive(Debug)]
+pub struct LaunchFailure {
+    pub error: AgentError,
+    /// 本次启动产生的进程是否都已结束；为 false 时不能改写数据库
+    pub stopped: bool,
+}
+
+/// 一次启动：子进程、可执行文件与启动前已存在的进程
+struct Launch {
+    spawned: Spawned,
+    existing: HashSet<u32>,
+}
+
+impl Launch {
+    /// 本次启动产生的、仍在运行的进程
+    fn running_pids(&mut self) -> Vec<u32> {
+        // 回收已退出的启动器，避免僵尸进程
+        let _ = self.spawned.child.try_wait();
+        crate::platform::find_launched_pids(
+            self.spawned.child.id(),
+            self.spawned.executable.as_deref(),
+            &self.existing,
+        )
+    }
+
+    /// 启动成功：在后台等待子进程退出并回收
+    fn detach(self) {
+        let mut child = self.spawned.child;
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+    }
+
+    /// 启动失败：结束本次启动产生的所有进程并回收子进程，返回是否全部结束
+    async fn stop(mut self) -> bool {
+        let pids = self.running_pids();
+        let _ = self.spawned.child.kill();
+        let _ = self.spawned.child.wait();
+        if pids.is_empty() {
+            return true;
+        }
+
+        tracing::warn!(pids = ?pids, "🛑 启动失败，结束本次启动的进程");
+        crate::platform::wait_for_processes_exit(&pids, Duration::ZERO)
+            .await
+            .all_exited()
+    }
+}
 
 /// 启动 Antigravity 应用程序（主入口函数）
 ///
+/// 启动后等待本次启动的进程（子进程及其后代，或启动后新出现的同一可执行文件的进程）
+/// 在宽限期内保持存活，才视为启动成功
+///
 /// # 返回值
 ///
 /// * `Ok(String)` - 启动成功，返回成功消息
-/// * `Err(String)` - 启动失败，返回错误信息
+/// * `Err(AgentError::LaunchFailed)` - 启动失败，附带尝试过的方法或启动日志
 ///
 /// # 示例
 ///
-/// ```rust
-/// match antigravity_starter::start_antigravity() {
+/// ```no_run
+/// # async fn example() {
+/// use antigravity_agent_lib::antigravity::starter;
+///
+/// match starter::start_antigravity().await {
 ///     Ok(msg) => println!("启动成功: {}", msg),
 ///     Err(e) => println!("启动失败: {}", e),
 /// }
+/// # }
 /// ```
-pub fn start_antigravity() -> Result<String, String> {
+pub async fn start_antigravity() -> Result<String, AgentError> {
+    launch_antigravity().await.map_err(|failure| failure.error)
+}
+
+/// 启动 Antigravity；失败时先结束本次启动的进程，并报告是否已全部结束
+pub async fn launch_antigravity() -> Result<String, LaunchFailure> {
+    let existing = crate::platform::running_pids();
+    let spawned = spawn_antigravity().map_err(|error| LaunchFailure {
+        error,
+        stopped: true,
+    })?;
+    let mut launch = Launch { spawned, existing };
+
+    match wait_for_launch(&mut launch, LAUNCH_TIMEOUT, LAUNCH_GRACE_PERIOD).await {
+        Ok(message) => {
+            launch.detach();
+            Ok(message)
+        }
+        Err(error) => {
+            let stopped = launch.stop().await;
+            if !stopped {
+                tracing::error!("❌ 启动失败后仍有进程未能结束");
+            }
+            Err(LaunchFailure { error, stopped })
+        }
+    }
+}
+
+/// 等待本次启动的进程出现并保持存活
+async fn wait_for_launch(
+    launch: &mut Launch,
+    timeout: Duration,
+    grace: Duration,
+) -> Result<String, AgentError> {
+    let start = Instant::now();
+
+    loop {
+        if !launch.running_pids().is_empty() {
+            // 进程出现后再观察一段时间，排除启动即崩溃的情况
+            tokio::time::sleep(grace).await;
+            let pids = launch.running_pids();
+            if pids.is_empty() {
+                return Err(AgentError::LaunchFailed(format!(
+                    "Antigravity 启动后 {}ms 内退出{}",
+                    grace.as_millis(),
+                    launch_log_tail()
+                )));
+            }
+
+            tracing::info!(pids = ?pids, "✅ Antigravity 已启动");
+            return Ok("Antigravity 已启动".to_string());
+        }
+
+        // 启动器（open、flatpak、脚本）正常退出后继续等待实际进程；异常退出则直接失败
+        if let Ok(Some(status)) = launch.spawned.child.try_wait() {
+            if !status.success() {
+                return Err(AgentError::LaunchFailed(format!(
+                    "启动进程异常退出（{}）{}",
+                    status,
+                    launch_log_tail()
+                )));
+            }
+        }
+
+        if start.elapsed() >= timeout {
+            return Err(AgentError::LaunchFailed(format!(
+                "{}s 内未检测到 Antigravity 进程{}",
+                timeout.as_secs(),
+                launch_log_tail()
+            )));
+        }
+        tokio::time::sleep(LAUNCH_POLL_INTERVAL).await;
+    }
+}
+
+/// 按活动目标、自定义路径、自动检测的顺序启动进程
+fn spawn_antigravity() -> Result<Spawned, AgentError> {
+    // 选择了活动目标时只启动该目标，不再猜测
+    if let Some(target) = targets::active_target()? {
+        return start_target(&target);
+    }
+
+    // 使用自定义数据目录时需要通过 --user-data-dir 指定
+    let options = LaunchOptions::new(
+        &targets::load_targets()?.default_launch_profile,
+        custom_user_data_dir().as_deref(),
+    );
+
     // 优先使用用户配置的可执行文件路径
     if let Ok(Some(custom_exec)) = crate::antigravity::path_config::get_custom_executable_path() {
         let path = PathBuf::from(&custom_exec);
         if path.exists() && path.is_file() {
             tracing::info!("📁 使用自定义 Antigravity 可执行文件: {}", custom_exec);
-            return try_start_from_path(&path)
-                .map_err(|e| format!("无法启动自定义 Antigravity: {}. 请检查路径是否正确", e));
+            return try_start_from_path(&path, &options).map_err(|e| {
+                AgentError::LaunchFailed(format!(
+                    "无法启动自定义 Antigravity: {}. 请检查路径是否正确",
+                    e
+                ))
+            });
         } else {
             tracing::warn!("⚠️ 自定义可执行文件路径无效: {}", custom_exec);
         }
@@ -35,25 +278,56 @@ pub fn start_antigravity() -> Result<String, String> {
 
     // 回退到自动检测
     match std::env::consts::OS {
-        "windows" => start_antigravity_windows(),
-        "macos" => start_antigravity_macos(),
-        "linux" => start_antigravity_linux(),
+        "windows" => start_antigravity_windows(&options),
+        "macos" => start_antigravity_macos(&options),
+        "linux" => start_antigravity_linux(&options),
         _ => Err("不支持的操作系统".to_string()),
     }
+    .map_err(AgentError::LaunchFailed)
+}
+
+/// 启动指定目标：使用目标的可执行文件与启动配置，并通过 --user-data-dir 指向目标的数据目录
+fn start_target(target: &AntigravityTarget) -> Result<Spawned, AgentError> {
+    let exe = match &target.executable_path {
+        Some(path) => path.clone(),
+        None => detect_antigravity_executable().ok_or_else(|| {
+            AgentError::LaunchFailed(format!(
+                "目标 {} 未设置可执行文件，且未能自动检测到 Antigravity",
+                target.name
+            ))
+        })?,
+    };
+    if !exe.exists() {
+        return Err(AgentError::LaunchFailed(format!(
+            "目标 {} 的可执行文件不存在: {}",
+            target.name,
+            exe.display()
+        )));
+    }
+
+    tracing::info!("🎯 启动目标 {}: {}", target.name, exe.display());
+    let options = LaunchOptions::new(&target.launch_profile, Some(&target.data_dir));
+    try_start_from_path(&exe, &options)
+        .map_err(|e| AgentError::LaunchFailed(format!("无法启动目标 {}: {}", target.name, e)))
+}
+
+/// 用户自定义数据目录对应的 --user-data-dir（未配置时为 None）
+fn custom_user_data_dir() -> Option<PathBuf> {
+    // globalStorage 的上两级即 --user-data-dir 目录
+    crate::platform::get_custom_antigravity_data_dir()
+        .and_then(|dir| dir.parent()?.parent().map(|root| root.to_path_buf()))
 }
 
 /// 在 Windows 平台启动 Antigravity
-fn start_antigravity_windows() -> Result<String, String> {
+fn start_antigravity_windows(options: &LaunchOptions) -> Result<Spawned, String> {
     let mut errors = Vec::new();
     let antigravity_paths = crate::path_utils::AppPaths::antigravity_executable_paths();
 
     // 尝试所有推测的路径
     for path in &antigravity_paths {
         if path.exists() {
-            match try_start_from_path(path) {
-                Ok(_) => {
-                    return Ok("Antigravity 已启动".to_string());
-                }
+            match try_start_from_path(path, options) {
+                Ok(spawned) => return Ok(spawned),
                 Err(e) => {
                     errors.push(format!("{}: {}", path.display(), e));
                 }
@@ -65,8 +339,8 @@ fn start_antigravity_windows() -> Result<String, String> {
 
     // 尝试从系统 PATH 启动命令
     let commands = vec!["Antigravity", "antigravity"];
-    match try_start_from_commands(commands) {
-        Ok(msg) => Ok(msg),
+    match try_start_from_commands(commands, options) {
+        Ok(spawned) => Ok(spawned),
         Err(e) => {
             errors.push(e);
             Err(format!(
@@ -78,17 +352,15 @@ fn start_antigravity_windows() -> Result<String, String> {
 }
 
 /// 在 macOS 平台启动 Antigravity
-fn start_antigravity_macos() -> Result<String, String> {
+fn start_antigravity_macos(options: &LaunchOptions) -> Result<Spawned, String> {
     let mut errors = Vec::new();
     let antigravity_paths = crate::path_utils::AppPaths::antigravity_executable_paths();
 
     // 尝试所有推测的路径
     for path in &antigravity_paths {
         if path.exists() {
-            match try_start_from_path(path) {
-                Ok(_) => {
-                    return Ok("Antigravity 已启动".to_string());
-                }
+            match try_start_from_path(path, options) {
+                Ok(spawned) => return Ok(spawned),
                 Err(e) => {
                     errors.push(format!("{}: {}", path.display(), e));
                 }
@@ -100,8 +372,8 @@ fn start_antigravity_macos() -> Result<String, String> {
 
     // 尝试系统 PATH 命令
     let commands = vec!["Antigravity", "antigravity"];
-    match try_start_from_commands(commands) {
-        Ok(msg) => Ok(msg),
+    match try_start_from_commands(commands, options) {
+        Ok(spawned) => Ok(spawned),
         Err(e) => {
             errors.push(e);
             Err(format!(
@@ -122,73 +394,185 @@ fn start_antigravity_macos() -> Result<String, String> {
 }
 
 /// 在 Linux 平台启动 Antigravity
-fn start_antigravity_linux() -> Result<String, String> {
-    let antigravity_path = std::path::PathBuf::from("/usr/share/antigravity/antigravity");
+///
+/// 依次尝试 `detect_antigravity_executable` 使用的候选路径，最后尝试系统 PATH 命令
+fn start_antigravity_linux(options: &LaunchOptions) -> Result<Spawned, String> {
+    let mut errors = Vec::new();
+    let antigravity_paths = crate::path_utils::AppPaths::antigravity_executable_paths();
+
+    // 尝试所有推测的路径
+    for path in &antigravity_paths {
+        if path.exists() {
+            match try_start_from_path(path, options) {
+                Ok(spawned) => {
+                    tracing::info!("✅ 已从 {} 启动 Antigravity", path.display());
+                    return Ok(spawned);
+                }
+                Err(e) => {
+                    errors.push(format!("{}: {}", path.display(), e));
+                }
+            }
+        } else {
+            errors.push(format!("{}: 文件不存在", path.display()));
+        }
+    }
+
+    // 尝试系统 PATH 命令
+    let commands = vec!["antigravity", "Antigravity"];
+    match try_start_from_commands(commands, options) {
+        Ok(spawned) => Ok(spawned),
+        Err(e) => {
+            errors.push(e);
+            Err(format!(
+                "无法启动Antigravity。请先安装 Antigravity 应用，或在设置中指定可执行文件。\n尝试的方法：\n{}",
+                errors.join("\n")
+            ))
+        }
+    }
+}
 
-    if !antigravity_path.exists() {
-        return Err("Antigravity 未安装。请先安装 Antigravity 应用。".to_string());
+/// 构建 Linux 上的启动命令：Flatpak 导出的命令通过 `flatpak run` 启动，AppImage 需要可执行权限
+#[cfg(target_os = "linux")]
+fn linux_command(path: &Path, options: &LaunchOptions) -> Result<Command, String> {
+    // Flatpak 导出目录中的文件名即应用 ID，沙箱内的环境变量需通过 --env 传递
+    if let Some(app_id) = flatpak_app_id(path) {
+        let mut cmd = Command::new("flatpak");
+        cmd.arg("run");
+        for (key, value) in &options.env {
+            cmd.arg(format!("--env={key}={value}"));
+        }
+        cmd.arg(app_id).args(&options.args);
+        if let Some(dir) = &options.working_dir {
+            cmd.current_dir(dir);
+        }
+        strip_appimage_env(&mut cmd);
+        return Ok(cmd);
     }
 
-    let mut cmd = std::process::Command::new(&antigravity_path);
+    let is_appimage = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("appimage"));
+    if is_appimage {
+        ensure_executable(path)?;
+    }
 
-    // 设置桌面环境变量
-    cmd.env("XDG_SESSION_TYPE", "wayland");
+    let mut cmd = Command::new(path);
 
-    // 如果当前有 DISPLAY，使用它；否则尝试常见值
-    if let Ok(display) = std::env::var("DISPLAY") {
-        cmd.env("DISPLAY", display);
-    } else {
+    // 如果当前有 DISPLAY，使用它；Wayland 会话之外再尝试常见值
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
         cmd.env("DISPLAY", ":0");
     }
 
-    // 设置其他必要的环境变量
-    if let Ok(xauthority) = std::env::var("XAUTHORITY") {
-        cmd.env("XAUTHORITY", xauthority);
+    // 没有 FUSE 时 AppImage 无法挂载，改为解压后运行
+    if is_appimage && !Path::new("/dev/fuse").exists() {
+        cmd.env("APPIMAGE_EXTRACT_AND_RUN", "1");
+    }
+
+    // 启动配置中的环境变量可以覆盖以上默认值
+    options.apply(&mut cmd);
+    Ok(cmd)
+}
+
+/// `.../flatpak/exports/bin/<app-id>` 形式的路径返回应用 ID
+#[cfg(target_os = "linux")]
+fn flatpak_app_id(path: &Path) -> Option<String> {
+    let bin_dir = path.parent()?;
+    let exports_dir = bin_dir.parent()?;
+    let is_flatpak_export = bin_dir.file_name()? == "bin"
+        && exports_dir.file_name()? == "exports"
+        && exports_dir.parent()?.file_name()? == "flatpak";
+    if !is_flatpak_export {
+        return None;
+    }
+
+    // 导出的命令是调用 `flatpak run` 的脚本，应用 ID 以脚本中的为准
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|script| {
+            script
+                .split_whitespace()
+                .skip_while(|word| *word != "run")
+                .skip(1)
+                .find(|word| !word.starts_with('-'))
+                .map(|word| word.trim_matches(['"', '\'']).to_string())
+        })
+        .or_else(|| Some(path.file_name()?.to_string_lossy().to_string()))
+}
+
+/// 下载的 AppImage 通常没有可执行权限
+#[cfg(target_os = "linux")]
+fn ensure_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = std::fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?;
+    let mut permissions = metadata.permissions();
+    if permissions.mode() & 0o111 == 0 {
+        permissions.set_mode(permissions.mode() | 0o755);
+        std::fs::set_permissions(path, permissions)
+            .map_err(|e| format!("无法添加可执行权限: {}", e))?;
+        tracing::info!("🔧 已为 AppImage 添加可执行权限: {}", path.display());
     }
+    Ok(())
+}
 
-    match cmd.spawn() {
-        Ok(_) => Ok("Antigravity 已启动".to_string()),
-        Err(e) => Err(format!("启动 Antigravity 失败: {}", e)),
+/// 本程序以 AppImage 运行时，运行时注入的变量会让 Antigravity 加载错误的库
+#[cfg(target_os = "linux")]
+fn strip_appimage_env(cmd: &mut Command) {
+    if std::env::var_os("APPDIR").is_some() {
+        for key in ["APPDIR", "APPIMAGE", "ARGV0", "OWD", "LD_LIBRARY_PATH"] {
+            cmd.env_remove(key);
+        }
     }
 }
 
 /// 尝试从指定路径启动应用程序
-fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
+fn try_start_from_path(path: &Path, options: &LaunchOptions) -> Result<Spawned, String> {
+    spawn_from_path(path, options).map(|child| Spawned {
+        child,
+        executable: Some(path.to_path_buf()),
+    })
+}
+
+fn spawn_from_path(path: &Path, options: &LaunchOptions) -> Result<Child, String> {
     // macOS 需要特殊处理：使用 open 命令启动 .app 应用
     #[cfg(target_os = "macos")]
     {
         // 确保路径是 .app bundle 格式
         let app_bundle_path = if path.to_str().unwrap_or("").contains(".app") {
-            path.clone()
+            path.to_path_buf()
         } else {
             return Err(format!("路径不是有效的 .app bundle: {}", path.display()));
         };
 
         // 方法1: 尝试不带 -n 参数的 open 命令（更兼容）
-        match Command::new("open")
+        // open 启动的应用不继承当前进程的环境变量，需通过 --env 传递
+        let mut open_args: Vec<String> = Vec::new();
+        for (key, value) in &options.env {
+            open_args.push("--env".to_string());
+            open_args.push(format!("{key}={value}"));
+        }
+        if !options.args.is_empty() {
+            open_args.push("--args".to_string());
+            open_args.extend(options.args.iter().cloned());
+        }
+
+        let mut open_cmd = Command::new("open");
+        open_cmd
             .arg("-g") // 在后台启动应用
             .arg(&app_bundle_path)
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .spawn()
-        {
-            Ok(_) => {
-                return Ok("Antigravity 已启动".to_string());
-            }
+            .args(&open_args);
+        match spawn_logged(&mut open_cmd) {
+            Ok(child) => return Ok(child),
             Err(_e1) => {
                 // 方法2: 尝试直接执行可执行文件
                 let exec_names = ["Electron", "Antigravity", "antigravity"];
                 for exec_name in &exec_names {
                     let exec_path = app_bundle_path.join("Contents/MacOS").join(exec_name);
                     if exec_path.exists() {
-                        match Command::new(&exec_path)
-                            .stdout(std::process::Stdio::null())
-                            .stderr(std::process::Stdio::null())
-                            .spawn()
-                        {
-                            Ok(_) => {
-                                return Ok("Antigravity 已启动".to_string());
-                            }
+                        let mut cmd = Command::new(&exec_path);
+                        options.apply(&mut cmd);
+                        match spawn_logged(&mut cmd) {
+                            Ok(child) => return Ok(child),
                             Err(_) => {
                                 continue; // 尝试下一个可执行文件
                             }
@@ -196,16 +580,11 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
                     }
                 }
 
-                // 方法3: 最后尝试不带任何参数的 open 命令
-                match Command::new("open")
-                    .arg(&app_bundle_path)
-                    .stdout(std::process::Stdio::null())
-                    .stderr(std::process::Stdio::null())
-                    .spawn()
-                {
-                    Ok(_) => {
-                        return Ok("Antigravity 已启动".to_string());
-                    }
+                // 方法3: 最后尝试不带 -g 参数的 open 命令
+                let mut open_cmd = Command::new("open");
+                open_cmd.arg(&app_bundle_path).args(&open_args);
+                match spawn_logged(&mut open_cmd) {
+                    Ok(child) => return Ok(child),
                     Err(_e3) => {
                         return Err("启动 Antigravity 失败".to_string());
                     }
@@ -215,44 +594,42 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
     }
 
     // Windows 和 Linux 直接执行二进制文件（静默启动）
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "windows")]
     {
-        // Windows：重定向输出到 null 设备
-        #[cfg(target_os = "windows")]
-        {
-            Command::new(path)
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .spawn()
-                .map_err(|e| format!("启动失败: {}", e))?;
-        }
+        let mut cmd = Command::new(path);
+        options.apply(&mut cmd);
+        spawn_logged(&mut cmd)
+    }
 
-        // Linux：重定向输出到 null 设备
-        #[cfg(target_os = "linux")]
-        {
-            Command::new(path)
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .spawn()
-                .map_err(|e| format!("启动失败: {}", e))?;
-        }
+    #[cfg(target_os = "linux")]
+    {
+        spawn_logged(&mut linux_command(path, options)?)
+    }
 
-        Ok("Antigravity 已启动".to_string())
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let mut cmd = Command::new(path);
+        options.apply(&mut cmd);
+        spawn_logged(&mut cmd)
     }
 }
 
 /// 尝试从系统命令启动应用程序（静默启动）
-fn try_start_from_commands(commands: Vec<&str>) -> Result<String, String> {
+fn try_start_from_commands(
+    commands: Vec<&str>,
+    options: &LaunchOptions,
+) -> Result<Spawned, String> {
     let mut errors = Vec::new();
 
     for cmd in commands {
-        match Command::new(cmd)
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .spawn()
-        {
-            Ok(_) => {
-                return Ok("Antigravity 已启动".to_string());
+        let mut command = Command::new(cmd);
+        options.apply(&mut command);
+        match spawn_logged(&mut command) {
+            Ok(child) => {
+                return Ok(Spawned {
+                    child,
+                    executable: find_in_path(cmd),
+                })
             }
             Err(e) => {
                 errors.push(format!("{}命令: {}", cmd, e));
@@ -263,6 +640,92 @@ fn try_start_from_commands(commands: Vec<&str>) -> Result<String, String> {
     Err(format!("所有命令尝试失败: {}", errors.join(", ")))
 }
 
+/// 在系统 PATH 中查找命令对应的文件
+fn find_in_path(command: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        let candidates = if cfg!(windows) {
+            vec![dir.join(format!("{command}.exe")), dir.join(command)]
+        } else {
+            vec![dir.join(command)]
+        };
+        candidates.into_iter().find(|path| path.is_file())
+    })
+}
+
+/// 启动进程：丢弃 stdout，stderr 写入启动日志，便于诊断启动失败
+fn spawn_logged(cmd: &mut Command) -> Result<Child, String> {
+    let stderr = launch_log_stdio(cmd);
+    cmd.stdout(Stdio::null()).stderr(stderr);
+    cmd.spawn().map_err(|e| format!("启动失败: {}", e))
+}
+
+/// 启动日志路径（每次启动覆盖）
+fn launch_log_path() -> PathBuf {
+    crate::directories::get_log_directory().join(LAUNCH_LOG_FILE)
+}
+
+/// 重新创建启动日志并写入命令行，失败时丢弃 stderr
+fn launch_log_stdio(cmd: &Command) -> Stdio {
+    let path = launch_log_path();
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+
+    match std::fs::File::create(&path) {
+        Ok(mut file) => {
+            let _ = writeln!(
+                file,
+                "=== {} {} ===",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                describe_command(cmd)
+            );
+            Stdio::from(file)
+        }
+        Err(e) => {
+            tracing::warn!("⚠️ 无法创建启动日志 {}: {}", path.display(), e);
+            Stdio::null()
+        }
+    }
+}
+
+/// 命令行描述：程序与参数，环境变量只列出名称（值可能包含用户在启动配置中设置的密钥）
+fn describe_command(cmd: &Command) -> String {
+    let mut description = format!("{:?}", cmd.get_program());
+    for arg in cmd.get_args() {
+        description.push_str(&format!(" {:?}", arg));
+    }
+
+    let env_keys: Vec<String> = cmd
+        .get_envs()
+        .map(|(key, value)| match value {
+            Some(_) => key.to_string_lossy().to_string(),
+            None => format!("-{}", key.to_string_lossy()),
+        })
+        .collect();
+    if !env_keys.is_empty() {
+        description.push_str(&format!(" [env: {}]", env_keys.join(", ")));
+    }
+    description
+}
+
+/// 启动日志的最后几行，用于拼接错误信息
+fn launch_log_tail() -> String {
+    let path = launch_log_path();
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    let lines: Vec<&str> = content.lines().skip(1).collect();
+    if lines.is_empty() {
+        return format!("（启动日志: {}）", path.display());
+    }
+
+    let tail = &lines[lines.len().saturating_sub(LAUNCH_LOG_TAIL_LINES)..];
+    format!(
+        "\n启动日志 {} 的最后输出:\n{}",
+        path.display(),
+        tail.join("\n")
+    )
+}
+
 /// 检测 Antigravity 可执行文件路径（不启动，只检测）
 pub fn detect_antigravity_executable() -> Option<PathBuf> {
     tracing::info!("🔍 开始自动检测 Antigravity 可执行文件...");
@@ -284,3 +747,23 @@ pub fn detect_antigravity_executable() -> Option<PathBuf> {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_command_omits_env_values() {
+        let mut cmd = Command::new("antigravity");
+        cmd.arg("--user-data-dir")
+            .arg("/data/a")
+            .env("API_KEY", "secret-value")
+            .env_remove("APPIMAGE");
+
+        let description = describe_command(&cmd);
+        assert!(description.starts_with("\"antigravity\" \"--user-data-dir\" \"/data/a\""));
+        assert!(description.contains("API_KEY"));
+        assert!(description.contains("-APPIMAGE"));
+        assert!(!description.contains("secret-value"));
+    }
+}
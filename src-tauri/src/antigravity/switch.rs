@@ -0,0 +1,224 @@
+//! Antigravity 账户切换模块
+//!
+//! 切换流程：快照 → 关闭进程 → 单事务恢复 → 重新启动
+//! 任一阶段失败都会回滚到切换前的账户，并返回失败阶段
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::antigravity::restore::{self, DatabaseSnapshot};
+use crate::error::AgentError;
+use crate::platform::{ProcessScope, ShutdownReport};
+
+/// 串行化所有账户切换（手动切换、深度链接、自动轮换、最佳账户），
+/// 防止两个切换同时关闭进程并写入数据库
+static SWITCH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// 切换流程的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchStage {
+    /// 读取目标账户文件并定位数据库
+    Prepare,
+    /// 采集当前账户快照
+    Snapshot,
+    /// 关闭 Antigravity 进程
+    StopProcess,
+    /// 写入目标账户数据
+    Restore,
+    /// 重新启动 Antigravity
+    Start,
+}
+
+/// 账户切换结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SwitchResult {
+    pub account: String,
+    pub success: bool,
+    /// 失败的阶段（成功时为 None）
+    #[serde(rename = "failedStage")]
+    pub failed_stage: Option<SwitchStage>,
+    pub error: Option<AgentError>,
+    /// 是否已回滚到切换前的账户
+    #[serde(rename = "rolledBack")]
+    pub rolled_back: bool,
+    /// 关闭 Antigravity 进程的结果（进程未运行时为 None）
+    pub shutdown: Option<ShutdownReport>,
+    pub message: String,
+}
+
+impl SwitchResult {
+    fn failed(account: &str, stage: SwitchStage, error: AgentError, rolled_back: bool) -> Self {
+        let message = if rolled_back {
+            format!("切换失败（{:?}）: {}，已回滚到原账户", stage, error)
+        } else {
+            format!("切换失败（{:?}）: {}", stage, error)
+        };
+
+        Self {
+            account: account.to_string(),
+            success: false,
+            failed_stage: Some(stage),
+            error: Some(error),
+            rolled_back,
+            shutdown: None,
+            message,
+        }
+    }
+
+    fn with_shutdown(mut self, shutdown: Option<ShutdownReport>) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+}
+
+/// 切换到指定账户文件
+///
+/// `exit_timeout` 为等待 Antigravity 进程退出的最长时间，超时后强制终止
+async fn switch_account(
+    account_name: &str,
+    account_file: PathBuf,
+    exit_timeout: Duration,
+) -> SwitchResult {
+    // 0. 准备：读取目标账户并定位数据库，尚未改动任何数据
+    let prepared = restore::read_account_agent_state(&account_file)
+        .and_then(|state| restore::resolve_antigravity_db_path().map(|db| (state, db)));
+    let (agent_state, db_path) = match prepared {
+        Ok(v) => v,
+        Err(e) => return SwitchResult::failed(account_name, SwitchStage::Prepare, e, false),
+    };
+
+    if !db_path.exists() {
+        let e = AgentError::database_missing(&db_path);
+        return SwitchResult::failed(account_name, SwitchStage::Prepare, e, false);
+    }
+
+    // 1. 快照当前账户
+    let snapshot = match DatabaseSnapshot::capture(&db_path) {
+        Ok(s) => s,
+        Err(e) => return SwitchResult::failed(account_name, SwitchStage::Snapshot, e, false),
+    };
+
+    // 2. 分阶段关闭活动目标的 Antigravity 进程 (如果存在)，并等待其真正退出
+    let scope = match ProcessScope::current() {
+        Ok(scope) => scope,
+        Err(e) => return SwitchResult::failed(account_name, SwitchStage::StopProcess, e, false),
+    };
+    let shutdown = match crate::platform::kill_antigravity_processes(&scope, exit_timeout).await {
+        Ok(report) => {
+            tracing::debug!(target: "account::switch::stop", result = %report.summary(), "进程关闭完成");
+            if !report.all_exited() {
+                // 进程仍在运行时写库会被其退出时覆盖，直接中止
+                let e = AgentError::ProcessKillFailed(report.summary());
+                return SwitchResult::failed(account_name, SwitchStage::StopProcess, e, false)
+                    .with_shutdown(Some(report));
+            }
+            Some(report)
+        }
+        Err(AgentError::ProcessNotRunning) => {
+            tracing::debug!(target: "account::switch::stop", "Antigravity 进程未运行，跳过关闭步骤");
+            None
+        }
+        Err(e) => {
+            tracing::error!(target: "account::switch::stop", error = %e, "关闭进程时发生错误");
+            return SwitchResult::failed(account_name, SwitchStage::StopProcess, e, false);
+        }
+    };
+    let was_running = shutdown.is_some();
+
+    // 3. 单事务写入目标账户
+    let restore_message = match restore::restore_agent_state(&db_path, &agent_state) {
+        Ok(report) => format!("主库恢复 {} 项", report.main_rows),
+        Err(e) => {
+            tracing::error!(target: "account::switch::restore", error = %e, "恢复账户数据失败");
+            let rolled_back = rollback(&snapshot);
+            if was_running {
+                // 原账户已恢复，重新拉起 Antigravity
+                if let Err(start_err) = crate::antigravity::starter::start_antigravity().await {
+                    tracing::warn!(target: "account::switch::rollback", error = %start_err, "回滚后重新启动 Antigravity 失败");
+                }
+            }
+            return SwitchResult::failed(account_name, SwitchStage::Restore, e, rolled_back)
+                .with_shutdown(shutdown);
+        }
+    };
+
+    // 4. 重新启动 Antigravity 进程
+    let start_message = match crate::antigravity::starter::launch_antigravity().await {
+        Ok(result) => result,
+        Err(failure) => {
+            tracing::error!(target: "account::switch::start", error = %failure.error, "Antigravity 启动失败");
+            // 本次启动的进程仍在运行时会占用并覆盖数据库，不能回滚
+            let rolled_back = if failure.stopped {
+                rollback(&snapshot)
+            } else {
+                tracing::error!(target: "account::switch::rollback", "启动的 Antigravity 进程未能结束，跳过回滚");
+                false
+            };
+            return SwitchResult::failed(
+                account_name,
+                SwitchStage::Start,
+                failure.error,
+                rolled_back,
+            )
+            .with_shutdown(shutdown);
+        }
+    };
+
+    SwitchResult {
+        account: account_name.to_string(),
+        success: true,
+        failed_stage: None,
+        error: None,
+        rolled_back: false,
+        message: format!(
+            "{} -> {} -> {}",
+            shutdown
+                .as_ref()
+                .map(|r| r.summary())
+                .unwrap_or_else(|| "Antigravity 进程未运行".to_string()),
+            restore_message,
+            start_message
+        ),
+        shutdown,
+    }
+}
+
+/// 切换到已保存的账户（antigravity-accounts/{account_name}.json），成功后记录到账户索引
+///
+/// 同一时间只进行一次切换，后到的请求等待前一次完成
+pub async fn switch_saved_account(account_name: &str, exit_timeout: Duration) -> SwitchResult {
+    let account_file = match crate::directories::get_account_file(account_name) {
+        Ok(account_file) => account_file,
+        Err(e) => return SwitchResult::failed(account_name, SwitchStage::Prepare, e, false),
+    };
+
+    let _guard = SWITCH_LOCK.lock().await;
+    let result = switch_account(account_name, account_file, exit_timeout).await;
+    if result.success {
+        tracing::info!(target: "account::switch", message = %result.message, "账户切换完成");
+        if let Err(e) = crate::antigravity::account_index::record_switched(account_name) {
+            tracing::warn!(target: "account::index", error = %e, "更新账户索引失败");
+        }
+    } else {
+        tracing::warn!(
+            target: "account::switch",
+            failed_stage = ?result.failed_stage,
+            rolled_back = result.rolled_back,
+            "账户切换失败"
+        );
+    }
+
+    result
+}
+
+fn rollback(snapshot: &DatabaseSnapshot) -> bool {
+    match snapshot.rollback() {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::error!(target: "account::switch::rollback", error = %e, "回滚失败");
+            false
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Antigravity 目标管理模块
+//!
+//! 一个“目标”是一组 Antigravity 用户数据目录与可执行文件（如正式版、Insiders 版、便携版），
+//! 保存在 ~/.antigravity-agent/targets.json。选中活动目标后，账户读取、清除、恢复、切换与
+//! 数据库监控都只作用于该目标；未选择目标时沿用自动检测的默认安装。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::directories;
+use crate::error::AgentError;
+
+/// 进程级的目标覆盖（命令行 `--target`），优先于配置中的活动目标
+static TARGET_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// 一个 Antigravity 安装
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AntigravityTarget {
+    /// 目标名称（唯一）
+    pub name: String,
+    /// 用户数据目录，即 `--user-data-dir` 对应的目录（其下包含 User/globalStorage）
+    #[serde(rename = "dataDir")]
+    pub data_dir: PathBuf,
+    /// 可执行文件路径，未设置时使用自动检测的可执行文件
+    #[serde(rename = "executablePath", default)]
+    pub executable_path: Option<PathBuf>,
+    /// 启动该目标时使用的配置
+    #[serde(rename = "launchProfile", default)]
+    pub launch_profile: LaunchProfile,
+}
+
+/// Linux 上 Antigravity（Electron）使用的显示协议
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayServer {
+    /// 由 Antigravity 自行决定
+    #[default]
+    Auto,
+    Wayland,
+    X11,
+}
+
+/// 启动配置：每次启动 Antigravity（包括切换账户后）都会应用
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchProfile {
+    /// 额外的命令行参数
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 额外的环境变量
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// 启动后打开的工作区文件夹
+    #[serde(rename = "workspaceFolder", default)]
+    pub workspace_folder: Option<PathBuf>,
+    /// 进程的工作目录
+    #[serde(rename = "workingDir", default)]
+    pub working_dir: Option<PathBuf>,
+    /// 显示协议（仅 Linux）
+    #[serde(rename = "displayServer", default)]
+    pub display_server: DisplayServer,
+}
+
+impl LaunchProfile {
+    fn validate(&self) -> Result<(), AgentError> {
+        if let Some(key) = self
+            .env
+            .keys()
+            .find(|key| key.is_empty() || key.contains(['=', '\0']))
+        {
+            return Err(AgentError::InvalidInput(format!(
+                "无效的环境变量名: {:?}",
+                key
+            )));
+        }
+        if let Some(dir) = &self.working_dir {
+            if !dir.is_dir() {
+                return Err(AgentError::InvalidInput(format!(
+                    "工作目录不存在: {}",
+                    dir.display()
+                )));
+            }
+        }
+        if let Some(folder) = &self.workspace_folder {
+            if !folder.exists() {
+                return Err(AgentError::InvalidInput(format!(
+                    "工作区不存在: {}",
+                    folder.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AntigravityTarget {
+    /// globalStorage 目录
+    pub fn global_storage_dir(&self) -> PathBuf {
+        self.data_dir.join("User").join("globalStorage")
+    }
+
+    /// 状态数据库路径
+    pub fn db_path(&self) -> PathBuf {
+        self.global_storage_dir().join("state.vscdb")
+    }
+}
+
+/// targets.json 内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TargetsConfig {
+    /// 活动目标名称，None 表示使用自动检测的默认安装
+    #[serde(default)]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub targets: Vec<AntigravityTarget>,
+    /// 未选择目标（自动检测的默认安装）时使用的启动配置
+    #[serde(rename = "defaultLaunchProfile", default)]
+    pub default_launch_profile: LaunchProfile,
+}
+
+impl TargetsConfig {
+    fn find(&self, name: &str) -> Option<&AntigravityTarget> {
+        self.targets.iter().find(|t| t.name == name)
+    }
+}
+
+/// 读取目标配置（文件不存在时返回空配置）
+pub fn load_targets() -> Result<TargetsConfig, AgentError> {
+    let path = directories::get_targets_file();
+    if !path.exists() {
+        return Ok(TargetsConfig::default());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| AgentError::io("读取目标配置失败", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| AgentError::Internal(format!("解析目标配置失败: {}", e)))
+}
+
+fn write_targets(config: &TargetsConfig) -> Result<(), AgentError> {
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(directories::get_targets_file(), json)
+        .map_err(|e| AgentError::io("写入目标配置失败", e))
+}
+
+/// 新增或更新目标（按名称匹配）
+pub fn save_target(target: AntigravityTarget) -> Result<TargetsConfig, AgentError> {
+    let name = target.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AgentError::InvalidInput("目标名称不能为空".to_string()));
+    }
+    if !target.data_dir.is_absolute() {
+        return Err(AgentError::InvalidInput(format!(
+            "数据目录必须是绝对路径: {}",
+            target.data_dir.display()
+        )));
+    }
+    if let Some(exe) = &target.executable_path {
+        // macOS 上可执行文件是 .app 目录
+        if !exe.exists() {
+            return Err(AgentError::InvalidInput(format!(
+                "可执行文件不存在: {}",
+                exe.display()
+            )));
+        }
+    }
+    target.launch_profile.validate()?;
+
+    let target = AntigravityTarget { name, ..target };
+    let mut config = load_targets()?;
+    match config.targets.iter_mut().find(|t| t.name == target.name) {
+        Some(existing) => *existing = target.clone(),
+        None => config.targets.push(target.clone()),
+    }
+    write_targets(&config)?;
+
+    tracing::info!(target: "antigravity::targets", name = %target.name, "✅ 已保存目标");
+    Ok(config)
+}
+
+/// 保存未选择目标时使用的启动配置
+pub fn save_default_launch_profile(profile: LaunchProfile) -> Result<TargetsConfig, AgentError> {
+    profile.validate()?;
+
+    let mut config = load_targets()?;
+    config.default_launch_profile = profile;
+    write_targets(&config)?;
+
+    tracing::info!(target: "antigravity::targets", "✅ 已保存默认启动配置");
+    Ok(config)
+}
+
+/// 删除目标；删除的是活动目标时回到自动检测
+pub fn remove_target(name: &str) -> Result<TargetsConfig, AgentError> {
+    let mut config = load_targets()?;
+    let before = config.targets.len();
+    config.targets.retain(|t| t.name != name);
+    if config.targets.len() == before {
+        return Err(AgentError::InvalidInput(format!("目标不存在: {}", name)));
+    }
+    if config.active.as_deref() == Some(name) {
+        config.active = None;
+    }
+    write_targets(&config)?;
+
+    tracing::info!(target: "antigravity::targets", name = %name, "🗑️ 已删除目标");
+    Ok(config)
+}
+
+/// 设置活动目标，None 表示使用自动检测的默认安装
+pub fn set_active_target(name: Option<String>) -> Result<TargetsConfig, AgentError> {
+    let mut config = load_targets()?;
+    if let Some(name) = &name {
+        if config.find(name).is_none() {
+            return Err(AgentError::InvalidInput(format!("目标不存在: {}", name)));
+        }
+    }
+    config.active = name;
+    write_targets(&config)?;
+
+    tracing::info!(target: "antigravity::targets", active = ?config.active, "🎯 已切换活动目标");
+    Ok(config)
+}
+
+/// 在当前进程内使用指定目标（不写入配置），只能设置一次
+pub fn set_target_override(name: String) {
+    let _ = TARGET_OVERRIDE.set(name);
+}
+
+/// 当前生效的目标；None 表示未选择目标，使用自动检测的默认安装
+///
+/// 选择的目标不存在时返回错误，而不是回退到自动检测
+pub fn active_target() -> Result<Option<AntigravityTarget>, AgentError> {
+    let config = load_targets()?;
+    let Some(name) = TARGET_OVERRIDE.get().or(config.active.as_ref()) else {
+        return Ok(None);
+    };
+
+    config
+        .find(name)
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| AgentError::InvalidInput(format!("目标不存在: {}", name)))
+}
@@ -0,0 +1,157 @@
+//! 令牌状态模块
+//!
+//! 根据账户快照中的 AuthMetadata.expiry_timestamp 判断令牌是否有效、即将过期或已过期。
+//! “即将过期”的提前量默认 60 分钟，应用启动时按设置覆盖。
+
+use chrono::Utc;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::antigravity::{account, restore};
+use crate::error::AgentError;
+
+/// 默认提前提醒的分钟数
+pub const DEFAULT_WARNING_MINUTES: u64 = 60;
+
+/// 当前生效的提前提醒分钟数
+static WARNING_MINUTES: AtomicU64 = AtomicU64::new(DEFAULT_WARNING_MINUTES);
+
+/// 令牌状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenState {
+    Valid,
+    /// 将在提醒时间内过期
+    ExpiringSoon,
+    Expired,
+    /// 快照中没有过期时间
+    Unknown,
+}
+
+/// 账户令牌状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenStatus {
+    pub state: TokenState,
+    /// AuthMetadata.expiry_timestamp（Unix 秒）
+    #[serde(rename = "expiryTimestamp")]
+    pub expiry_timestamp: Option<i64>,
+    /// 距离过期的秒数，已过期时为负数
+    #[serde(rename = "expiresInSeconds")]
+    pub expires_in_seconds: Option<i64>,
+}
+
+impl TokenStatus {
+    /// 按指定的当前时间和提前量计算状态
+    pub fn evaluate(expiry_timestamp: Option<i64>, now: i64, warning_minutes: u64) -> Self {
+        // 未写入过期时间的快照中该字段为 0
+        let expiry_timestamp = expiry_timestamp.filter(|ts| *ts > 0);
+        let expires_in_seconds = expiry_timestamp.map(|ts| ts - now);
+
+        let state = match expires_in_seconds {
+            None => TokenState::Unknown,
+            Some(secs) if secs <= 0 => TokenState::Expired,
+            Some(secs) if secs <= warning_minutes as i64 * 60 => TokenState::ExpiringSoon,
+            Some(_) => TokenState::Valid,
+        };
+
+        Self {
+            state,
+            expiry_timestamp,
+            expires_in_seconds,
+        }
+    }
+
+    /// 按当前时间和设置中的提前量计算状态
+    pub fn from_expiry(expiry_timestamp: Option<i64>) -> Self {
+        Self::evaluate(expiry_timestamp, Utc::now().timestamp(), warning_minutes())
+    }
+
+    /// 令牌即将过期或已过期
+    pub fn needs_attention(&self) -> bool {
+        matches!(self.state, TokenState::ExpiringSoon | TokenState::Expired)
+    }
+}
+
+/// 当前的提前提醒分钟数
+pub fn warning_minutes() -> u64 {
+    WARNING_MINUTES.load(Ordering::Relaxed)
+}
+
+/// 设置提前提醒分钟数
+pub fn set_warning_minutes(minutes: u64) {
+    WARNING_MINUTES.store(minutes, Ordering::Relaxed);
+}
+
+/// 已保存账户的令牌状态
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountTokenStatus {
+    pub email: String,
+    #[serde(flatten)]
+    pub status: TokenStatus,
+}
+
+/// 读取所有已保存账户的令牌状态（无法读取的账户文件会被跳过）
+pub fn saved_account_statuses() -> Result<Vec<AccountTokenStatus>, AgentError> {
+    let mut statuses: Vec<AccountTokenStatus> = account::list_saved_accounts()?
+        .into_iter()
+        .map(|entry| AccountTokenStatus {
+            status: TokenStatus::from_expiry(entry.summary.expiry_timestamp),
+            email: entry.summary.email,
+        })
+        .collect();
+
+    statuses.sort_by(|a, b| a.email.cmp(&b.email));
+    Ok(statuses)
+}
+
+/// 读取单个已保存账户的令牌状态
+pub fn saved_account_status(email: &str) -> Result<TokenStatus, AgentError> {
+    let account_file = crate::directories::get_account_file(email)?;
+    let summary = restore::read_account_agent_state(&account_file)
+        .and_then(|state| account::summarize_agent_state(&state))?;
+    Ok(TokenStatus::from_expiry(summary.expiry_timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_800_000_000;
+
+    #[test]
+    fn evaluate_classifies_by_remaining_time() {
+        let valid = TokenStatus::evaluate(Some(NOW + 61 * 60), NOW, 60);
+        assert_eq!(valid.state, TokenState::Valid);
+        assert_eq!(valid.expires_in_seconds, Some(61 * 60));
+
+        let expiring = TokenStatus::evaluate(Some(NOW + 60 * 60), NOW, 60);
+        assert_eq!(expiring.state, TokenState::ExpiringSoon);
+        assert!(expiring.needs_attention());
+
+        let expired = TokenStatus::evaluate(Some(NOW), NOW, 60);
+        assert_eq!(expired.state, TokenState::Expired);
+        assert_eq!(expired.expires_in_seconds, Some(0));
+
+        let long_expired = TokenStatus::evaluate(Some(NOW - 10), NOW, 60);
+        assert_eq!(long_expired.state, TokenState::Expired);
+        assert_eq!(long_expired.expires_in_seconds, Some(-10));
+    }
+
+    #[test]
+    fn evaluate_treats_missing_expiry_as_unknown() {
+        for expiry in [None, Some(0), Some(-1)] {
+            let status = TokenStatus::evaluate(expiry, NOW, 60);
+            assert_eq!(status.state, TokenState::Unknown);
+            assert_eq!(status.expiry_timestamp, None);
+            assert_eq!(status.expires_in_seconds, None);
+            assert!(!status.needs_attention());
+        }
+    }
+
+    #[test]
+    fn evaluate_without_warning_window() {
+        let status = TokenStatus::evaluate(Some(NOW + 1), NOW, 0);
+        assert_eq!(status.state, TokenState::Valid);
+        assert!(!status.needs_attention());
+    }
+}
@@ -5,12 +5,48 @@ use std::sync::Mutex;
 use tauri::AppHandle;
 
 /// 应用程序设置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
     /// 是否启用系统托盘
     pub system_tray_enabled: bool,
     /// 是否启用静默启动（启动时最小化到托盘或后台）
     pub silent_start_enabled: bool,
+    /// 数据库监控只读取并比较这些 ItemTable 键
+    #[serde(default = "default_db_watched_keys")]
+    pub db_watched_keys: Vec<String>,
+    /// 是否启用本地控制 API（Unix socket / 命名管道）
+    #[serde(default)]
+    pub ipc_server_enabled: bool,
+    /// 账户自动轮换计划
+    #[serde(default)]
+    pub rotation: crate::rotation::RotationPlan,
+    /// 令牌在多少分钟内过期时提醒
+    #[serde(default = "default_token_expiry_warning_minutes")]
+    pub token_expiry_warning_minutes: u64,
+}
+
+fn default_db_watched_keys() -> Vec<String> {
+    vec![
+        crate::constants::database::AGENT_STATE.to_string(),
+        crate::constants::database::AUTH_STATUS.to_string(),
+    ]
+}
+
+fn default_token_expiry_warning_minutes() -> u64 {
+    crate::antigravity::token_status::DEFAULT_WARNING_MINUTES
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            system_tray_enabled: false,
+            silent_start_enabled: false,
+            db_watched_keys: default_db_watched_keys(),
+            ipc_server_enabled: false,
+            rotation: crate::rotation::RotationPlan::default(),
+            token_expiry_warning_minutes: default_token_expiry_warning_minutes(),
+        }
+    }
 }
 
 impl AppSettings {
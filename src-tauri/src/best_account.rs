@@ -0,0 +1,431 @@
+//! 按配额切换到最佳账户
+//!
+//! 针对指定模型，结合配额缓存（fetchAvailableModels 的 remainingFraction）与账户快照中
+//! 解码出的 `ModelConfig.items`，对已保存账户排序：剩余配额高者优先，没有配额数据但模型
+//! 列表包含该模型的账户排在最后。令牌已过期、配额已用尽或不提供该模型的账户会被跳过。
+//! 选中账户后沿用 `switch_saved_account` 的关闭 → 恢复 → 启动流程。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::antigravity::switch::{self, SwitchResult};
+use crate::antigravity::token_status::{TokenState, TokenStatus};
+use crate::antigravity::{account, restore};
+use crate::error::AgentError;
+use crate::proto::SessionResponse;
+use crate::quota::{self, AccountQuota, ModelQuota};
+
+/// 候选账户
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedAccount {
+    pub email: String,
+    /// 该模型的剩余配额比例（0 ~ 1），没有配额数据时为 None
+    #[serde(rename = "remainingFraction")]
+    pub remaining_fraction: Option<f64>,
+    /// 配额重置时间
+    #[serde(rename = "resetTime")]
+    pub reset_time: Option<String>,
+    /// 账户快照的 ModelConfig.items 中包含该模型
+    #[serde(rename = "modelListed")]
+    pub model_listed: bool,
+    #[serde(rename = "tokenStatus")]
+    pub token_status: TokenStatus,
+}
+
+/// 被跳过的账户及原因
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedAccount {
+    pub email: String,
+    pub reason: String,
+}
+
+/// 切换到最佳账户的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BestAccountSwitch {
+    /// 请求的模型
+    pub model: String,
+    /// 切换前的账户
+    pub from: Option<String>,
+    /// 选中的账户，没有可用账户时为 None
+    pub chosen: Option<RankedAccount>,
+    /// 选中该账户（或没有选中任何账户）的原因
+    pub reason: String,
+    /// 所有候选账户（按排名）
+    pub ranking: Vec<RankedAccount>,
+    pub skipped: Vec<SkippedAccount>,
+    /// 切换结果，没有可用账户时为 None
+    #[serde(rename = "switchResult")]
+    pub switch_result: Option<SwitchResult>,
+}
+
+impl BestAccountSwitch {
+    pub fn success(&self) -> bool {
+        self.switch_result.as_ref().is_some_and(|r| r.success)
+    }
+}
+
+/// 模型 id、显示名称或 ModelConfig.items 中的名称与请求的模型一致（忽略大小写）
+fn model_matches(requested: &str, name: &str) -> bool {
+    requested.eq_ignore_ascii_case(name.trim())
+}
+
+fn find_model<'a>(quota: &'a AccountQuota, model: &str) -> Option<&'a ModelQuota> {
+    quota.models.iter().find(|m| {
+        model_matches(model, &m.id)
+            || m.display_name
+                .as_deref()
+                .is_some_and(|name| model_matches(model, name))
+    })
+}
+
+fn format_fraction(fraction: f64) -> String {
+    format!("{:.0}%", fraction * 100.0)
+}
+
+/// 评估单个账户，返回候选信息或跳过原因
+fn evaluate_account(
+    email: &str,
+    model: &str,
+    quota: Option<&AccountQuota>,
+) -> Result<RankedAccount, String> {
+    let session = crate::directories::get_account_file(email)
+        .and_then(|account_file| restore::read_account_agent_state(&account_file))
+        .and_then(|state| account::decode_session(&state))
+        .map_err(|e| format!("无法读取账户: {}", e))?;
+
+    evaluate_session(
+        email,
+        model,
+        session,
+        quota,
+        quota::refreshed_token_expiry(email),
+    )
+}
+
+/// 按账户快照与配额评估账户
+///
+/// `refreshed_expiry` 为查询配额时在内存中刷新的令牌过期时间，晚于快照中的过期时间时以它为准
+fn evaluate_session(
+    email: &str,
+    model: &str,
+    session: SessionResponse,
+    quota: Option<&AccountQuota>,
+    refreshed_expiry: Option<i64>,
+) -> Result<RankedAccount, String> {
+    let saved_expiry = session
+        .auth
+        .and_then(|a| a.meta)
+        .map(|m| m.expiry_timestamp);
+    let token_status = TokenStatus::from_expiry(saved_expiry.max(refreshed_expiry));
+    if token_status.state == TokenState::Expired {
+        return Err("令牌已过期".to_string());
+    }
+
+    let model_listed = session
+        .context
+        .and_then(|c| c.models)
+        .is_some_and(|models| {
+            models
+                .items
+                .iter()
+                .any(|item| model_matches(model, &item.name))
+        });
+    let model_quota = quota.and_then(|q| find_model(q, model));
+
+    let Some(model_quota) = model_quota else {
+        if !model_listed {
+            return Err("账户不提供该模型".to_string());
+        }
+        return Ok(RankedAccount {
+            email: email.to_string(),
+            remaining_fraction: None,
+            reset_time: None,
+            model_listed,
+            token_status,
+        });
+    };
+
+    if model_quota.remaining_fraction.is_some_and(|f| f <= 0.0) {
+        return Err(match &model_quota.reset_time {
+            Some(reset_time) => format!("配额已用尽（{} 重置）", reset_time),
+            None => "配额已用尽".to_string(),
+        });
+    }
+
+    Ok(RankedAccount {
+        email: email.to_string(),
+        remaining_fraction: model_quota.remaining_fraction,
+        reset_time: model_quota.reset_time.clone(),
+        model_listed,
+        token_status,
+    })
+}
+
+/// 按指定模型的剩余配额对已保存账户排序（不含当前账户）
+pub fn rank_accounts(
+    model: &str,
+    current: Option<&str>,
+) -> Result<(Vec<RankedAccount>, Vec<SkippedAccount>), AgentError> {
+    let quotas: HashMap<String, AccountQuota> = quota::cached_quotas()
+        .into_iter()
+        .map(|q| (q.email.clone(), q))
+        .collect();
+
+    let mut ranking = Vec::new();
+    let mut skipped = Vec::new();
+    for email in quota::saved_account_emails()? {
+        if Some(email.as_str()) == current {
+            continue;
+        }
+
+        match evaluate_account(&email, model, quotas.get(&email)) {
+            Ok(ranked) => ranking.push(ranked),
+            Err(reason) => {
+                tracing::info!(target: "best_account", email = %email, reason = %reason, "跳过账户");
+                skipped.push(SkippedAccount { email, reason });
+            }
+        }
+    }
+
+    sort_ranking(&mut ranking);
+    Ok((ranking, skipped))
+}
+
+/// 剩余配额从高到低，没有配额数据的排在最后；相同时按邮箱排序
+fn sort_ranking(ranking: &mut [RankedAccount]) {
+    ranking.sort_by(|a, b| {
+        match (a.remaining_fraction, b.remaining_fraction) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+        .then_with(|| a.email.cmp(&b.email))
+    });
+}
+
+fn choose_reason(model: &str, chosen: &RankedAccount, ranking: &[RankedAccount]) -> String {
+    let runner_up = ranking.get(1);
+    match chosen.remaining_fraction {
+        Some(fraction) => {
+            let mut reason = format!("{} 剩余配额 {}", model, format_fraction(fraction));
+            match runner_up.and_then(|r| r.remaining_fraction.map(|f| (r, f))) {
+                Some((runner_up, f)) => reason.push_str(&format!(
+                    "，高于其余 {} 个候选账户（次高为 {} 的 {}）",
+                    ranking.len() - 1,
+                    runner_up.email,
+                    format_fraction(f)
+                )),
+                None if ranking.len() > 1 => reason.push_str(&format!(
+                    "，其余 {} 个候选账户没有配额数据",
+                    ranking.len() - 1
+                )),
+                None => reason.push_str("，是唯一可用的账户"),
+            }
+            reason
+        }
+        None => format!(
+            "所有候选账户都没有 {} 的配额数据，选择模型列表中包含该模型的第一个账户",
+            model
+        ),
+    }
+}
+
+/// 切换到指定模型剩余配额最多的已保存账户
+///
+/// `refresh` 为 true 时先重新查询所有账户的配额；查询失败时继续使用缓存
+pub async fn switch_to_best_account(
+    model: &str,
+    refresh: bool,
+    exit_timeout: Duration,
+) -> Result<BestAccountSwitch, AgentError> {
+    let model = model.trim();
+    if model.is_empty() {
+        return Err(AgentError::InvalidInput("模型不能为空".to_string()));
+    }
+
+    if refresh {
+        if let Err(e) = quota::refresh_all().await {
+            tracing::warn!(target: "best_account", error = %e, "刷新配额失败，使用缓存数据");
+        }
+    }
+
+    let current = account::read_current_agent_state()
+        .and_then(|state| account::summarize_agent_state(&state))
+        .ok()
+        .map(|summary| summary.email)
+        .filter(|email| !email.is_empty());
+
+    let (ranking, skipped) = rank_accounts(model, current.as_deref())?;
+    let Some(chosen) = ranking.first().cloned() else {
+        tracing::warn!(target: "best_account", model = %model, skipped = skipped.len(), "没有可切换的账户");
+        return Ok(BestAccountSwitch {
+            model: model.to_string(),
+            from: current,
+            chosen: None,
+            reason: format!(
+                "没有可切换的账户（其余账户令牌已过期、{} 配额已用尽或不提供该模型）",
+                model
+            ),
+            ranking,
+            skipped,
+            switch_result: None,
+        });
+    };
+
+    let reason = choose_reason(model, &chosen, &ranking);
+    tracing::info!(target: "best_account", from = ?current, to = %chosen.email, reason = %reason, "🏆 切换到配额最多的账户");
+    let result = switch::switch_saved_account(&chosen.email, exit_timeout).await;
+
+    Ok(BestAccountSwitch {
+        model: model.to_string(),
+        from: current,
+        chosen: Some(chosen),
+        reason,
+        ranking,
+        skipped,
+        switch_result: Some(result),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(email: &str, remaining_fraction: Option<f64>) -> RankedAccount {
+        RankedAccount {
+            email: email.to_string(),
+            remaining_fraction,
+            reset_time: None,
+            model_listed: true,
+            token_status: TokenStatus::evaluate(None, 0, 60),
+        }
+    }
+
+    fn emails(ranking: &[RankedAccount]) -> Vec<&str> {
+        ranking.iter().map(|r| r.email.as_str()).collect()
+    }
+
+    #[test]
+    fn ranking_prefers_highest_remaining_fraction() {
+        let mut ranking = vec![
+            ranked("low@example.com", Some(0.2)),
+            ranked("unknown@example.com", None),
+            ranked("high@example.com", Some(0.9)),
+            ranked("mid@example.com", Some(0.5)),
+        ];
+        sort_ranking(&mut ranking);
+        assert_eq!(
+            emails(&ranking),
+            vec![
+                "high@example.com",
+                "mid@example.com",
+                "low@example.com",
+                "unknown@example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn ranking_ties_are_ordered_by_email() {
+        let mut ranking = vec![
+            ranked("d@example.com", None),
+            ranked("b@example.com", Some(0.5)),
+            ranked("c@example.com", None),
+            ranked("a@example.com", Some(0.5)),
+        ];
+        sort_ranking(&mut ranking);
+        assert_eq!(
+            emails(&ranking),
+            vec![
+                "a@example.com",
+                "b@example.com",
+                "c@example.com",
+                "d@example.com"
+            ]
+        );
+    }
+
+    /// 快照中令牌已过期、提供给定模型的账户
+    fn expired_session(model: &str) -> SessionResponse {
+        use crate::proto::{AuthInfo, AuthMetadata, ModelConfig, ModelItem, UserContext};
+
+        SessionResponse {
+            auth: Some(AuthInfo {
+                meta: Some(AuthMetadata {
+                    expiry_timestamp: chrono::Utc::now().timestamp() - 3600,
+                }),
+                ..Default::default()
+            }),
+            context: Some(UserContext {
+                models: Some(ModelConfig {
+                    items: vec![ModelItem {
+                        name: model.to_string(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn quota_with(email: &str, model: &str, remaining_fraction: f64) -> AccountQuota {
+        AccountQuota {
+            email: email.to_string(),
+            tier: None,
+            tier_name: None,
+            project: None,
+            models: vec![ModelQuota {
+                id: model.to_string(),
+                display_name: None,
+                remaining_fraction: Some(remaining_fraction),
+                reset_time: None,
+            }],
+            fetched_at: None,
+            checked_at: chrono::Utc::now(),
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn ranking_uses_tokens_refreshed_during_quota_check() {
+        let email = format!("refreshed-{}@example.com", std::process::id());
+        let model = "gemini-3-pro-high";
+        let quota = quota_with(&email, model, 0.5);
+
+        let skipped = evaluate_session(
+            &email,
+            model,
+            expired_session(model),
+            Some(&quota),
+            quota::refreshed_token_expiry(&email),
+        );
+        assert_eq!(skipped.unwrap_err(), "令牌已过期");
+
+        // 查询配额时刷新的令牌只保存在内存中，账户快照里仍是过期的令牌
+        quota::remember_access_token(
+            &email,
+            "refreshed-access",
+            chrono::Utc::now().timestamp() + 3600,
+        );
+        let ranked = evaluate_session(
+            &email,
+            model,
+            expired_session(model),
+            Some(&quota),
+            quota::refreshed_token_expiry(&email),
+        )
+        .unwrap();
+        assert_eq!(ranked.remaining_fraction, Some(0.5));
+        assert_ne!(ranked.token_status.state, TokenState::Expired);
+    }
+
+    #[test]
+    fn model_matching_ignores_case_and_whitespace() {
+        assert!(model_matches("Gemini-3-Pro-High", " gemini-3-pro-high "));
+        assert!(!model_matches("gemini-3-pro", "gemini-3-pro-high"));
+    }
+}
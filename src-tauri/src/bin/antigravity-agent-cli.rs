@@ -0,0 +1,485 @@
+//! Antigravity Agent 命令行工具
+//!
+//! 不启动 WebView，直接复用桌面应用的账户、进程与目录模块，便于在脚本中切换和管理账户。
+//! 成功时向 stdout 输出 JSON；失败时向 stderr 输出 `{"error": {code, message, details}}`，
+//! 并以非零退出码结束（见 `exit_code`）。
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::Value;
+use tracing_subscriber::EnvFilter;
+
+use antigravity_agent_lib::antigravity::token_status::TokenStatus;
+use antigravity_agent_lib::antigravity::{account, backup, cleanup, starter, switch, targets};
+use antigravity_agent_lib::best_account;
+use antigravity_agent_lib::directories;
+use antigravity_agent_lib::error::AgentError;
+use antigravity_agent_lib::oauth;
+use antigravity_agent_lib::platform::{self, ShutdownReport};
+use antigravity_agent_lib::quota;
+
+/// 退出码：成功
+const EXIT_OK: u8 = 0;
+/// 退出码：一般错误
+const EXIT_ERROR: u8 = 1;
+/// 退出码：账户不存在
+const EXIT_ACCOUNT_NOT_FOUND: u8 = 3;
+/// 退出码：未找到 Antigravity 安装或数据库
+const EXIT_NOT_INSTALLED: u8 = 4;
+/// 退出码：切换失败（已按需回滚）
+const EXIT_SWITCH_FAILED: u8 = 5;
+
+#[derive(Parser)]
+#[command(
+    name = "antigravity-agent-cli",
+    version,
+    about = "Antigravity Agent 命令行工具：列出、保存、切换、导入导出账户"
+)]
+struct Cli {
+    /// 本次操作的目标（见 `targets`），默认使用桌面应用中选择的活动目标
+    #[arg(long, global = true, env = "ANTIGRAVITY_AGENT_TARGET")]
+    target: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 列出已保存的账户
+    List,
+    /// 列出已配置的 Antigravity 目标及活动目标
+    Targets,
+    /// 显示 Antigravity 当前登录的账户
+    Current,
+    /// 保存 Antigravity 当前登录的账户
+    Save,
+    /// 切换到已保存的账户（关闭 → 恢复 → 启动，失败自动回滚）
+    Switch {
+        /// 账户邮箱
+        email: String,
+        /// 等待 Antigravity 退出的超时时间（毫秒）
+        #[arg(long)]
+        exit_timeout_ms: Option<u64>,
+    },
+    /// 刷新已保存账户的 access token 并写回账户文件
+    ///
+    /// OAuth 客户端通过 ANTIGRAVITY_AGENT_OAUTH_CLIENT_ID / _CLIENT_SECRET / _TOKEN_ENDPOINT 配置
+    Refresh {
+        /// 账户邮箱
+        email: String,
+    },
+    /// 在浏览器中登录 Google 账户（PKCE）并保存为账户快照
+    Login {
+        /// 不自动打开浏览器，只输出授权链接
+        #[arg(long)]
+        no_browser: bool,
+    },
+    /// 显示已保存账户的剩余配额（默认读取缓存）
+    ///
+    /// 服务地址可通过 ANTIGRAVITY_AGENT_CLOUDCODE_BASE_URL 替换为本地模拟服务
+    Quota {
+        /// 立即重新查询所有账户并更新缓存
+        #[arg(long)]
+        refresh: bool,
+    },
+    /// 切换到指定模型剩余配额最多的已保存账户（跳过令牌已过期的账户）
+    Best {
+        /// 模型 id 或显示名称（如 gemini-3-pro-high）
+        model: String,
+        /// 先重新查询所有账户的配额
+        #[arg(long)]
+        refresh: bool,
+        /// 等待 Antigravity 退出的超时时间（毫秒）
+        #[arg(long)]
+        exit_timeout_ms: Option<u64>,
+    },
+    /// 退出 Antigravity 当前登录的账户（默认先保存当前账户）
+    Logout {
+        /// 不保存当前账户
+        #[arg(long)]
+        no_backup: bool,
+        /// 清除后重新启动 Antigravity
+        #[arg(long)]
+        start: bool,
+        /// 等待 Antigravity 退出的超时时间（毫秒）
+        #[arg(long)]
+        exit_timeout_ms: Option<u64>,
+    },
+    /// 导出所有账户（指定密码时生成与桌面应用兼容的加密配置文件）
+    Export {
+        /// 输出文件，省略时写到 stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// 加密密码
+        #[arg(
+            short,
+            long,
+            env = "ANTIGRAVITY_AGENT_PASSWORD",
+            hide_env_values = true
+        )]
+        password: Option<String>,
+    },
+    /// 从导出的配置文件导入账户（`-` 表示从 stdin 读取）
+    Import {
+        /// 配置文件路径
+        input: PathBuf,
+        /// 解密密码（导入加密配置文件时必需）
+        #[arg(
+            short,
+            long,
+            env = "ANTIGRAVITY_AGENT_PASSWORD",
+            hide_env_values = true
+        )]
+        password: Option<String>,
+    },
+}
+
+/// 命令执行结果：输出内容与退出码
+struct Outcome {
+    output: Value,
+    code: u8,
+}
+
+impl Outcome {
+    fn ok(output: impl Serialize) -> Result<Self, AgentError> {
+        Self::with_code(output, EXIT_OK)
+    }
+
+    fn with_code(output: impl Serialize, code: u8) -> Result<Self, AgentError> {
+        Ok(Self {
+            output: serde_json::to_value(output)?,
+            code,
+        })
+    }
+}
+
+/// 已保存账户的列表项
+#[derive(Serialize)]
+struct AccountListItem {
+    #[serde(flatten)]
+    metadata: antigravity_agent_lib::AntigravityAccount,
+    plan: Option<String>,
+    #[serde(rename = "expiryTimestamp")]
+    expiry_timestamp: Option<i64>,
+    #[serde(rename = "tokenStatus")]
+    token_status: TokenStatus,
+}
+
+/// 当前账户信息
+#[derive(Serialize)]
+struct CurrentAccount {
+    #[serde(flatten)]
+    summary: account::AccountSummary,
+    /// 是否已保存到账户目录
+    saved: bool,
+}
+
+/// logout 结果
+#[derive(Serialize)]
+struct LogoutResult {
+    shutdown: Option<ShutdownReport>,
+    backup: Option<account::SavedAccount>,
+    cleared: String,
+    started: Option<String>,
+}
+
+/// refresh 结果（不输出令牌本身）
+#[derive(Serialize)]
+struct RefreshResult {
+    email: String,
+    #[serde(rename = "expiryTimestamp")]
+    expiry_timestamp: i64,
+    #[serde(rename = "refreshTokenRotated")]
+    refresh_token_rotated: bool,
+}
+
+/// 写入文件时的导出结果
+#[derive(Serialize)]
+struct ExportResult {
+    file: PathBuf,
+    #[serde(rename = "backupCount")]
+    backup_count: usize,
+    encrypted: bool,
+}
+
+fn exit_code(error: &AgentError) -> u8 {
+    match error {
+        AgentError::AccountNotFound(_) => EXIT_ACCOUNT_NOT_FOUND,
+        AgentError::NotInstalled | AgentError::DatabaseMissing(_) => EXIT_NOT_INSTALLED,
+        _ => EXIT_ERROR,
+    }
+}
+
+fn exit_timeout(exit_timeout_ms: Option<u64>) -> Duration {
+    exit_timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(platform::DEFAULT_EXIT_TIMEOUT)
+}
+
+fn list() -> Result<Outcome, AgentError> {
+    let items: Vec<AccountListItem> = account::list_saved_accounts()?
+        .into_iter()
+        .map(|entry| AccountListItem {
+            metadata: entry.metadata,
+            plan: entry.summary.plan,
+            expiry_timestamp: entry.summary.expiry_timestamp,
+            token_status: TokenStatus::from_expiry(entry.summary.expiry_timestamp),
+        })
+        .collect();
+    Outcome::ok(items)
+}
+
+fn current() -> Result<Outcome, AgentError> {
+    let state = account::read_current_agent_state()?;
+    let summary = account::summarize_agent_state(&state)?;
+    let saved = !summary.email.is_empty()
+        && directories::get_account_file(&summary.email).is_ok_and(|file| file.exists());
+    Outcome::ok(CurrentAccount { summary, saved })
+}
+
+fn save() -> Result<Outcome, AgentError> {
+    let state = account::read_current_agent_state()?;
+    Outcome::ok(account::save_agent_state(&state)?)
+}
+
+async fn switch_to(email: &str, exit_timeout_ms: Option<u64>) -> Result<Outcome, AgentError> {
+    if !directories::get_account_file(email)?.exists() {
+        return Err(AgentError::AccountNotFound(email.to_string()));
+    }
+
+    let result = switch::switch_saved_account(email, exit_timeout(exit_timeout_ms)).await;
+    let code = if result.success {
+        EXIT_OK
+    } else {
+        EXIT_SWITCH_FAILED
+    };
+    Outcome::with_code(result, code)
+}
+
+async fn refresh(email: &str) -> Result<Outcome, AgentError> {
+    let refreshed = oauth::refresh_saved_account(email).await?;
+    Outcome::ok(RefreshResult {
+        email: refreshed.email,
+        expiry_timestamp: refreshed.expiry_timestamp,
+        refresh_token_rotated: refreshed.refresh_token_rotated,
+    })
+}
+
+async fn login(no_browser: bool) -> Result<Outcome, AgentError> {
+    let pending = oauth::login::PendingLogin::start(oauth::OAuthConfig::from_env()?).await?;
+    // stdout 只输出结果 JSON，授权链接写到 stderr
+    eprintln!("请在浏览器中完成登录: {}", pending.authorization_url());
+    if !no_browser {
+        oauth::login::open_browser(pending.authorization_url())?;
+    }
+    Outcome::ok(pending.finish().await?)
+}
+
+async fn quota(refresh: bool) -> Result<Outcome, AgentError> {
+    if refresh {
+        Outcome::ok(quota::refresh_all().await?)
+    } else {
+        Outcome::ok(quota::cached_quotas())
+    }
+}
+
+async fn best(
+    model: &str,
+    refresh: bool,
+    exit_timeout_ms: Option<u64>,
+) -> Result<Outcome, AgentError> {
+    let result =
+        best_account::switch_to_best_account(model, refresh, exit_timeout(exit_timeout_ms)).await?;
+    let code = if result.success() {
+        EXIT_OK
+    } else {
+        EXIT_SWITCH_FAILED
+    };
+    Outcome::with_code(result, code)
+}
+
+async fn logout(
+    no_backup: bool,
+    start: bool,
+    exit_timeout_ms: Option<u64>,
+) -> Result<Outcome, AgentError> {
+    // 先关闭进程，避免其退出时把登录状态写回数据库
+    let scope = platform::ProcessScope::current()?;
+    let shutdown =
+        match platform::kill_antigravity_processes(&scope, exit_timeout(exit_timeout_ms)).await {
+            Ok(report) if !report.all_exited() => {
+                return Err(AgentError::ProcessKillFailed(report.summary()))
+            }
+            Ok(report) => Some(report),
+            Err(AgentError::ProcessNotRunning) => None,
+            Err(e) => return Err(e),
+        };
+
+    let backup = if no_backup {
+        None
+    } else {
+        // 未登录时没有可保存的账户，直接跳过
+        match account::read_current_agent_state().and_then(|s| account::save_agent_state(&s)) {
+            Ok(saved) => Some(saved),
+            Err(e) => {
+                tracing::warn!(error = %e, "未保存当前账户");
+                None
+            }
+        }
+    };
+
+    let cleared = cleanup::clear_all_antigravity_data().await?;
+    let started = if start {
+        Some(starter::start_antigravity().await?)
+    } else {
+        None
+    };
+
+    Outcome::ok(LogoutResult {
+        shutdown,
+        backup,
+        cleared,
+        started,
+    })
+}
+
+fn export(output: Option<PathBuf>, password: Option<String>) -> Result<Outcome, AgentError> {
+    let config = backup::ExportedConfig::new(backup::collect_account_contents()?);
+    let backup_count = config.backup_count;
+    let json = serde_json::to_string_pretty(&config)?;
+
+    let encrypted = password.is_some();
+    let content = match password {
+        Some(password) => backup::encrypt_config(&json, &password)?,
+        None => json,
+    };
+
+    match output {
+        Some(file) => {
+            std::fs::write(&file, content)
+                .map_err(|e| AgentError::io(format!("写入 {} 失败", file.display()), e))?;
+            Outcome::ok(ExportResult {
+                file,
+                backup_count,
+                encrypted,
+            })
+        }
+        None => {
+            // 直接输出配置内容本身，便于通过管道传给 import
+            let mut stdout = std::io::stdout().lock();
+            writeln!(stdout, "{content}").map_err(|e| AgentError::io("写入 stdout 失败", e))?;
+            Ok(Outcome {
+                output: Value::Null,
+                code: EXIT_OK,
+            })
+        }
+    }
+}
+
+fn import(input: PathBuf, password: Option<String>) -> Result<Outcome, AgentError> {
+    let content = if input.as_os_str() == "-" {
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buf)
+            .map_err(|e| AgentError::io("读取 stdin 失败", e))?;
+        buf
+    } else {
+        std::fs::read_to_string(&input)
+            .map_err(|e| AgentError::io(format!("读取 {} 失败", input.display()), e))?
+    };
+
+    let json = match password {
+        Some(password) => backup::decrypt_config(&content, &password)?,
+        None => content,
+    };
+    let config: backup::ExportedConfig = serde_json::from_str(&json).map_err(|e| {
+        AgentError::InvalidInput(format!(
+            "配置文件格式无效（加密文件需要 --password）: {}",
+            e
+        ))
+    })?;
+
+    let result = backup::restore_account_files(config.backups)?;
+    let code = if result.failed.is_empty() {
+        EXIT_OK
+    } else {
+        EXIT_ERROR
+    };
+    Outcome::with_code(result, code)
+}
+
+async fn run(command: Command) -> Result<Outcome, AgentError> {
+    match command {
+        Command::List => list(),
+        Command::Targets => Outcome::ok(targets::load_targets()?),
+        Command::Current => current(),
+        Command::Save => save(),
+        Command::Switch {
+            email,
+            exit_timeout_ms,
+        } => switch_to(&email, exit_timeout_ms).await,
+        Command::Refresh { email } => refresh(&email).await,
+        Command::Login { no_browser } => login(no_browser).await,
+        Command::Quota { refresh } => quota(refresh).await,
+        Command::Best {
+            model,
+            refresh,
+            exit_timeout_ms,
+        } => best(&model, refresh, exit_timeout_ms).await,
+        Command::Logout {
+            no_backup,
+            start,
+            exit_timeout_ms,
+        } => logout(no_backup, start, exit_timeout_ms).await,
+        Command::Export { output, password } => export(output, password),
+        Command::Import { input, password } => import(input, password),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    // 日志写到 stderr，stdout 只输出 JSON 结果；默认只显示警告（可被 RUST_LOG 覆盖）
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .compact()
+        .init();
+
+    if let Err(e) = antigravity_agent_lib::directories::migrate_legacy_accounts_if_needed() {
+        tracing::warn!("⚠️ 账户目录迁移检查失败: {}", e);
+    }
+
+    if let Some(target) = cli.target {
+        targets::set_target_override(target);
+    }
+
+    match run(cli.command).await {
+        Ok(outcome) => {
+            if !outcome.output.is_null() {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&outcome.output).unwrap_or_default()
+                );
+            }
+            ExitCode::from(outcome.code)
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({ "error": e }))
+                    .unwrap_or_default()
+            );
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
@@ -1,88 +1,49 @@
 //! 账户基础命令：查询、备份、恢复、切换、清理
 
-use crate::antigravity::account::decode_jetski_state_proto;
-use base64::Engine;
-use prost::Message;
-use rusqlite::{Connection, OptionalExtension};
-use serde_json::{from_str, Value};
-use std::fs;
-use tauri::State;
+use crate::antigravity::account::{self, decode_jetski_state_proto};
+use crate::antigravity::history::{self, SnapshotVersion};
+use crate::antigravity::token_status::TokenStatus;
+use crate::error::AgentError;
+use serde_json::Value;
 use tracing::instrument;
 
 /// 获取所有 Antigravity 账户（解码 jetskiStateSync.agentManagerInitState，返回完整 SessionResponse JSON）
+///
+/// 每个账户附带账户索引中的 `metadata` 和令牌状态 `tokenStatus`，按置顶、最近活动时间排序
 #[tauri::command]
 #[instrument]
-pub async fn get_antigravity_accounts(
-    state: State<'_, crate::AppState>,
-) -> Result<Vec<Value>, String> {
+pub async fn get_antigravity_accounts() -> Result<Vec<Value>, AgentError> {
     tracing::debug!("📋 开始获取所有 Antigravity 账户");
 
     let start_time = std::time::Instant::now();
 
     let result = async {
-        let mut accounts: Vec<(std::time::SystemTime, Value)> = Vec::new();
-
-        // 获取备份目录路径
-        let antigravity_dir = state.config_dir.join("antigravity-accounts");
-
-        if !antigravity_dir.exists() {
-            tracing::info!("📂 备份目录不存在，返回空列表");
-            return Ok(Vec::new());
-        }
-
-        // 读取目录中的所有 JSON 文件
-        let entries =
-            fs::read_dir(&antigravity_dir).map_err(|e| format!("读取备份目录失败: {}", e))?;
-
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
-            let path = entry.path();
-
-            // 只处理 JSON 文件
-            if path.extension().is_some_and(|ext| ext == "json") {
-                let file_name = match path.file_stem() {
-                    Some(name) => name.to_string_lossy().to_string(),
-                    None => continue,
+        let decoded_with_metadata: Vec<Value> = account::list_saved_accounts()?
+            .into_iter()
+            .filter_map(|entry| {
+                let mut decoded = match entry.decode() {
+                    Ok(decoded) => decoded,
+                    Err(e) => {
+                        tracing::warn!(email = %entry.summary.email, error = %e, "跳过无法解码的账户");
+                        return None;
+                    }
                 };
+                if let Value::Object(map) = &mut decoded {
+                    map.insert("metadata".to_string(), serde_json::json!(entry.metadata));
+                    map.insert(
+                        "tokenStatus".to_string(),
+                        serde_json::json!(TokenStatus::from_expiry(
+                            entry.summary.expiry_timestamp
+                        )),
+                    );
+                }
+                Some(decoded)
+            })
+            .collect();
 
-                tracing::debug!("📄 正在解析备份文件: {}", file_name);
-
-                // 读取并解析 JSON 文件
-                let content = fs::read_to_string(&path)
-                    .map_err(|e| format!("读取文件失败 {}: {}", file_name, e))?;
-
-                let backup_data: Value = from_str(&content)
-                    .map_err(|e| format!("解析 JSON 失败 {}: {}", file_name, e))?;
-
-                let jetski_state = backup_data
-                    .get("jetskiStateSync.agentManagerInitState")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| {
-                        format!(
-                            "备份文件 {} 缺少 jetskiStateSync.agentManagerInitState",
-                            file_name
-                        )
-                    })?;
-
-                let decoded = decode_jetski_state_proto(jetski_state)?;
-
-                let modified_time = fs::metadata(&path)
-                    .and_then(|m| m.modified())
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-
-                accounts.push((modified_time, decoded));
-
-                tracing::info!("✅ 成功解析账户: {}", file_name);
-            }
-        }
-
-        // 按文件修改时间排序（最新的在前），仅返回解码后的对象
-        accounts.sort_by(|a, b| b.0.cmp(&a.0));
-        let decoded_only: Vec<Value> = accounts.into_iter().map(|(_, decoded)| decoded).collect();
-
-        tracing::debug!("🎉 成功加载 {} 个账户", decoded_only.len());
+        tracing::debug!("🎉 成功加载 {} 个账户", decoded_with_metadata.len());
 
-        Ok(decoded_only)
+        Ok(decoded_with_metadata)
     }
     .await;
 
@@ -111,48 +72,14 @@ pub async fn get_antigravity_accounts(
 /// 获取当前 Antigravity 账户信息
 #[tauri::command]
 #[instrument]
-pub async fn get_current_antigravity_account_info() -> Result<Value, String> {
+pub async fn get_current_antigravity_account_info() -> Result<Value, AgentError> {
     tracing::info!("开始获取当前 Antigravity 信息");
 
     let start_time = std::time::Instant::now();
 
     let result = async {
-        // 尝试获取 Antigravity 状态数据库路径
-        let app_data = match crate::platform::get_antigravity_db_path() {
-            Some(path) => path,
-            None => {
-                // 如果主路径不存在，尝试其他可能的位置
-                let possible_paths = crate::platform::get_all_antigravity_db_paths();
-                if possible_paths.is_empty() {
-                    return Err("未找到Antigravity安装位置".to_string());
-                }
-                possible_paths[0].clone()
-            }
-        };
-
-        if !app_data.exists() {
-            return Err(format!(
-                "Antigravity 状态数据库文件不存在: {}",
-                app_data.display()
-            ));
-        }
-
-        // 连接到 SQLite 数据库并获取认证信息
-        let conn = Connection::open(&app_data)
-            .map_err(|e| format!("连接数据库失败 ({}): {}", app_data.display(), e))?;
-
-        // jetski 状态（可选）
-        let jetski_state: Option<String> = conn
-            .query_row(
-                "SELECT value FROM ItemTable WHERE key = 'jetskiStateSync.agentManagerInitState'",
-                [],
-                |row| row.get(0),
-            )
-            .optional()
-            .map_err(|e| format!("查询 jetskiStateSync.agentManagerInitState 失败: {}", e))?;
-
-        let state_str = jetski_state
-            .ok_or_else(|| "未找到 jetskiStateSync.agentManagerInitState".to_string())?;
+        // jetski 状态
+        let state_str = account::read_current_agent_state()?;
 
         // 解码 jetski 状态（base64 + proto）；失败直接报错
         let decoded = decode_jetski_state_proto(&state_str)?;
@@ -185,87 +112,20 @@ pub async fn get_current_antigravity_account_info() -> Result<Value, String> {
 /// 备份当前 Antigravity 账户
 #[tauri::command]
 #[instrument]
-pub async fn save_antigravity_current_account() -> Result<String, String> {
+pub async fn save_antigravity_current_account() -> Result<String, AgentError> {
     tracing::info!("📥 开始保存 jetskiStateSync.agentManagerInitState");
 
     let start_time = std::time::Instant::now();
 
     let result = async {
-        // 尝试获取 Antigravity 状态数据库路径
-        let app_data = match crate::platform::get_antigravity_db_path() {
-            Some(path) => path,
-            None => {
-                // 如果主路径不存在，尝试其他可能的位置
-                let possible_paths = crate::platform::get_all_antigravity_db_paths();
-                if possible_paths.is_empty() {
-                    return Err("未找到Antigravity安装位置".to_string());
-                }
-                possible_paths[0].clone()
-            }
-        };
-
-        if !app_data.exists() {
-            return Err(format!(
-                "Antigravity 状态数据库文件不存在: {}",
-                app_data.display()
-            ));
-        }
-
-        // 连接到 SQLite 数据库并获取认证信息
-        let conn = Connection::open(&app_data)
-            .map_err(|e| format!("连接数据库失败 ({}): {}", app_data.display(), e))?;
-
         // jetski 状态（必需）
-        let jetski_state: String = conn
-            .query_row(
-                "SELECT value FROM ItemTable WHERE key = 'jetskiStateSync.agentManagerInitState'",
-                [],
-                |row| row.get(0),
-            )
-            .optional()
-            .map_err(|e| format!("查询 jetskiStateSync.agentManagerInitState 失败: {}", e))?
-            .ok_or_else(|| "未找到 jetskiStateSync.agentManagerInitState".to_string())?;
-
-        // 从 jetski proto 解码邮箱（仅用于文件名）
-        let bytes = base64::engine::general_purpose::STANDARD
-            .decode(jetski_state.trim())
-            .map_err(|e| format!("jetskiStateSync Base64 解码失败: {}", e))?;
-        let msg = crate::proto::SessionResponse::decode(bytes.as_slice())
-            .map_err(|e| format!("jetskiStateSync Protobuf 解码失败: {}", e))?;
-
-        let email = msg
-            .context
-            .as_ref()
-            .and_then(|c| {
-                if c.email.is_empty() {
-                    None
-                } else {
-                    Some(c.email.as_str())
-                }
-            })
-            .ok_or_else(|| "jetskiStateSync 中未找到邮箱字段，无法确定备份文件名".to_string())?;
-
-        // 直接保存原始字符串，不解码，文件名与原逻辑保持：{email}.json
-        let accounts_dir = crate::directories::get_accounts_directory();
-        if let Err(e) = std::fs::create_dir_all(&accounts_dir) {
-            return Err(format!("创建账户目录失败: {}", e));
-        }
-
-        let account_file = accounts_dir.join(format!("{email}.json"));
-        let content = serde_json::json!({
-            "jetskiStateSync.agentManagerInitState": jetski_state
-        });
-        std::fs::write(
-            &account_file,
-            serde_json::to_string_pretty(&content).unwrap(),
-        )
-        .map_err(|e| format!("写入 jetski 状态失败: {}", e))?;
+        let jetski_state = account::read_current_agent_state()?;
 
+        let saved = account::save_agent_state(&jetski_state)?;
         let message = format!(
             "已保存 jetskiStateSync.agentManagerInitState 到 {}",
-            account_file.display()
+            saved.account_file.display()
         );
-        tracing::info!(file = %account_file.display(), "✅ 保存 jetski 状态完成");
         Ok(message)
     }
     .await;
@@ -294,74 +154,73 @@ pub async fn save_antigravity_current_account() -> Result<String, String> {
 
 /// 清除所有 Antigravity 数据
 #[tauri::command]
-pub async fn clear_all_antigravity_data() -> Result<String, String> {
+pub async fn clear_all_antigravity_data() -> Result<String, AgentError> {
     crate::antigravity::cleanup::clear_all_antigravity_data().await
 }
 
 /// 恢复 Antigravity 账户
+///
+/// 指定 `version_id` 时直接从该历史版本恢复数据库，成功后再将其写回账户文件
 #[tauri::command]
-pub async fn restore_antigravity_account(account_name: String) -> Result<String, String> {
-    tracing::debug!(target: "account::restore", account_name = %account_name, "调用 restore_antigravity_account");
-
-    // 1. 构建备份文件路径
-    let accounts_dir = crate::directories::get_accounts_directory();
-    let account_file = accounts_dir.join(format!("{account_name}.json"));
-
-    // 2. 调用统一的恢复函数
-    crate::antigravity::restore::save_antigravity_account_to_file(account_file).await
-}
-
-/// 切换到 Antigravity 账户（调用 restore_antigravity_account）
-#[tauri::command]
-pub async fn switch_to_antigravity_account(account_name: String) -> Result<String, String> {
-    crate::log_async_command!("switch_to_antigravity_account", async {
-        // 1. 关闭 Antigravity 进程 (如果存在)
-        let kill_result = match crate::platform::kill_antigravity_processes() {
-            Ok(result) => {
-                if result.contains("not found") || result.contains("未找到") {
-                    tracing::debug!(target: "account::switch::step1", "Antigravity 进程未运行，跳过关闭步骤");
-                    "Antigravity 进程未运行".to_string()
-                } else {
-                    tracing::debug!(target: "account::switch::step1", result = %result, "进程关闭完成");
-                    result
-                }
-            }
-            Err(e) => {
-                if e.contains("not found") || e.contains("未找到") {
-                    tracing::debug!(target: "account::switch::step1", "Antigravity 进程未运行，跳过关闭步骤");
-                    "Antigravity 进程未运行".to_string()
-                } else {
-                    tracing::error!(target: "account::switch::step1", error = %e, "关闭进程时发生错误");
-                    return Err(format!("关闭进程时发生错误: {}", e));
-                }
-            }
+pub async fn restore_antigravity_account(
+    account_name: String,
+    version_id: Option<String>,
+) -> Result<String, AgentError> {
+    crate::log_async_command!("restore_antigravity_account", async {
+        // 1. 构建备份文件路径
+        let account_file = crate::directories::get_account_file(&account_name)?;
+
+        let Some(version_id) = version_id.as_deref() else {
+            // 2. 调用统一的恢复函数
+            return crate::antigravity::restore::save_antigravity_account_to_file(account_file)
+                .await;
         };
 
-        // 等待一秒确保进程完全关闭
-        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-
-        // 2. 恢复指定账户到 Antigravity 数据库
-        let restore_result = restore_antigravity_account(account_name.clone()).await?;
-        tracing::debug!(target: "account::switch::step2", result = %restore_result, "账户数据恢复完成");
-
-        // 等待一秒确保数据库操作完成
-        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+        // 2. 从历史版本恢复数据库，失败时账户文件保持不变
+        let agent_state = history::read_version_agent_state(&account_name, version_id)?;
+        let message = crate::antigravity::restore::restore_agent_state_to_database(&agent_state)?;
 
-        // 3. 重新启动 Antigravity 进程
-        let start_result = crate::antigravity::starter::start_antigravity();
-        let start_message = match start_result {
-            Ok(result) => {
-                tracing::debug!(target: "account::switch::step3", result = %result, "Antigravity 启动成功");
-                result
-            }
+        // 3. 历史版本成为当前版本；数据库已恢复，写回失败只提示
+        match history::restore_version_to_account_file(
+            &account_name,
+            version_id,
+            &agent_state,
+            &account_file,
+        ) {
+            Ok(()) => Ok(message),
             Err(e) => {
-                tracing::warn!(target: "account::switch::step3", error = %e, "Antigravity 启动失败");
-                format!("启动失败: {}", e)
+                tracing::warn!(target: "account::history", error = %e, "历史版本写回账户文件失败");
+                Ok(format!("{}（账户文件未更新: {}）", message, e))
             }
-        };
+        }
+    })
+}
+
+/// 列出账户的历史快照版本（最新的在前）
+#[tauri::command]
+pub async fn list_antigravity_account_versions(
+    account_name: String,
+) -> Result<Vec<SnapshotVersion>, AgentError> {
+    crate::log_async_command!("list_antigravity_account_versions", async {
+        history::list_versions(&account_name)
+    })
+}
 
-        let final_message = format!("{} -> {} -> {}", kill_result, restore_result, start_message);
+/// 切换到 Antigravity 账户（快照 → 关闭进程 → 单事务恢复 → 启动，失败自动回滚）
+///
+/// `exit_timeout_ms` 为等待进程退出的超时时间，未指定时使用默认值
+#[tauri::command]
+pub async fn switch_to_antigravity_account(
+    account_name: String,
+    exit_timeout_ms: Option<u64>,
+) -> Result<crate::antigravity::switch::SwitchResult, AgentError> {
+    crate::log_async_command!("switch_to_antigravity_account", async {
+        let exit_timeout = exit_timeout_ms
+            .map(std::time::Duration::from_millis)
+            .unwrap_or(crate::platform::DEFAULT_EXIT_TIMEOUT);
 
-        Ok(final_message)
+        let result =
+            crate::antigravity::switch::switch_saved_account(&account_name, exit_timeout).await;
+        Ok(result)
     })
 }
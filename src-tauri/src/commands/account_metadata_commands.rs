@@ -0,0 +1,96 @@
+//! 账户元数据命令：显示名称、备注、标签、置顶
+//! 元数据保存在 antigravity-accounts/meta/index.json，不修改账户文件本身
+
+use crate::antigravity::account_index;
+use crate::error::AgentError;
+use crate::AntigravityAccount;
+
+/// 单个标签的最大长度
+const MAX_TAG_LEN: usize = 32;
+
+/// 修改已保存账户的元数据并返回修改后的结果
+fn update_account_metadata(
+    email: &str,
+    update_fn: impl FnOnce(&mut AntigravityAccount),
+) -> Result<AntigravityAccount, AgentError> {
+    let account_file = crate::directories::get_account_file(email)?;
+    if !account_file.exists() {
+        return Err(AgentError::AccountNotFound(email.to_string()));
+    }
+
+    account_index::update(|index| {
+        let account = index.entry(email);
+        update_fn(account);
+        account.clone()
+    })
+}
+
+/// 去除首尾空白，空字符串视为未设置
+fn normalize_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// 设置账户显示名称（传空则恢复显示邮箱）
+#[tauri::command]
+pub async fn set_account_label(
+    email: String,
+    label: Option<String>,
+) -> Result<AntigravityAccount, AgentError> {
+    crate::log_async_command!("set_account_label", async {
+        let label = normalize_text(label);
+        update_account_metadata(&email, |account| account.label = label)
+    })
+}
+
+/// 设置账户备注
+#[tauri::command]
+pub async fn set_account_notes(
+    email: String,
+    notes: Option<String>,
+) -> Result<AntigravityAccount, AgentError> {
+    crate::log_async_command!("set_account_notes", async {
+        let notes = normalize_text(notes);
+        update_account_metadata(&email, |account| account.notes = notes)
+    })
+}
+
+/// 设置账户标签（去重并保持传入顺序）
+#[tauri::command]
+pub async fn set_account_tags(
+    email: String,
+    tags: Vec<String>,
+) -> Result<AntigravityAccount, AgentError> {
+    crate::log_async_command!("set_account_tags", async {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = tag.trim();
+            if tag.is_empty() {
+                continue;
+            }
+            if tag.chars().count() > MAX_TAG_LEN {
+                return Err(AgentError::InvalidInput(format!(
+                    "标签 '{}' 超过 {} 个字符",
+                    tag, MAX_TAG_LEN
+                )));
+            }
+            if !normalized.iter().any(|t| t == tag) {
+                normalized.push(tag.to_string());
+            }
+        }
+
+        update_account_metadata(&email, |account| account.tags = normalized)
+    })
+}
+
+/// 设置账户是否置顶
+#[tauri::command]
+pub async fn set_account_pinned(
+    email: String,
+    pinned: bool,
+) -> Result<AntigravityAccount, AgentError> {
+    crate::log_async_command!("set_account_pinned", async {
+        update_account_metadata(&email, |account| account.pinned = pinned)
+    })
+}
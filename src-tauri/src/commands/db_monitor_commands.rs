@@ -2,37 +2,51 @@
 //! 提供数据库监控状态的查询和控制功能
 
 use crate::db_monitor::DatabaseMonitor;
+use crate::error::AgentError;
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 
 /// 获取数据库监控运行状态
 #[tauri::command]
-pub async fn is_database_monitoring_running(_app: AppHandle) -> Result<bool, String> {
+pub async fn is_database_monitoring_running(app: AppHandle) -> Result<bool, AgentError> {
     crate::log_async_command!("is_database_monitoring_running", async {
-        // 智能监控现在是默认功能，总是返回 true
-        Ok(true)
+        let monitor = app.state::<Arc<DatabaseMonitor>>();
+        Ok(monitor.is_monitoring().await)
     })
 }
 
 /// 手动启动数据库监控
 #[tauri::command]
-pub async fn start_database_monitoring(app: AppHandle) -> Result<String, String> {
+pub async fn start_database_monitoring(app: AppHandle) -> Result<String, AgentError> {
     crate::log_async_command!("start_database_monitoring", async {
         let monitor = app.state::<Arc<DatabaseMonitor>>();
         monitor
             .start_monitoring()
             .await
-            .map_err(|e| format!("启动监控失败: {}", e))?;
+            .map_err(|e| AgentError::Internal(format!("启动监控失败: {}", e)))?;
         Ok("数据库监控已启动".to_string())
     })
 }
 
 /// 手动停止数据库监控
 #[tauri::command]
-pub async fn stop_database_monitoring(app: AppHandle) -> Result<String, String> {
+pub async fn stop_database_monitoring(app: AppHandle) -> Result<String, AgentError> {
     crate::log_async_command!("stop_database_monitoring", async {
         let monitor = app.state::<Arc<DatabaseMonitor>>();
         monitor.stop_monitoring().await;
         Ok("数据库监控已停止".to_string())
     })
 }
+
+/// 设置 database-changed 事件只推送哪些键（传 null 恢复为全部监控键）
+#[tauri::command]
+pub async fn set_database_subscription(
+    app: AppHandle,
+    keys: Option<Vec<String>>,
+) -> Result<(), AgentError> {
+    crate::log_async_command!("set_database_subscription", async {
+        let monitor = app.state::<Arc<DatabaseMonitor>>();
+        monitor.set_subscribed_keys(keys);
+        Ok(())
+    })
+}
@@ -1,22 +1,23 @@
 /// 日志和加密命令
 /// 负责日志管理、文件写入、数据加密解密等功能
+use crate::error::AgentError;
 use std::fs;
 use std::path::Path;
 
 /// 写入文本文件
 /// 将文本内容写入指定路径的文件
 #[tauri::command]
-pub async fn write_text_file(path: String, content: String) -> Result<String, String> {
+pub async fn write_text_file(path: String, content: String) -> Result<String, AgentError> {
     crate::log_async_command!("write_text_file", async {
         let file_path = Path::new(&path);
 
         // 确保父目录存在
         if let Some(parent) = file_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+            fs::create_dir_all(parent).map_err(|e| AgentError::io("创建目录失败", e))?;
         }
 
         // 写入文件
-        fs::write(file_path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+        fs::write(file_path, content).map_err(|e| AgentError::io("写入文件失败", e))?;
 
         Ok(format!("文件写入成功: {}", path))
     })
@@ -25,7 +26,7 @@ pub async fn write_text_file(path: String, content: String) -> Result<String, St
 /// 写入前端日志
 /// 将前端日志条目写入到后端日志系统
 #[tauri::command]
-pub async fn write_frontend_log(log_entry: serde_json::Value) -> Result<(), String> {
+pub async fn write_frontend_log(log_entry: serde_json::Value) -> Result<(), AgentError> {
     use tracing::{debug, error, info, warn};
 
     // 解析日志级别
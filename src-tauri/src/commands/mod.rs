@@ -4,6 +4,9 @@ pub mod account_commands;
 // 账户管理命令
 pub mod account_manage_commands;
 
+// 账户元数据命令
+pub mod account_metadata_commands;
+
 // 进程管理命令
 pub mod process_commands;
 
@@ -24,14 +27,27 @@ pub mod settings_commands;
 
 // 数据库监控命令
 pub mod db_monitor_commands;
+
+// 账户轮换命令
+pub mod rotation_commands;
+
+// OAuth 命令
+pub mod oauth_commands;
+
+// 配额命令
+pub mod quota_commands;
 // 语言服务器相关命令（在 src/language_server 下）
 
 // 重新导出所有命令，保持与 main.rs 的兼容性
 pub use account_commands::*;
 pub use account_manage_commands::*;
+pub use account_metadata_commands::*;
 pub use db_monitor_commands::*;
 pub use logging_commands::*;
+pub use oauth_commands::*;
 pub use platform_commands::*;
 pub use process_commands::*;
+pub use quota_commands::*;
+pub use rotation_commands::*;
 pub use settings_commands::*;
 pub use tray_commands::*;
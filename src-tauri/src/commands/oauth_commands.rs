@@ -0,0 +1,33 @@
+//! OAuth 命令：刷新已保存账户的令牌、浏览器登录
+
+use crate::antigravity::account::SavedAccount;
+use crate::error::AgentError;
+use crate::oauth::{self, RefreshedAccount};
+
+/// 刷新已保存账户的 access token 并写回账户文件
+#[tauri::command]
+pub async fn refresh_antigravity_account_token(
+    account_name: String,
+) -> Result<RefreshedAccount, AgentError> {
+    crate::log_async_command!("refresh_antigravity_account_token", async {
+        oauth::refresh_saved_account(&account_name).await
+    })
+}
+
+/// 在浏览器中完成 Google 登录（PKCE），生成并保存账户快照
+///
+/// 保存后即可像其他已保存账户一样切换，无需先在 Antigravity 中登录
+#[tauri::command]
+pub async fn login_with_google_oauth() -> Result<SavedAccount, AgentError> {
+    crate::log_async_command!("login_with_google_oauth", async {
+        oauth::login::login_with_browser().await
+    })
+}
+
+/// 取消正在进行的浏览器登录
+#[tauri::command]
+pub async fn cancel_google_oauth_login() -> Result<bool, AgentError> {
+    crate::log_async_command!("cancel_google_oauth_login", async {
+        Ok::<_, AgentError>(oauth::login::cancel_login())
+    })
+}
@@ -1,11 +1,15 @@
 //! 平台支持命令
 //! 负责获取平台信息、安装位置验证等跨平台操作
 
+use crate::antigravity::targets::{self, AntigravityTarget, LaunchProfile, TargetsConfig};
+use crate::error::AgentError;
 use serde_json::Value;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
 
 /// 获取平台信息
 #[tauri::command]
-pub async fn get_platform_info() -> Result<Value, String> {
+pub async fn get_platform_info() -> Result<Value, AgentError> {
     let os_type = std::env::consts::OS;
     let arch = std::env::consts::ARCH;
     let family = std::env::consts::FAMILY;
@@ -27,7 +31,7 @@ pub async fn get_platform_info() -> Result<Value, String> {
 
 /// 查找 Antigravity 安装位置
 #[tauri::command]
-pub async fn find_antigravity_installations() -> Result<Vec<String>, String> {
+pub async fn find_antigravity_installations() -> Result<Vec<String>, AgentError> {
     let paths = crate::platform::find_antigravity_installations();
     Ok(paths
         .iter()
@@ -37,7 +41,7 @@ pub async fn find_antigravity_installations() -> Result<Vec<String>, String> {
 
 /// 验证 Antigravity 可执行文件路径
 #[tauri::command]
-pub async fn validate_antigravity_executable(path: String) -> Result<bool, String> {
+pub async fn validate_antigravity_executable(path: String) -> Result<bool, AgentError> {
     Ok(crate::antigravity::path_config::validate_executable_path(
         &path,
     ))
@@ -45,8 +49,11 @@ pub async fn validate_antigravity_executable(path: String) -> Result<bool, Strin
 
 /// 检测 Antigravity 安装状态（数据库路径）
 #[tauri::command]
-pub async fn detect_antigravity_installation() -> Result<serde_json::Value, String> {
-    // 自动检测 Antigravity 数据库路径
+pub async fn detect_antigravity_installation() -> Result<serde_json::Value, AgentError> {
+    // 活动目标或自定义数据目录优先于自动检测
+    let is_custom_path = matches!(targets::active_target(), Ok(Some(_)))
+        || crate::platform::get_custom_antigravity_data_dir().is_some();
+
     if let Some(db_path) = crate::platform::get_antigravity_db_path() {
         if db_path.exists() {
             let data_dir = db_path
@@ -60,7 +67,7 @@ pub async fn detect_antigravity_installation() -> Result<serde_json::Value, Stri
             return Ok(serde_json::json!({
                 "found": true,
                 "path": data_dir,
-                "isCustomPath": false
+                "isCustomPath": is_custom_path
             }));
         }
     }
@@ -70,13 +77,13 @@ pub async fn detect_antigravity_installation() -> Result<serde_json::Value, Stri
     Ok(serde_json::json!({
         "found": false,
         "path": null,
-        "isCustomPath": false
+        "isCustomPath": is_custom_path
     }))
 }
 
 /// 检测 Antigravity 可执行文件
 #[tauri::command]
-pub async fn detect_antigravity_executable() -> Result<serde_json::Value, String> {
+pub async fn detect_antigravity_executable() -> Result<serde_json::Value, AgentError> {
     // 1. 尝试从配置读取自定义可执行文件路径
     let custom_exec = crate::antigravity::path_config::get_custom_executable_path().unwrap_or(None);
 
@@ -114,10 +121,13 @@ pub async fn detect_antigravity_executable() -> Result<serde_json::Value, String
 
 /// 保存用户自定义的 Antigravity 可执行文件路径
 #[tauri::command]
-pub async fn save_antigravity_executable(path: String) -> Result<String, String> {
+pub async fn save_antigravity_executable(path: String) -> Result<String, AgentError> {
     // 1. 验证路径有效性
     if !crate::antigravity::path_config::validate_executable_path(&path) {
-        return Err(format!("路径无效：文件 '{}' 不存在或不是可执行文件", path));
+        return Err(AgentError::InvalidInput(format!(
+            "路径无效：文件 '{}' 不存在或不是可执行文件",
+            path
+        )));
     }
 
     // 2. 保存路径到配置
@@ -126,12 +136,117 @@ pub async fn save_antigravity_executable(path: String) -> Result<String, String>
     Ok(format!("已保存 Antigravity 可执行文件路径: {}", path))
 }
 
+/// 验证 Antigravity 数据目录（需包含带 ItemTable 表的 state.vscdb）
+#[tauri::command]
+pub async fn validate_antigravity_data_dir(path: String) -> Result<bool, AgentError> {
+    Ok(crate::antigravity::path_config::validate_data_dir(&path).is_ok())
+}
+
+/// 保存用户自定义的 Antigravity 数据目录
+#[tauri::command]
+pub async fn save_antigravity_data_dir(app: AppHandle, path: String) -> Result<String, AgentError> {
+    crate::log_async_command!("save_antigravity_data_dir", async {
+        // 1. 验证目录中的数据库
+        crate::antigravity::path_config::validate_data_dir(&path)
+            .map_err(|e| AgentError::InvalidInput(format!("数据目录无效：{}", e)))?;
+
+        // 2. 保存路径到配置，监控切换到新的数据库
+        crate::antigravity::path_config::save_custom_data_dir(path.clone())?;
+        restart_database_monitor(&app).await;
+
+        Ok(format!("已保存 Antigravity 数据目录: {}", path))
+    })
+}
+
+/// 清除自定义数据目录，恢复自动检测
+#[tauri::command]
+pub async fn clear_antigravity_data_dir(app: AppHandle) -> Result<String, AgentError> {
+    crate::log_async_command!("clear_antigravity_data_dir", async {
+        crate::antigravity::path_config::clear_custom_data_dir()?;
+        restart_database_monitor(&app).await;
+        Ok("已恢复自动检测 Antigravity 数据目录".to_string())
+    })
+}
+
 /// 获取当前配置的路径
 #[tauri::command]
-pub async fn get_current_paths() -> Result<serde_json::Value, String> {
+pub async fn get_current_paths() -> Result<serde_json::Value, AgentError> {
     let exec_path = crate::antigravity::path_config::get_custom_executable_path().unwrap_or(None);
+    let data_dir = crate::antigravity::path_config::get_custom_data_dir().unwrap_or(None);
 
     Ok(serde_json::json!({
-        "executablePath": exec_path
+        "executablePath": exec_path,
+        "dataDir": data_dir
     }))
 }
+
+/// 获取已配置的 Antigravity 目标及活动目标
+#[tauri::command]
+pub async fn list_antigravity_targets() -> Result<TargetsConfig, AgentError> {
+    crate::log_async_command!("list_antigravity_targets", async {
+        targets::load_targets()
+    })
+}
+
+/// 新增或更新 Antigravity 目标
+#[tauri::command]
+pub async fn save_antigravity_target(
+    app: AppHandle,
+    target: AntigravityTarget,
+) -> Result<TargetsConfig, AgentError> {
+    crate::log_async_command!("save_antigravity_target", async {
+        let name = target.name.trim().to_string();
+        let config = targets::save_target(target)?;
+        // 修改的是活动目标时，监控需要切换到新的数据目录
+        if config.active.as_deref() == Some(name.as_str()) {
+            restart_database_monitor(&app).await;
+        }
+        Ok(config)
+    })
+}
+
+/// 保存未选择目标时使用的启动配置
+#[tauri::command]
+pub async fn save_default_launch_profile(
+    profile: LaunchProfile,
+) -> Result<TargetsConfig, AgentError> {
+    crate::log_async_command!("save_default_launch_profile", async {
+        targets::save_default_launch_profile(profile)
+    })
+}
+
+/// 删除 Antigravity 目标
+#[tauri::command]
+pub async fn remove_antigravity_target(
+    app: AppHandle,
+    name: String,
+) -> Result<TargetsConfig, AgentError> {
+    crate::log_async_command!("remove_antigravity_target", async {
+        let was_active = targets::load_targets()?.active.as_deref() == Some(name.as_str());
+        let config = targets::remove_target(&name)?;
+        if was_active {
+            restart_database_monitor(&app).await;
+        }
+        Ok(config)
+    })
+}
+
+/// 设置活动目标（传 null 恢复为自动检测），并让数据库监控切换到该目标
+#[tauri::command]
+pub async fn set_active_antigravity_target(
+    app: AppHandle,
+    name: Option<String>,
+) -> Result<TargetsConfig, AgentError> {
+    crate::log_async_command!("set_active_antigravity_target", async {
+        let config = targets::set_active_target(name)?;
+        restart_database_monitor(&app).await;
+        Ok(config)
+    })
+}
+
+async fn restart_database_monitor(app: &AppHandle) {
+    let monitor = app.state::<Arc<crate::db_monitor::DatabaseMonitor>>();
+    if let Err(e) = monitor.restart_monitoring().await {
+        tracing::error!(target: "app::targets", error = %e, "重启数据库监控失败");
+    }
+}
@@ -1,5 +1,11 @@
-/// 检查 Antigravity 进程是否正在运行
+/// 检查活动目标的 Antigravity 进程是否正在运行
 #[tauri::command]
 pub async fn is_antigravity_running() -> bool {
-    crate::platform::is_antigravity_running()
+    match crate::platform::ProcessScope::current() {
+        Ok(scope) => crate::platform::is_antigravity_running(&scope),
+        Err(e) => {
+            tracing::warn!("⚠️ 无法确定活动目标: {}", e);
+            false
+        }
+    }
 }
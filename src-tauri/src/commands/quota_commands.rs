@@ -0,0 +1,44 @@
+//! 配额命令：查询已保存账户的剩余配额、切换到配额最多的账户
+
+use crate::best_account::{self, BestAccountSwitch};
+use crate::error::AgentError;
+use crate::quota::{self, AccountQuota};
+
+/// 获取所有已保存账户的配额
+///
+/// 默认返回缓存结果；`refresh` 为 true 时立即重新查询并更新缓存
+#[tauri::command]
+pub async fn get_accounts_quota(
+    app: tauri::AppHandle,
+    refresh: Option<bool>,
+) -> Result<Vec<AccountQuota>, AgentError> {
+    crate::log_async_command!("get_accounts_quota", async {
+        if !refresh.unwrap_or(false) {
+            return Ok(quota::cached_quotas());
+        }
+
+        let quotas = quota::refresh_all().await?;
+        if let Err(e) = crate::system_tray::refresh_tray_menu(&app) {
+            tracing::warn!(target: "quota", error = %e, "配额更新后刷新托盘菜单失败");
+        }
+        Ok::<_, AgentError>(quotas)
+    })
+}
+
+/// 切换到指定模型剩余配额最多的已保存账户（跳过令牌已过期的账户）
+///
+/// 返回排名、被跳过的账户和选中原因；`refresh` 为 true 时先重新查询配额
+#[tauri::command]
+pub async fn switch_to_best_account(
+    model: String,
+    refresh: Option<bool>,
+    exit_timeout_ms: Option<u64>,
+) -> Result<BestAccountSwitch, AgentError> {
+    crate::log_async_command!("switch_to_best_account", async {
+        let exit_timeout = exit_timeout_ms
+            .map(std::time::Duration::from_millis)
+            .unwrap_or(crate::platform::DEFAULT_EXIT_TIMEOUT);
+
+        best_account::switch_to_best_account(&model, refresh.unwrap_or(false), exit_timeout).await
+    })
+}
@@ -0,0 +1,73 @@
+//! 账户轮换命令
+//! 查询和保存自动轮换计划，以及手动触发一次轮换
+
+use crate::app_settings::AppSettingsManager;
+use crate::error::AgentError;
+use crate::rotation::{RotationEvent, RotationPlan, RotationScheduler};
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+/// 轮换计划及下一次轮换时间
+#[derive(Debug, Clone, Serialize)]
+pub struct RotationStatus {
+    pub plan: RotationPlan,
+    #[serde(rename = "nextRotationAt")]
+    pub next_rotation_at: Option<DateTime<Utc>>,
+}
+
+fn rotation_status(plan: RotationPlan) -> RotationStatus {
+    let next_rotation_at = plan
+        .next_rotation_at(Local::now())
+        .map(|at| at.with_timezone(&Utc));
+    RotationStatus {
+        plan,
+        next_rotation_at,
+    }
+}
+
+/// 获取自动轮换计划
+#[tauri::command]
+pub async fn get_rotation_plan(app: AppHandle) -> Result<RotationStatus, AgentError> {
+    crate::log_async_command!("get_rotation_plan", async {
+        let plan = app.state::<AppSettingsManager>().get_settings().rotation;
+        Ok(rotation_status(plan))
+    })
+}
+
+/// 保存自动轮换计划，下一次检查时生效
+#[tauri::command]
+pub async fn save_rotation_plan(
+    app: AppHandle,
+    plan: RotationPlan,
+) -> Result<RotationStatus, AgentError> {
+    crate::log_async_command!("save_rotation_plan", async {
+        let mut plan = plan.normalize()?;
+        let settings_manager = app.state::<AppSettingsManager>();
+        let previous = settings_manager.get_settings().rotation;
+
+        // 轮换状态由后端维护；启用或修改时间表时从现在开始计时
+        plan.last_account = previous.last_account;
+        plan.last_rotation_at =
+            if plan.enabled && (!previous.enabled || previous.schedule != plan.schedule) {
+                Some(Utc::now().timestamp())
+            } else {
+                previous.last_rotation_at
+            };
+
+        settings_manager.update_settings(|settings| {
+            settings.rotation = plan.clone();
+        })?;
+
+        Ok(rotation_status(plan))
+    })
+}
+
+/// 立即轮换到下一个可用账户
+#[tauri::command]
+pub async fn rotate_account_now(app: AppHandle) -> Result<RotationEvent, AgentError> {
+    crate::log_async_command!("rotate_account_now", async {
+        let scheduler = app.state::<RotationScheduler>();
+        Ok::<_, AgentError>(scheduler.rotate(&app, false).await)
+    })
+}
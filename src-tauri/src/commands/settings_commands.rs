@@ -1,11 +1,12 @@
 //! 应用设置命令
 //! 负责应用程序配置的管理和存储，使用 State 模式
 
+use crate::error::AgentError;
 use tauri::{AppHandle, Manager};
 
 /// 获取静默启动状态
 #[tauri::command]
-pub async fn is_silent_start_enabled(app: AppHandle) -> Result<bool, String> {
+pub async fn is_silent_start_enabled(app: AppHandle) -> Result<bool, AgentError> {
     crate::log_async_command!("is_silent_start_enabled", async {
         let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
         let settings = settings_manager.get_settings();
@@ -15,7 +16,7 @@ pub async fn is_silent_start_enabled(app: AppHandle) -> Result<bool, String> {
 
 /// 保存静默启动状态
 #[tauri::command]
-pub async fn save_silent_start_state(app: AppHandle, enabled: bool) -> Result<bool, String> {
+pub async fn save_silent_start_state(app: AppHandle, enabled: bool) -> Result<bool, AgentError> {
     crate::log_async_command!("save_silent_start_state", async {
         let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
 
@@ -29,14 +30,97 @@ pub async fn save_silent_start_state(app: AppHandle, enabled: bool) -> Result<bo
 
 /// 获取所有应用设置
 #[tauri::command]
-pub async fn get_all_settings(app: AppHandle) -> Result<serde_json::Value, String> {
+pub async fn get_all_settings(app: AppHandle) -> Result<serde_json::Value, AgentError> {
     crate::log_async_command!("get_all_settings", async {
         let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
         let settings = settings_manager.get_settings();
 
         Ok(serde_json::json!({
             "system_tray_enabled": settings.system_tray_enabled,
-            "silent_start_enabled": settings.silent_start_enabled
+            "silent_start_enabled": settings.silent_start_enabled,
+            "db_watched_keys": settings.db_watched_keys,
+            "ipc_server_enabled": settings.ipc_server_enabled,
+            "rotation": settings.rotation,
+            "token_expiry_warning_minutes": settings.token_expiry_warning_minutes
         }))
     })
 }
+
+/// 保存数据库监控关注的键，并立即应用到正在运行的监控
+#[tauri::command]
+pub async fn save_db_watched_keys(
+    app: AppHandle,
+    keys: Vec<String>,
+) -> Result<Vec<String>, AgentError> {
+    crate::log_async_command!("save_db_watched_keys", async {
+        let mut normalized: Vec<String> = Vec::with_capacity(keys.len());
+        for key in keys {
+            let key = key.trim();
+            if !key.is_empty() && !normalized.iter().any(|k| k == key) {
+                normalized.push(key.to_string());
+            }
+        }
+
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.db_watched_keys = normalized.clone();
+        })?;
+
+        let monitor = app.state::<std::sync::Arc<crate::db_monitor::DatabaseMonitor>>();
+        Ok(monitor.set_watched_keys(normalized))
+    })
+}
+
+/// 保存令牌过期提醒的提前分钟数，立即生效
+#[tauri::command]
+pub async fn save_token_expiry_warning_minutes(
+    app: AppHandle,
+    minutes: u64,
+) -> Result<u64, AgentError> {
+    crate::log_async_command!("save_token_expiry_warning_minutes", async {
+        if minutes == 0 || minutes > 7 * 24 * 60 {
+            return Err(AgentError::InvalidInput(
+                "提前提醒时间需在 1 分钟到 7 天之间".to_string(),
+            ));
+        }
+
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.token_expiry_warning_minutes = minutes;
+        })?;
+
+        crate::antigravity::token_status::set_warning_minutes(minutes);
+        Ok(minutes)
+    })
+}
+
+/// 启用或停用本地控制 API，返回连接信息
+#[tauri::command]
+pub async fn save_ipc_server_enabled(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<crate::ipc_server::IpcServerInfo, AgentError> {
+    crate::log_async_command!("save_ipc_server_enabled", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.ipc_server_enabled = enabled;
+        })?;
+
+        let server = app.state::<crate::ipc_server::IpcServer>();
+        if enabled {
+            server.start()
+        } else {
+            Ok(server.stop())
+        }
+    })
+}
+
+/// 获取本地控制 API 的连接信息
+#[tauri::command]
+pub async fn get_ipc_server_info(
+    app: AppHandle,
+) -> Result<crate::ipc_server::IpcServerInfo, AgentError> {
+    crate::log_async_command!("get_ipc_server_info", async {
+        Ok(app.state::<crate::ipc_server::IpcServer>().info())
+    })
+}
@@ -1,9 +1,10 @@
+use crate::error::AgentError;
 use crate::system_tray::{update_tray_menu, SystemTrayManager};
 use tauri::Manager;
 
 /// 启用系统托盘
 #[tauri::command]
-pub async fn enable_system_tray(app: tauri::AppHandle) -> Result<String, String> {
+pub async fn enable_system_tray(app: tauri::AppHandle) -> Result<String, AgentError> {
     let system_tray = app.state::<SystemTrayManager>();
     system_tray.enable(&app)?;
 
@@ -12,7 +13,7 @@ pub async fn enable_system_tray(app: tauri::AppHandle) -> Result<String, String>
 
 /// 禁用系统托盘
 #[tauri::command]
-pub async fn disable_system_tray(app: tauri::AppHandle) -> Result<String, String> {
+pub async fn disable_system_tray(app: tauri::AppHandle) -> Result<String, AgentError> {
     let system_tray = app.state::<SystemTrayManager>();
     system_tray.disable(&app)?;
 
@@ -21,7 +22,7 @@ pub async fn disable_system_tray(app: tauri::AppHandle) -> Result<String, String
 
 /// 切换系统托盘状态
 #[tauri::command]
-pub async fn toggle_system_tray(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+pub async fn toggle_system_tray(app: tauri::AppHandle) -> Result<serde_json::Value, AgentError> {
     let system_tray = app.state::<SystemTrayManager>();
     let enabled = system_tray.toggle(&app)?;
 
@@ -33,7 +34,7 @@ pub async fn toggle_system_tray(app: tauri::AppHandle) -> Result<serde_json::Val
 
 /// 获取系统托盘状态
 #[tauri::command]
-pub async fn get_system_tray_state(app: tauri::AppHandle) -> Result<bool, String> {
+pub async fn get_system_tray_state(app: tauri::AppHandle) -> Result<bool, AgentError> {
     let system_tray = app.state::<SystemTrayManager>();
     Ok(system_tray.is_enabled_setting(&app))
 }
@@ -43,14 +44,14 @@ pub async fn get_system_tray_state(app: tauri::AppHandle) -> Result<bool, String
 pub async fn update_tray_menu_command(
     app: tauri::AppHandle,
     accounts: Vec<String>,
-) -> Result<String, String> {
+) -> Result<String, AgentError> {
     update_tray_menu(&app, accounts)?;
     Ok("托盘菜单已更新".to_string())
 }
 
 /// 最小化到托盘
 #[tauri::command]
-pub async fn minimize_to_tray(app: tauri::AppHandle) -> Result<String, String> {
+pub async fn minimize_to_tray(app: tauri::AppHandle) -> Result<String, AgentError> {
     let system_tray = app.state::<SystemTrayManager>();
     system_tray.minimize_to_tray(&app)?;
     Ok("已最小化到托盘".to_string())
@@ -58,7 +59,7 @@ pub async fn minimize_to_tray(app: tauri::AppHandle) -> Result<String, String> {
 
 /// 从托盘恢复
 #[tauri::command]
-pub async fn restore_from_tray(app: tauri::AppHandle) -> Result<String, String> {
+pub async fn restore_from_tray(app: tauri::AppHandle) -> Result<String, AgentError> {
     let system_tray = app.state::<SystemTrayManager>();
     system_tray.restore_from_tray(&app)?;
     Ok("已恢复窗口".to_string())
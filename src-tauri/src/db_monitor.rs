@@ -1,54 +1,262 @@
 //! 数据库监控模块 - 简化版本：newData, oldData, diff
+//!
+//! 通过文件通知监听 state.vscdb 及其 -wal，并用 PRAGMA data_version 确认确有新提交后，
+//! 只读取设置中配置的监控键（db_watched_keys）进行比较，不再定时读取整张 ItemTable。
+//! database-changed 事件只包含逐键差异（及 agent 状态的语义差异），并按前端订阅过滤
+//!
+//! 检测到 jetskiStateSync.agentManagerInitState 切换到新账户或刷新令牌时，
+//! 直接在后端保存当前账户并推送 account-captured 事件（窗口隐藏在托盘时同样生效）
 
+use chrono::{DateTime, Utc};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::Connection;
 use serde::Serialize;
 use serde_json::Value;
-use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
-use tokio::sync::Mutex;
-use tokio::time::{interval, Duration};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, sleep, Duration};
 use tracing::{error, info, warn};
 
-// 数据差异结构
+use crate::app_settings::AppSettingsManager;
+use crate::constants::database;
+use crate::error::AgentError;
+
+/// 文件事件去抖时间，一次提交通常会连续触发多个事件
+const NOTIFY_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 兜底检查间隔（文件通知不可用或被遗漏时）
+const FALLBACK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 单个键的变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// 单个 ItemTable 键的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyChange {
+    pub key: String,
+    pub kind: ChangeKind,
+    #[serde(rename = "oldValue")]
+    pub old_value: Option<Value>,
+    #[serde(rename = "newValue")]
+    pub new_value: Option<Value>,
+}
+
+/// jetskiStateSync.agentManagerInitState 解码后的语义差异
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AgentStateDiff {
+    #[serde(rename = "oldEmail")]
+    pub old_email: Option<String>,
+    #[serde(rename = "newEmail")]
+    pub new_email: Option<String>,
+    #[serde(rename = "emailChanged")]
+    pub email_changed: bool,
+    /// 同一账户的访问令牌发生变化
+    #[serde(rename = "tokenRefreshed")]
+    pub token_refreshed: bool,
+    #[serde(rename = "oldPlan")]
+    pub old_plan: Option<String>,
+    #[serde(rename = "newPlan")]
+    pub new_plan: Option<String>,
+    #[serde(rename = "planChanged")]
+    pub plan_changed: bool,
+    #[serde(rename = "modelsAdded")]
+    pub models_added: Vec<String>,
+    #[serde(rename = "modelsRemoved")]
+    pub models_removed: Vec<String>,
+}
+
+impl AgentStateDiff {
+    fn has_changes(&self) -> bool {
+        self.email_changed
+            || self.token_refreshed
+            || self.plan_changed
+            || !self.models_added.is_empty()
+            || !self.models_removed.is_empty()
+    }
+}
+
+/// database-changed 事件内容
 #[derive(Debug, Clone, Serialize)]
 pub struct DataDiff {
+    #[serde(rename = "hasChanges")]
     pub has_changes: bool,
-    pub changed_fields: Vec<String>,
+    pub changes: Vec<KeyChange>,
+    /// agent 状态键变化且能解码时的语义差异
+    #[serde(rename = "agentState")]
+    pub agent_state: Option<AgentStateDiff>,
     pub summary: String,
 }
 
+/// 从 agent 状态中提取用于比较的字段
+#[derive(Default)]
+struct AgentStateSummary {
+    email: Option<String>,
+    access_token: Option<String>,
+    plan: Option<String>,
+    models: Vec<String>,
+}
+
+impl AgentStateSummary {
+    fn decode(value: Option<&Value>) -> Option<Self> {
+        let Some(Value::String(state)) = value else {
+            return Some(Self::default());
+        };
+        let msg = crate::antigravity::account::decode_session(state).ok()?;
+        let non_empty = |s: &str| Some(s.to_string()).filter(|s| !s.is_empty());
+
+        let context = msg.context.unwrap_or_default();
+        let plan = context
+            .plan
+            .as_ref()
+            .and_then(|p| non_empty(&p.name))
+            .or_else(|| non_empty(&context.plan_name));
+        let models = context
+            .models
+            .map(|m| m.items.into_iter().map(|item| item.name).collect())
+            .unwrap_or_default();
+
+        Some(Self {
+            email: non_empty(&context.email),
+            access_token: msg.auth.and_then(|a| non_empty(&a.access_token)),
+            plan,
+            models,
+        })
+    }
+}
+
+/// 自动保存账户的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureReason {
+    /// 首次登录的账户
+    NewAccount,
+    /// Antigravity 切换到了另一个已保存的账户
+    AccountChanged,
+    /// 同一账户刷新了令牌
+    TokenRefreshed,
+}
+
+/// account-captured 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountCapturedEvent {
+    pub email: String,
+    pub reason: CaptureReason,
+    #[serde(rename = "accountFile")]
+    pub account_file: String,
+    /// AuthMetadata.expiry_timestamp（Unix 秒）
+    #[serde(rename = "expiryTimestamp")]
+    pub expiry_timestamp: Option<i64>,
+    #[serde(rename = "capturedAt")]
+    pub captured_at: DateTime<Utc>,
+}
+
 // 数据库监控器
 pub struct DatabaseMonitor {
     app_handle: AppHandle,
     last_data: Arc<Mutex<Option<Value>>>,
     is_running: Arc<Mutex<bool>>,
+    /// 最近一次看到的登录邮箱，用于区分切换账户与刷新令牌
+    last_email: Arc<Mutex<Option<String>>>,
+    /// 只读取并比较这些 ItemTable 键
+    watched_keys: Arc<RwLock<Vec<String>>>,
+    /// 前端订阅的键，None 表示全部监控键
+    subscribed_keys: Arc<RwLock<Option<Vec<String>>>>,
+    task: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl DatabaseMonitor {
-    /// 创建新的数据库监控器
+    /// 创建新的数据库监控器（监控键取自应用设置）
     pub fn new(app_handle: AppHandle) -> Self {
+        let keys = app_handle
+            .state::<AppSettingsManager>()
+            .get_settings()
+            .db_watched_keys;
+
         Self {
             app_handle,
             last_data: Arc::new(Mutex::new(None)),
             is_running: Arc::new(Mutex::new(false)),
+            last_email: Arc::new(Mutex::new(None)),
+            watched_keys: Arc::new(RwLock::new(Self::normalize_keys(keys))),
+            subscribed_keys: Arc::new(RwLock::new(None)),
+            task: Mutex::new(None),
+        }
+    }
+
+    /// agent 状态键用于自动保存账户，始终保留
+    fn normalize_keys(mut keys: Vec<String>) -> Vec<String> {
+        if !keys.iter().any(|k| k == database::AGENT_STATE) {
+            keys.insert(0, database::AGENT_STATE.to_string());
         }
+        keys
     }
 
-    /// 启动数据库监控
+    /// 更新监控键，下一次检查时生效；返回实际使用的键
+    pub fn set_watched_keys(&self, keys: Vec<String>) -> Vec<String> {
+        let keys = Self::normalize_keys(keys);
+        *self.watched_keys.write().unwrap_or_else(|e| e.into_inner()) = keys.clone();
+        info!("🔧 数据库监控键已更新: {:?}", keys);
+        keys
+    }
+
+    /// 设置 database-changed 事件包含的键，None 表示全部监控键
+    ///
+    /// 订阅的键需要同时在监控键中才会被读取
+    pub fn set_subscribed_keys(&self, keys: Option<Vec<String>>) {
+        info!("🔧 数据库变化订阅已更新: {:?}", keys);
+        *self
+            .subscribed_keys
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = keys;
+    }
+
+    /// 启动数据库监控（已在运行时直接返回）
     pub async fn start_monitoring(&self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("🔧 启动数据库自动监控（简化版）");
+        let mut task = self.task.lock().await;
+        if task.as_ref().is_some_and(|t| !t.is_finished()) {
+            info!("ℹ️ 数据库监控已在运行，跳过启动");
+            return Ok(());
+        }
+
+        info!("🔧 启动数据库自动监控（文件通知 + data_version）");
 
         let last_data = self.last_data.clone();
         let is_running = self.is_running.clone();
+        let last_email = self.last_email.clone();
+        let watched_keys = self.watched_keys.clone();
+        let subscribed_keys = self.subscribed_keys.clone();
         let app_handle = self.app_handle.clone();
 
         // 标记监控为运行状态
         *is_running.lock().await = true;
 
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(3)); // 3秒间隔，更敏感
+        *task = Some(tokio::spawn(async move {
+            // 文件通知只负责唤醒，是否真的有提交由 data_version 判断
+            let (notify_tx, mut notify_rx) = mpsc::unbounded_channel::<()>();
+            let _watcher = Self::watch_database_files(notify_tx);
+
+            // 监听失败或错过通知时的兜底检查
+            let mut fallback = interval(FALLBACK_CHECK_INTERVAL);
+            let mut reader = WatchedKeyReader::default();
+            let mut current_keys: Vec<String> = Vec::new();
 
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    Some(()) = notify_rx.recv() => {
+                        // 合并一次事务产生的多个文件事件
+                        sleep(NOTIFY_DEBOUNCE).await;
+                        while notify_rx.try_recv().is_ok() {}
+                    }
+                    _ = fallback.tick() => {}
+                }
 
                 // 检查监控是否还在运行
                 let running = is_running.lock().await;
@@ -58,28 +266,45 @@ impl DatabaseMonitor {
                 }
                 drop(running);
 
-                // 获取当前完整数据
-                match Self::get_complete_data().await {
-                    Ok(new_data) => {
+                // 监控键变化后需要重新读取
+                let keys = watched_keys
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone();
+                if keys != current_keys {
+                    reader.invalidate();
+                    current_keys = keys;
+                }
+
+                // 仅在数据库有新提交时读取监控键
+                match reader.read_if_changed(&current_keys) {
+                    Ok(Some(new_data)) => {
                         let mut last = last_data.lock().await;
 
+                        // jetski 状态与上次不同（含启动后首次读取）时尝试自动保存
+                        let old_state = last.as_ref().and_then(|d| d.get(database::AGENT_STATE));
+                        let new_state = new_data.get(database::AGENT_STATE);
+                        if let Some(Value::String(state)) = new_state {
+                            if last.is_none() || old_state != new_state {
+                                Self::capture_account(&app_handle, state, &last_email).await;
+                            }
+                        }
+
                         // 检查是否有数据变化
                         if let Some(ref old_data) = *last {
-                            // 分析差异
-                            let diff = Self::analyze_diff(old_data, &new_data);
+                            // 分析差异，只推送前端订阅的键
+                            let subscribed = subscribed_keys
+                                .read()
+                                .unwrap_or_else(|e| e.into_inner())
+                                .clone();
+                            let diff = Self::analyze_diff(old_data, &new_data)
+                                .filtered(subscribed.as_deref());
 
                             if diff.has_changes {
                                 info!("📢 检测到数据库变化: {}", diff.summary);
 
-                                // 构建简化的事件数据：newData, oldData, diff
-                                let event_data = serde_json::json!({
-                                    "newData": new_data,
-                                    "oldData": old_data,
-                                    "diff": diff
-                                });
-
                                 // 推送事件到前端
-                                if let Err(e) = app_handle.emit("database-changed", &event_data) {
+                                if let Err(e) = app_handle.emit("database-changed", &diff) {
                                     error!("❌ 推送数据库变化事件失败: {}", e);
                                 } else {
                                     info!("✅ 数据库变化事件推送成功");
@@ -89,12 +314,13 @@ impl DatabaseMonitor {
 
                         *last = Some(new_data);
                     }
+                    Ok(None) => {}
                     Err(e) => {
-                        warn!("⚠️ 获取完整数据失败: {}", e);
+                        warn!("⚠️ 读取监控数据失败: {}", e);
                     }
                 }
             }
-        });
+        }));
 
         Ok(())
     }
@@ -103,109 +329,338 @@ impl DatabaseMonitor {
     pub async fn stop_monitoring(&self) {
         info!("⏹️ 停止数据库自动监控");
         *self.is_running.lock().await = false;
+        if let Some(task) = self.task.lock().await.take() {
+            task.abort();
+        }
     }
 
-    /// 获取完整数据库数据
-    async fn get_complete_data() -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        // 检测数据库路径
-        let db_path = if cfg!(windows) {
-            dirs::home_dir()
-                .unwrap_or_default()
-                .join("AppData")
-                .join("Roaming")
-                .join("Antigravity")
-                .join("User")
-                .join("globalStorage")
-                .join("state.vscdb")
-        } else {
-            dirs::config_dir()
-                .unwrap_or_default()
-                .join("Antigravity")
-                .join("User")
-                .join("globalStorage")
-                .join("state.vscdb")
-        };
+    /// 监控是否正在运行
+    pub async fn is_monitoring(&self) -> bool {
+        *self.is_running.lock().await
+            && self
+                .task
+                .lock()
+                .await
+                .as_ref()
+                .is_some_and(|t| !t.is_finished())
+    }
+
+    /// 数据库路径（如活动目标）变化后调用：清除上一个数据库的缓存数据，
+    /// 监控原本在运行时重新启动，用户手动停止的监控保持停止
+    pub async fn restart_monitoring(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let was_running = self.is_monitoring().await;
+        self.stop_monitoring().await;
 
-        let mut complete_data = serde_json::Map::new();
+        // 旧数据库的数据会在首次检查时产生错误的差异并保存错误的账户
+        *self.last_data.lock().await = None;
+        *self.last_email.lock().await = None;
 
-        if db_path.exists() {
-            let conn = rusqlite::Connection::open(&db_path)?;
+        if !was_running {
+            info!("ℹ️ 数据库监控未在运行，已清除缓存数据，不重新启动");
+            return Ok(());
+        }
+        self.start_monitoring().await
+    }
 
-            // 查询所有数据（完整的ItemTable）
-            let mut stmt = conn.prepare("SELECT key, value FROM ItemTable ORDER BY key")?;
+    /// 监听 state.vscdb 所在目录，数据库或其 -wal/-journal 文件变化时发送通知
+    ///
+    /// 返回的 watcher 需要在监控期间保持存活；失败时返回 None，仅依赖兜底检查
+    fn watch_database_files(notify_tx: mpsc::UnboundedSender<()>) -> Option<RecommendedWatcher> {
+        let db_path = match crate::antigravity::restore::resolve_antigravity_db_path() {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("⚠️ 无法定位数据库，仅使用定时检查: {}", e);
+                return None;
+            }
+        };
+        let (Some(dir), Some(db_name)) = (db_path.parent(), db_path.file_name()) else {
+            return None;
+        };
+        let db_name = db_name.to_string_lossy().to_string();
+        let file_names = [
+            db_name.clone(),
+            format!("{db_name}-wal"),
+            format!("{db_name}-journal"),
+        ];
+
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            let relevant = event.paths.iter().any(|path| {
+                path.file_name()
+                    .is_some_and(|name| file_names.iter().any(|f| name == f.as_str()))
+            });
+            if relevant {
+                let _ = notify_tx.send(());
+            }
+        });
 
-            let rows: Vec<(String, String)> = stmt
-                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
-                .collect::<Result<Vec<_>, _>>()?;
+        // 监听目录而不是文件：-wal 会被创建/删除，数据库文件也可能被整体替换
+        match watcher.and_then(|mut w| w.watch(dir, RecursiveMode::NonRecursive).map(|_| w)) {
+            Ok(watcher) => {
+                info!("👀 正在监听数据库文件: {}", dir.display());
+                Some(watcher)
+            }
+            Err(e) => {
+                warn!("⚠️ 监听数据库文件失败，仅使用定时检查: {}", e);
+                None
+            }
+        }
+    }
 
-            // 构建完整数据对象
-            for (key, value) in rows {
-                // 尝试解析为JSON，如果失败则保持原始字符串
-                let json_value: Value = match serde_json::from_str(&value) {
-                    Ok(parsed) => parsed,
-                    Err(_) => Value::String(value.clone()),
-                };
+    /// 保存当前登录的账户（与 save_antigravity_current_account 相同的逻辑），并推送 account-captured 事件
+    async fn capture_account(
+        app_handle: &AppHandle,
+        agent_state: &str,
+        last_email: &Mutex<Option<String>>,
+    ) {
+        // 未登录（无邮箱或无令牌）时不保存
+        let logged_in = crate::antigravity::account::decode_session(agent_state)
+            .map(|msg| {
+                let has_email = msg.context.is_some_and(|c| !c.email.is_empty());
+                let has_token = msg.auth.is_some_and(|a| !a.access_token.is_empty());
+                has_email && has_token
+            })
+            .unwrap_or(false);
+        if !logged_in {
+            return;
+        }
 
-                complete_data.insert(key, json_value);
+        let saved = match crate::antigravity::account::save_agent_state(agent_state) {
+            Ok(saved) => saved,
+            Err(e) => {
+                warn!(target: "db_monitor::capture", error = %e, "自动保存当前账户失败");
+                return;
             }
+        };
+
+        let mut last_email = last_email.lock().await;
+        let previous_email = last_email.replace(saved.email.clone());
+        if saved.unchanged {
+            return;
         }
 
-        Ok(Value::Object(complete_data))
+        let reason = if saved.is_new {
+            CaptureReason::NewAccount
+        } else if previous_email.is_some_and(|email| email != saved.email) {
+            CaptureReason::AccountChanged
+        } else {
+            CaptureReason::TokenRefreshed
+        };
+
+        let event = AccountCapturedEvent {
+            email: saved.email,
+            reason,
+            account_file: saved.account_file.display().to_string(),
+            expiry_timestamp: saved.expiry_timestamp,
+            captured_at: Utc::now(),
+        };
+        info!(target: "db_monitor::capture", reason = ?event.reason, "📥 已自动保存当前账户");
+
+        if let Err(e) = app_handle.emit("account-captured", &event) {
+            error!("❌ 推送账户保存事件失败: {}", e);
+        }
     }
 
-    /// 分析两个数据之间的差异
+    /// 比较两次读取的监控键，生成逐键差异
     fn analyze_diff(old: &Value, new: &Value) -> DataDiff {
-        let mut changed_fields = Vec::new();
-
-        // 比较数据
-        match (old, new) {
-            (Value::Object(old_obj), Value::Object(new_obj)) => {
-                // 检查新增的字段
-                for key in new_obj.keys() {
-                    match old_obj.get(key) {
-                        Some(old_value) => {
-                            if old_value != new_obj.get(key).unwrap() {
-                                changed_fields.push(format!("{}: changed", key));
-                            }
-                        }
-                        None => {
-                            changed_fields.push(format!("{}: added", key));
-                        }
-                    }
-                }
+        let empty = serde_json::Map::new();
+        let old_obj = old.as_object().unwrap_or(&empty);
+        let new_obj = new.as_object().unwrap_or(&empty);
 
-                // 检查删除的字段
-                for key in old_obj.keys() {
-                    if !new_obj.contains_key(key) {
-                        changed_fields.push(format!("{}: removed", key));
-                    }
-                }
-            }
-            (Value::Null, Value::Object(_)) => {
-                changed_fields.push("data: added".to_string());
-            }
-            (Value::Object(_), Value::Null) => {
-                changed_fields.push("data: removed".to_string());
-            }
-            (Value::Null, Value::Null) => {
-                // 都没有数据，无变化
+        let mut changes = Vec::new();
+        for (key, new_value) in new_obj {
+            match old_obj.get(key) {
+                Some(old_value) if old_value == new_value => {}
+                Some(old_value) => changes.push(KeyChange {
+                    key: key.clone(),
+                    kind: ChangeKind::Changed,
+                    old_value: Some(old_value.clone()),
+                    new_value: Some(new_value.clone()),
+                }),
+                None => changes.push(KeyChange {
+                    key: key.clone(),
+                    kind: ChangeKind::Added,
+                    old_value: None,
+                    new_value: Some(new_value.clone()),
+                }),
             }
-            _ => {
-                changed_fields.push("data: structure_changed".to_string());
+        }
+        for (key, old_value) in old_obj {
+            if !new_obj.contains_key(key) {
+                changes.push(KeyChange {
+                    key: key.clone(),
+                    kind: ChangeKind::Removed,
+                    old_value: Some(old_value.clone()),
+                    new_value: None,
+                });
             }
         }
 
-        let has_changes = !changed_fields.is_empty();
+        let agent_state = changes
+            .iter()
+            .find(|c| c.key == database::AGENT_STATE)
+            .and_then(|c| Self::analyze_agent_state(c.old_value.as_ref(), c.new_value.as_ref()));
+
+        DataDiff::new(changes, agent_state)
+    }
+
+    /// 解码 agent 状态前后两个版本，比较邮箱、令牌、套餐和模型列表
+    fn analyze_agent_state(old: Option<&Value>, new: Option<&Value>) -> Option<AgentStateDiff> {
+        let old = AgentStateSummary::decode(old)?;
+        let new = AgentStateSummary::decode(new)?;
+
+        let email_changed = old.email != new.email;
+        let diff = AgentStateDiff {
+            email_changed,
+            token_refreshed: !email_changed
+                && new.access_token.is_some()
+                && old.access_token != new.access_token,
+            plan_changed: old.plan != new.plan,
+            models_added: new
+                .models
+                .iter()
+                .filter(|m| !old.models.contains(m))
+                .cloned()
+                .collect(),
+            models_removed: old
+                .models
+                .iter()
+                .filter(|m| !new.models.contains(m))
+                .cloned()
+                .collect(),
+            old_email: old.email,
+            new_email: new.email,
+            old_plan: old.plan,
+            new_plan: new.plan,
+        };
+        Some(diff)
+    }
+}
+
+impl DataDiff {
+    fn new(changes: Vec<KeyChange>, agent_state: Option<AgentStateDiff>) -> Self {
+        let has_changes = !changes.is_empty();
         let summary = if has_changes {
-            format!("{} fields changed", changed_fields.len())
+            let keys: Vec<&str> = changes.iter().map(|c| c.key.as_str()).collect();
+            let mut summary = format!("{} keys changed: {}", changes.len(), keys.join(", "));
+            if let Some(agent) = agent_state.as_ref().filter(|a| a.has_changes()) {
+                let mut parts = Vec::new();
+                if agent.email_changed {
+                    parts.push("email changed");
+                }
+                if agent.token_refreshed {
+                    parts.push("token refreshed");
+                }
+                if agent.plan_changed {
+                    parts.push("plan changed");
+                }
+                if !agent.models_added.is_empty() || !agent.models_removed.is_empty() {
+                    parts.push("models changed");
+                }
+                summary.push_str(&format!(" ({})", parts.join(", ")));
+            }
+            summary
         } else {
             "No changes".to_string()
         };
 
-        DataDiff {
+        Self {
             has_changes,
-            changed_fields,
+            changes,
+            agent_state,
             summary,
         }
     }
+
+    /// 只保留前端订阅的键；None 表示订阅全部监控键
+    fn filtered(self, subscribed: Option<&[String]>) -> Self {
+        let Some(keys) = subscribed else {
+            return self;
+        };
+        let changes: Vec<KeyChange> = self
+            .changes
+            .into_iter()
+            .filter(|c| keys.contains(&c.key))
+            .collect();
+        let agent_state = self
+            .agent_state
+            .filter(|_| keys.iter().any(|k| k == database::AGENT_STATE));
+        Self::new(changes, agent_state)
+    }
+}
+
+/// 持有到 state.vscdb 的连接，用 PRAGMA data_version 判断其他连接是否提交过写入
+#[derive(Default)]
+struct WatchedKeyReader {
+    conn: Option<Connection>,
+    data_version: Option<i64>,
+}
+
+impl WatchedKeyReader {
+    /// 下一次读取时无论 data_version 是否变化都重新读取
+    fn invalidate(&mut self) {
+        self.data_version = None;
+    }
+
+    /// 数据库自上次读取后有新的提交时返回监控键的当前值，否则返回 None
+    fn read_if_changed(&mut self, keys: &[String]) -> Result<Option<Value>, AgentError> {
+        let result = self.try_read(keys);
+        if result.is_err() {
+            // 数据库被替换或删除后旧连接不再可靠，下次重新打开
+            self.conn = None;
+            self.data_version = None;
+        }
+        result
+    }
+
+    fn try_read(&mut self, keys: &[String]) -> Result<Option<Value>, AgentError> {
+        let conn = match self.conn.take() {
+            Some(conn) => conn,
+            None => {
+                let db_path = crate::antigravity::restore::resolve_antigravity_db_path()?;
+                if !db_path.exists() {
+                    return Err(AgentError::database_missing(&db_path));
+                }
+                // 新连接的 data_version 与旧连接无关，视为有变化
+                self.data_version = None;
+                Connection::open(&db_path).map_err(|e| AgentError::sqlite("打开数据库失败", e))?
+            }
+        };
+        let conn = self.conn.insert(conn);
+
+        let version: i64 = conn
+            .query_row("PRAGMA data_version", [], |row| row.get(0))
+            .map_err(|e| AgentError::sqlite("读取 data_version 失败", e))?;
+        if self.data_version == Some(version) {
+            return Ok(None);
+        }
+
+        let placeholders = vec!["?"; keys.len()].join(", ");
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT key, value FROM ItemTable WHERE key IN ({placeholders})"
+            ))
+            .map_err(|e| AgentError::sqlite("查询监控键失败", e))?;
+        let rows: Vec<(String, String)> = stmt
+            .query_map(rusqlite::params_from_iter(keys), |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .and_then(|rows| rows.collect())
+            .map_err(|e| AgentError::sqlite("查询监控键失败", e))?;
+
+        let mut data = serde_json::Map::new();
+        for (key, value) in rows {
+            // 尝试解析为JSON，如果失败则保持原始字符串
+            let json_value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            data.insert(key, json_value);
+        }
+
+        self.data_version = Some(version);
+        Ok(Some(Value::Object(data)))
+    }
 }
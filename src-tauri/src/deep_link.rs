@@ -0,0 +1,375 @@
+//! URL Scheme 处理模块
+//!
+//! 支持以下链接（在书签或其他工具中触发）：
+//! - `antigravity-agent://switch?email=user@x.com`：切换到已保存的账户（需确认）
+//! - `antigravity-agent://capture`：保存 Antigravity 当前登录的账户
+//! - `antigravity-agent://sign-in`：备份当前账户后登出，以便登录新账户（需确认）
+//!
+//! Windows / Linux 上系统以命令行参数的形式启动应用，由单实例模块转发给正在运行的实例；
+//! macOS 上通过 Info.plist 注册，链接以 `RunEvent::Opened` 送达。
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use url::Url;
+
+use crate::error::AgentError;
+
+/// 注册的 URL Scheme
+pub const SCHEME: &str = "antigravity-agent";
+
+/// 链接中可执行的操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLinkAction {
+    Switch { email: String },
+    Capture,
+    SignIn,
+}
+
+/// deep-link-handled 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct DeepLinkResult {
+    pub action: String,
+    pub success: bool,
+    pub message: String,
+}
+
+impl DeepLinkAction {
+    /// 解析并校验链接
+    pub fn parse(link: &str) -> Result<Self, AgentError> {
+        let url = Url::parse(link.trim())
+            .map_err(|e| AgentError::InvalidInput(format!("无效的链接 {}: {}", link, e)))?;
+        if url.scheme() != SCHEME {
+            return Err(AgentError::InvalidInput(format!(
+                "不支持的链接协议: {}",
+                url.scheme()
+            )));
+        }
+
+        // antigravity-agent://switch 中操作名是 host，兼容 antigravity-agent:switch 写法
+        let action = url
+            .host_str()
+            .filter(|host| !host.is_empty())
+            .unwrap_or_else(|| url.path().trim_matches('/'));
+
+        match action {
+            "switch" => {
+                let email = url
+                    .query_pairs()
+                    .find(|(key, _)| key == "email")
+                    .map(|(_, value)| value.trim().to_string())
+                    .filter(|email| !email.is_empty())
+                    .ok_or_else(|| {
+                        AgentError::InvalidInput("switch 链接缺少 email 参数".to_string())
+                    })?;
+
+                if !email.contains('@') {
+                    return Err(AgentError::InvalidInput(format!("无效的邮箱: {}", email)));
+                }
+                // 邮箱用于拼接账户文件名，拒绝路径分隔符
+                crate::directories::validate_account_name(&email)?;
+                Ok(Self::Switch { email })
+            }
+            "capture" => Ok(Self::Capture),
+            "sign-in" => Ok(Self::SignIn),
+            other => Err(AgentError::InvalidInput(format!(
+                "不支持的链接操作: {}",
+                other
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Switch { .. } => "switch",
+            Self::Capture => "capture",
+            Self::SignIn => "sign-in",
+        }
+    }
+
+    /// 会关闭 Antigravity 或改动其登录状态的操作需要用户确认
+    fn confirm_message(&self) -> Option<String> {
+        match self {
+            Self::Switch { email } => Some(format!(
+                "外部链接请求切换到账户 {email}。\n\nAntigravity 将被关闭并以该账户重新启动，是否继续？"
+            )),
+            Self::Capture => None,
+            Self::SignIn => Some(
+                "外部链接请求登出当前账户以登录新账户。\n\n当前账户会先被保存，Antigravity 将被关闭并重新启动，是否继续？"
+                    .to_string(),
+            ),
+        }
+    }
+
+    async fn execute(self) -> Result<String, AgentError> {
+        match self {
+            Self::Switch { email } => {
+                let account_file = crate::directories::get_account_file(&email)?;
+                if !account_file.exists() {
+                    return Err(AgentError::AccountNotFound(email));
+                }
+
+                let result = crate::antigravity::switch::switch_saved_account(
+                    &email,
+                    crate::platform::DEFAULT_EXIT_TIMEOUT,
+                )
+                .await;
+                match result.error {
+                    Some(e) if !result.success => Err(e),
+                    _ => Ok(result.message),
+                }
+            }
+            Self::Capture => crate::commands::save_antigravity_current_account().await,
+            Self::SignIn => crate::commands::sign_in_new_antigravity_account(None).await,
+        }
+    }
+}
+
+/// 从命令行参数中找出链接
+pub fn find_in_args(args: &[String]) -> Option<&str> {
+    let prefix = format!("{SCHEME}:");
+    args.iter()
+        .map(String::as_str)
+        .find(|arg| arg.starts_with(&prefix))
+}
+
+/// 处理一个链接：校验、按需确认，然后在后台执行并推送 deep-link-handled 事件
+pub fn handle_url(app_handle: &AppHandle, link: &str) {
+    tracing::info!(target: "app::deep_link", link = %link, "收到链接");
+
+    let action = match DeepLinkAction::parse(link) {
+        Ok(action) => action,
+        Err(e) => {
+            tracing::warn!(target: "app::deep_link", error = %e, "链接无效，已忽略");
+            emit_result(
+                app_handle,
+                DeepLinkResult {
+                    action: "invalid".to_string(),
+                    success: false,
+                    message: e.to_string(),
+                },
+            );
+            return;
+        }
+    };
+
+    let app = app_handle.clone();
+    match action.confirm_message() {
+        Some(message) => {
+            app_handle
+                .dialog()
+                .message(message)
+                .title("Antigravity Agent")
+                .kind(MessageDialogKind::Warning)
+                .buttons(MessageDialogButtons::OkCancelCustom(
+                    "继续".to_string(),
+                    "取消".to_string(),
+                ))
+                .show(move |confirmed| {
+                    if confirmed {
+                        spawn_action(app, action);
+                    } else {
+                        tracing::info!(target: "app::deep_link", action = action.name(), "用户取消了链接操作");
+                    }
+                });
+        }
+        None => spawn_action(app, action),
+    }
+}
+
+fn spawn_action(app_handle: AppHandle, action: DeepLinkAction) {
+    tauri::async_runtime::spawn(async move {
+        let name = action.name().to_string();
+        let result = match action.execute().await {
+            Ok(message) => {
+                tracing::info!(target: "app::deep_link", action = %name, "✅ 链接操作完成");
+                DeepLinkResult {
+                    action: name,
+                    success: true,
+                    message,
+                }
+            }
+            Err(e) => {
+                tracing::error!(target: "app::deep_link", action = %name, error = %e, "链接操作失败");
+                DeepLinkResult {
+                    action: name,
+                    success: false,
+                    message: e.to_string(),
+                }
+            }
+        };
+        emit_result(&app_handle, result);
+    });
+}
+
+fn emit_result(app_handle: &AppHandle, result: DeepLinkResult) {
+    if let Err(e) = app_handle.emit("deep-link-handled", &result) {
+        tracing::error!(target: "app::deep_link", error = %e, "推送链接处理结果失败");
+    }
+}
+
+/// 在当前用户范围内把 URL Scheme 关联到本程序（macOS 由 Info.plist 注册，无需处理）
+pub fn register_url_scheme() -> Result<(), AgentError> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+        let exe = std::env::current_exe().map_err(|e| AgentError::io("获取程序路径失败", e))?;
+        let key = format!(r"HKCU\Software\Classes\{SCHEME}");
+        let command_key = format!(r"{key}\shell\open\command");
+        let command = format!("\"{}\" \"%1\"", exe.display());
+        let entries: [(&str, &[&str]); 3] = [
+            (key.as_str(), &["/ve", "/d", "URL:Antigravity Agent"]),
+            (key.as_str(), &["/v", "URL Protocol", "/d", ""]),
+            (command_key.as_str(), &["/ve", "/d", command.as_str()]),
+        ];
+
+        for (path, args) in entries {
+            let status = std::process::Command::new("reg")
+                .arg("add")
+                .arg(path)
+                .args(args)
+                .arg("/f")
+                .creation_flags(CREATE_NO_WINDOW)
+                .status()
+                .map_err(|e| AgentError::io("执行 reg 失败", e))?;
+            if !status.success() {
+                return Err(AgentError::Internal(format!("写入注册表 {} 失败", path)));
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // AppImage 运行时 current_exe 指向临时挂载目录，需使用 APPIMAGE 的实际路径
+        let exe = match std::env::var_os("APPIMAGE") {
+            Some(path) => std::path::PathBuf::from(path),
+            None => std::env::current_exe().map_err(|e| AgentError::io("获取程序路径失败", e))?,
+        };
+        let applications_dir = dirs::data_dir()
+            .ok_or_else(|| AgentError::Internal("无法确定用户数据目录".to_string()))?
+            .join("applications");
+        std::fs::create_dir_all(&applications_dir)
+            .map_err(|e| AgentError::io("创建 applications 目录失败", e))?;
+
+        let desktop_name = format!("{SCHEME}-handler.desktop");
+        let desktop_entry = format!(
+            "[Desktop Entry]\nType=Application\nName=Antigravity Agent\nExec=\"{}\" %u\nTerminal=false\nNoDisplay=true\nMimeType=x-scheme-handler/{SCHEME};\n",
+            exe.display()
+        );
+        std::fs::write(applications_dir.join(&desktop_name), desktop_entry)
+            .map_err(|e| AgentError::io("写入 desktop 文件失败", e))?;
+
+        let status = std::process::Command::new("xdg-mime")
+            .args([
+                "default",
+                &desktop_name,
+                &format!("x-scheme-handler/{SCHEME}"),
+            ])
+            .status()
+            .map_err(|e| AgentError::io("执行 xdg-mime 失败", e))?;
+        if !status.success() {
+            return Err(AgentError::Internal(
+                "xdg-mime 注册 URL Scheme 失败".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_invalid_input(link: &str) -> bool {
+        matches!(
+            DeepLinkAction::parse(link),
+            Err(AgentError::InvalidInput(_))
+        )
+    }
+
+    #[test]
+    fn parse_supported_actions() {
+        assert_eq!(
+            DeepLinkAction::parse("antigravity-agent://switch?email=user@example.com").unwrap(),
+            DeepLinkAction::Switch {
+                email: "user@example.com".to_string()
+            }
+        );
+        assert_eq!(
+            DeepLinkAction::parse("antigravity-agent://capture").unwrap(),
+            DeepLinkAction::Capture
+        );
+        assert_eq!(
+            DeepLinkAction::parse("antigravity-agent://sign-in/").unwrap(),
+            DeepLinkAction::SignIn
+        );
+        // 不带 // 的写法
+        assert_eq!(
+            DeepLinkAction::parse("antigravity-agent:capture").unwrap(),
+            DeepLinkAction::Capture
+        );
+    }
+
+    #[test]
+    fn parse_decodes_email_parameter() {
+        assert_eq!(
+            DeepLinkAction::parse("antigravity-agent://switch?email=%20user%2Btag%40example.com")
+                .unwrap(),
+            DeepLinkAction::Switch {
+                email: "user+tag@example.com".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unknown_action_and_scheme() {
+        assert!(is_invalid_input(
+            "antigravity-agent://delete?email=user@example.com"
+        ));
+        assert!(is_invalid_input("https://switch?email=user@example.com"));
+        assert!(is_invalid_input("not a link"));
+    }
+
+    #[test]
+    fn parse_rejects_missing_or_invalid_email() {
+        assert!(is_invalid_input("antigravity-agent://switch"));
+        assert!(is_invalid_input("antigravity-agent://switch?email="));
+        assert!(is_invalid_input("antigravity-agent://switch?email=%20%20"));
+        assert!(is_invalid_input(
+            "antigravity-agent://switch?email=user.example.com"
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_path_traversal_emails() {
+        assert!(is_invalid_input(
+            "antigravity-agent://switch?email=../x@y.com"
+        ));
+        assert!(is_invalid_input(
+            "antigravity-agent://switch?email=..%2Fx%40y.com"
+        ));
+        assert!(is_invalid_input(
+            "antigravity-agent://switch?email=a/b@c.com"
+        ));
+        assert!(is_invalid_input(
+            "antigravity-agent://switch?email=a%5Cb@c.com"
+        ));
+        assert!(is_invalid_input(
+            "antigravity-agent://switch?email=C:x@y.com"
+        ));
+    }
+
+    #[test]
+    fn find_in_args_returns_first_link() {
+        let args = vec![
+            "antigravity-agent".to_string(),
+            "--minimized".to_string(),
+            "antigravity-agent://capture".to_string(),
+        ];
+        assert_eq!(find_in_args(&args), Some("antigravity-agent://capture"));
+        assert_eq!(find_in_args(&args[..2]), None);
+    }
+}
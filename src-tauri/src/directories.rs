@@ -1,11 +1,13 @@
+use serde_json::{self, Value};
 /// 目录获取模块
 /// 统一管理所有配置和数据目录路径
 use std::fs;
 use std::io;
 use std::path::PathBuf;
-use serde_json::{self, Value};
 use tracing::{info, warn};
 
+use crate::error::AgentError;
+
 /// 获取应用主配置目录
 /// 所有配置、日志、数据都统一存放在用户主目录的 .antigravity-agent 下
 #[cfg(windows)]
@@ -61,6 +63,33 @@ pub fn get_accounts_directory() -> PathBuf {
     accounts_dir
 }
 
+/// 校验账户名（邮箱）可以安全地拼接为文件或目录名
+///
+/// 账户名来自前端、IPC、深度链接或账户快照，拒绝路径分隔符、盘符与 `..`，防止访问账户目录之外的路径
+pub fn validate_account_name(name: &str) -> Result<(), AgentError> {
+    if name.trim().is_empty() || name.contains(['/', '\\', ':', '\0']) || name.contains("..") {
+        return Err(AgentError::InvalidInput(format!("无效的账户名: {}", name)));
+    }
+    Ok(())
+}
+
+/// 获取已保存账户的文件路径（antigravity-accounts/{name}.json）
+pub fn get_account_file(name: &str) -> Result<PathBuf, AgentError> {
+    validate_account_name(name)?;
+    Ok(get_accounts_directory().join(format!("{name}.json")))
+}
+
+/// 获取账户索引文件路径
+/// 放在账户目录的 meta 子目录中，避免被当作账户文件枚举
+pub fn get_account_index_file() -> PathBuf {
+    get_accounts_directory().join("meta").join("index.json")
+}
+
+/// 获取账户历史快照目录
+pub fn get_accounts_history_directory() -> PathBuf {
+    get_accounts_directory().join("history")
+}
+
 /// 获取应用设置文件路径
 pub fn get_app_settings_file() -> PathBuf {
     get_config_directory().join("app_settings.json")
@@ -76,6 +105,27 @@ pub fn get_antigravity_path_file() -> PathBuf {
     get_config_directory().join("antigravity_path.json")
 }
 
+/// 获取 Antigravity 目标配置文件路径
+pub fn get_targets_file() -> PathBuf {
+    get_config_directory().join("targets.json")
+}
+
+/// 获取账户配额缓存文件路径
+pub fn get_quota_cache_file() -> PathBuf {
+    get_config_directory().join("quota_cache.json")
+}
+
+/// 获取本地控制 API 令牌文件路径
+pub fn get_ipc_token_file() -> PathBuf {
+    get_config_directory().join("ipc_token")
+}
+
+/// 获取本地控制 API 的 Unix socket 路径（Windows 使用命名管道）
+#[cfg(not(windows))]
+pub fn get_ipc_socket_path() -> PathBuf {
+    get_config_directory().join("agent.sock")
+}
+
 /// 在应用启动时检查并迁移旧账户目录到新路径。
 /// 当前为空实现，后续补充实际迁移逻辑。
 pub fn migrate_legacy_accounts_if_needed() -> io::Result<()> {
@@ -249,3 +299,27 @@ pub fn migrate_legacy_accounts_if_needed() -> io::Result<()> {
     // TODO: 检测 Roaming 下的旧账户目录并迁移到新目录，处理冲突和错误。
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_account_name_rejects_paths() {
+        assert!(validate_account_name("user@example.com").is_ok());
+        assert!(validate_account_name("first.last+tag@example.com").is_ok());
+
+        for name in [
+            "",
+            " ",
+            "../user@example.com",
+            "..",
+            "a/b@example.com",
+            "a\\b@example.com",
+            "C:user@example.com",
+            "user\0@example.com",
+        ] {
+            assert!(validate_account_name(name).is_err(), "{name:?}");
+        }
+    }
+}
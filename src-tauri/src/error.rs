@@ -0,0 +1,184 @@
+//! 统一错误类型
+//! 所有 Tauri 命令返回 AgentError，序列化为稳定的 `{code, message, details}` 结构供前端判断
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::path::Path;
+
+/// 应用统一错误
+#[derive(Debug, Clone)]
+pub enum AgentError {
+    /// 未找到 Antigravity 安装
+    NotInstalled,
+    /// Antigravity 状态数据库不存在
+    DatabaseMissing(String),
+    /// 数据库被其他进程锁定
+    DatabaseLocked(String),
+    /// 其他数据库错误
+    Database(String),
+    /// jetskiStateSync Base64 / Protobuf 解码失败
+    ProtoDecode(String),
+    /// 账户文件不存在或内容无效
+    AccountNotFound(String),
+    /// 没有正在运行的 Antigravity 进程
+    ProcessNotRunning,
+    /// 关闭 Antigravity 进程失败
+    ProcessKillFailed(String),
+    /// 启动 Antigravity 失败
+    LaunchFailed(String),
+    /// 加密 / 解密失败
+    Crypto(String),
+    /// 网络请求失败
+    Network(String),
+    /// OAuth 服务拒绝了请求（如 refresh token 已失效）
+    OAuth(String),
+    /// 文件读写失败
+    Io(String),
+    /// 参数无效
+    InvalidInput(String),
+    /// 其他内部错误
+    Internal(String),
+}
+
+impl AgentError {
+    /// 稳定的错误码，前端据此判断错误类型
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotInstalled => "NOT_INSTALLED",
+            Self::DatabaseMissing(_) => "DATABASE_MISSING",
+            Self::DatabaseLocked(_) => "DATABASE_LOCKED",
+            Self::Database(_) => "DATABASE",
+            Self::ProtoDecode(_) => "PROTO_DECODE",
+            Self::AccountNotFound(_) => "ACCOUNT_NOT_FOUND",
+            Self::ProcessNotRunning => "PROCESS_NOT_RUNNING",
+            Self::ProcessKillFailed(_) => "PROCESS_KILL_FAILED",
+            Self::LaunchFailed(_) => "LAUNCH_FAILED",
+            Self::Crypto(_) => "CRYPTO",
+            Self::Network(_) => "NETWORK",
+            Self::OAuth(_) => "OAUTH",
+            Self::Io(_) => "IO",
+            Self::InvalidInput(_) => "INVALID_INPUT",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// 面向用户的错误描述
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::NotInstalled => "未找到 Antigravity 安装位置",
+            Self::DatabaseMissing(_) => "Antigravity 状态数据库不存在",
+            Self::DatabaseLocked(_) => "Antigravity 状态数据库被占用",
+            Self::Database(_) => "数据库操作失败",
+            Self::ProtoDecode(_) => "账户数据解码失败",
+            Self::AccountNotFound(_) => "账户不存在",
+            Self::ProcessNotRunning => "Antigravity 进程未运行",
+            Self::ProcessKillFailed(_) => "关闭 Antigravity 进程失败",
+            Self::LaunchFailed(_) => "启动 Antigravity 失败",
+            Self::Crypto(_) => "加密或解密失败",
+            Self::Network(_) => "网络请求失败",
+            Self::OAuth(_) => "OAuth 认证失败",
+            Self::Io(_) => "文件读写失败",
+            Self::InvalidInput(_) => "参数无效",
+            Self::Internal(_) => "内部错误",
+        }
+    }
+
+    /// 错误细节（路径、底层错误信息等）
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            Self::NotInstalled | Self::ProcessNotRunning => None,
+            Self::DatabaseMissing(d)
+            | Self::DatabaseLocked(d)
+            | Self::Database(d)
+            | Self::ProtoDecode(d)
+            | Self::AccountNotFound(d)
+            | Self::ProcessKillFailed(d)
+            | Self::LaunchFailed(d)
+            | Self::Crypto(d)
+            | Self::Network(d)
+            | Self::OAuth(d)
+            | Self::Io(d)
+            | Self::InvalidInput(d)
+            | Self::Internal(d) => Some(d.as_str()),
+        }
+    }
+
+    /// 数据库文件不存在
+    pub fn database_missing(path: &Path) -> Self {
+        Self::DatabaseMissing(path.display().to_string())
+    }
+
+    /// 带上下文的 SQLite 错误，SQLITE_BUSY / SQLITE_LOCKED 归类为 DatabaseLocked
+    pub fn sqlite(context: impl fmt::Display, e: rusqlite::Error) -> Self {
+        let details = format!("{}: {}", context, e);
+        match e.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseBusy) | Some(rusqlite::ErrorCode::DatabaseLocked) => {
+                Self::DatabaseLocked(details)
+            }
+            _ => Self::Database(details),
+        }
+    }
+
+    /// 带上下文的文件读写错误
+    pub fn io(context: impl fmt::Display, e: std::io::Error) -> Self {
+        Self::Io(format!("{}: {}", context, e))
+    }
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.details() {
+            Some(details) => write!(f, "{}: {}", self.message(), details),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+impl Serialize for AgentError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AgentError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for AgentError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseBusy) | Some(rusqlite::ErrorCode::DatabaseLocked) => {
+                Self::DatabaseLocked(e.to_string())
+            }
+            _ => Self::Database(e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for AgentError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AgentError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Internal(format!("JSON 处理失败: {}", e))
+    }
+}
+
+/// 尚未细分的错误（托盘、设置等模块仍返回 String）
+impl From<String> for AgentError {
+    fn from(e: String) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<&str> for AgentError {
+    fn from(e: &str) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
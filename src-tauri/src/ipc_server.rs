@@ -0,0 +1,394 @@
+//! 本地控制 API
+//!
+//! 可选启用（设置项 ipc_server_enabled）的本地 IPC 服务：Linux/macOS 使用
+//! ~/.antigravity-agent/agent.sock，Windows 使用命名管道 \\.\pipe\antigravity-agent。
+//!
+//! 协议为按行分隔的 JSON-RPC 2.0，每个请求需携带顶层 `token` 字段，
+//! 其值为 ~/.antigravity-agent/ipc_token 的内容（首次启用时生成）。例如：
+//!
+//! ```text
+//! {"jsonrpc":"2.0","id":1,"token":"...","method":"switch_to_antigravity_account","params":{"accountName":"user@x.com"}}
+//! ```
+//!
+//! 方法与 `commands::account_commands` 中的命令同名，参数名与前端 invoke 一致（camelCase）。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::error::AgentError;
+
+/// 令牌长度（字节）
+const TOKEN_LEN: usize = 32;
+
+/// JSON-RPC 错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// 业务错误，`data` 为 AgentError 的 {code, message, details}
+const AGENT_ERROR: i64 = -32000;
+const UNAUTHORIZED: i64 = -32001;
+
+/// IPC 服务的连接信息
+#[derive(Debug, Clone, Serialize)]
+pub struct IpcServerInfo {
+    pub running: bool,
+    /// Unix socket 路径或命名管道名称
+    pub endpoint: String,
+    /// 令牌文件路径
+    #[serde(rename = "tokenFile")]
+    pub token_file: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl RpcResponse {
+    fn ok(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    fn err(id: Value, code: i64, message: impl Into<String>, data: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(RpcError {
+                code,
+                message: message.into(),
+                data,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountParams {
+    #[serde(rename = "accountName")]
+    account_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestoreParams {
+    #[serde(rename = "accountName")]
+    account_name: String,
+    #[serde(rename = "versionId", default)]
+    version_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwitchParams {
+    #[serde(rename = "accountName")]
+    account_name: String,
+    #[serde(rename = "exitTimeoutMs", default)]
+    exit_timeout_ms: Option<u64>,
+}
+
+/// 本地 IPC 服务
+pub struct IpcServer {
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl IpcServer {
+    pub fn new() -> Self {
+        Self {
+            task: Mutex::new(None),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .is_some_and(|t| !t.inner().is_finished())
+    }
+
+    /// 当前连接信息
+    pub fn info(&self) -> IpcServerInfo {
+        IpcServerInfo {
+            running: self.is_running(),
+            endpoint: endpoint(),
+            token_file: crate::directories::get_ipc_token_file(),
+        }
+    }
+
+    /// 启动服务（已在运行时直接返回）
+    pub fn start(&self) -> Result<IpcServerInfo, AgentError> {
+        let mut task = self.task.lock().unwrap_or_else(|e| e.into_inner());
+        if task.as_ref().is_some_and(|t| !t.inner().is_finished()) {
+            drop(task);
+            return Ok(self.info());
+        }
+
+        let token = load_or_create_token()?;
+        let endpoint = endpoint();
+        tracing::info!(target: "ipc::server", endpoint = %endpoint, "🔌 启动本地控制 API");
+
+        *task = Some(tauri::async_runtime::spawn(async move {
+            if let Err(e) = serve(endpoint, token).await {
+                tracing::error!(target: "ipc::server", error = %e, "本地控制 API 已退出");
+            }
+        }));
+        drop(task);
+
+        Ok(self.info())
+    }
+
+    /// 停止服务
+    pub fn stop(&self) -> IpcServerInfo {
+        if let Some(task) = self.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
+            tracing::info!(target: "ipc::server", "⏹️ 本地控制 API 已停止");
+        }
+
+        #[cfg(unix)]
+        {
+            let _ = fs::remove_file(endpoint());
+        }
+
+        self.info()
+    }
+}
+
+impl Default for IpcServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn endpoint() -> String {
+    #[cfg(windows)]
+    {
+        r"\\.\pipe\antigravity-agent".to_string()
+    }
+
+    #[cfg(not(windows))]
+    {
+        crate::directories::get_ipc_socket_path()
+            .display()
+            .to_string()
+    }
+}
+
+/// 读取令牌，不存在时生成（每次安装一个，跨重启保持不变）
+fn load_or_create_token() -> Result<String, AgentError> {
+    let path = crate::directories::get_ipc_token_file();
+    if let Ok(token) = fs::read_to_string(&path) {
+        let token = token.trim().to_string();
+        if !token.is_empty() {
+            return Ok(token);
+        }
+    }
+
+    let token = crate::utils::token::random_hex_token(TOKEN_LEN);
+    fs::write(&path, &token).map_err(|e| AgentError::io("写入 IPC 令牌失败", e))?;
+
+    // 令牌只允许当前用户读取
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| AgentError::io("设置 IPC 令牌权限失败", e))?;
+    }
+
+    tracing::info!(target: "ipc::server", file = %path.display(), "🔑 已生成 IPC 令牌");
+    Ok(token)
+}
+
+#[cfg(unix)]
+async fn serve(endpoint: String, token: String) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // 单实例运行，残留的 socket 文件来自上次异常退出
+    let _ = fs::remove_file(&endpoint);
+    let listener = tokio::net::UnixListener::bind(&endpoint)?;
+    fs::set_permissions(&endpoint, fs::Permissions::from_mode(0o600))?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let token = token.clone();
+        tauri::async_runtime::spawn(handle_connection(stream, token));
+    }
+}
+
+#[cfg(windows)]
+async fn serve(endpoint: String, token: String) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&endpoint)?;
+
+    loop {
+        server.connect().await?;
+        // 先创建下一个管道实例，再处理当前连接
+        let connected = std::mem::replace(&mut server, ServerOptions::new().create(&endpoint)?);
+        let token = token.clone();
+        tauri::async_runtime::spawn(handle_connection(connected, token));
+    }
+}
+
+/// 逐行读取请求并逐行写回响应
+async fn handle_connection<S>(stream: S, token: String)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!(target: "ipc::server", error = %e, "读取请求失败");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = handle_line(&line, &token).await;
+        let mut payload = serde_json::to_string(&response).unwrap_or_default();
+        payload.push('\n');
+        if let Err(e) = writer.write_all(payload.as_bytes()).await {
+            tracing::warn!(target: "ipc::server", error = %e, "写回响应失败");
+            break;
+        }
+    }
+}
+
+async fn handle_line(line: &str, token: &str) -> RpcResponse {
+    let request: RpcRequest = match serde_json::from_str::<Value>(line) {
+        Err(e) => return RpcResponse::err(Value::Null, PARSE_ERROR, e.to_string(), None),
+        Ok(value) => match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(e) => return RpcResponse::err(Value::Null, INVALID_REQUEST, e.to_string(), None),
+        },
+    };
+
+    if request.token.as_deref() != Some(token) {
+        tracing::warn!(target: "ipc::server", method = %request.method, "拒绝未授权的请求");
+        return RpcResponse::err(request.id, UNAUTHORIZED, "令牌无效", None);
+    }
+
+    let method = request.method.as_str();
+    let result = crate::log_async_command!(method, dispatch(method, request.params));
+    match result {
+        Ok(value) => RpcResponse::ok(request.id, value),
+        Err(RpcFailure::MethodNotFound) => RpcResponse::err(
+            request.id,
+            METHOD_NOT_FOUND,
+            format!("未知方法: {}", method),
+            None,
+        ),
+        Err(RpcFailure::InvalidParams(message)) => {
+            RpcResponse::err(request.id, INVALID_PARAMS, message, None)
+        }
+        Err(RpcFailure::Agent(e)) => RpcResponse::err(
+            request.id,
+            AGENT_ERROR,
+            e.to_string(),
+            serde_json::to_value(&e).ok(),
+        ),
+    }
+}
+
+/// 方法调用失败的原因
+#[derive(Debug)]
+enum RpcFailure {
+    MethodNotFound,
+    InvalidParams(String),
+    Agent(AgentError),
+}
+
+impl std::fmt::Display for RpcFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MethodNotFound => write!(f, "未知方法"),
+            Self::InvalidParams(message) => write!(f, "参数无效: {}", message),
+            Self::Agent(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<AgentError> for RpcFailure {
+    fn from(e: AgentError) -> Self {
+        Self::Agent(e)
+    }
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcFailure> {
+    serde_json::from_value(params).map_err(|e| RpcFailure::InvalidParams(e.to_string()))
+}
+
+fn to_result(value: impl Serialize) -> Result<Value, RpcFailure> {
+    serde_json::to_value(value).map_err(|e| RpcFailure::Agent(e.into()))
+}
+
+async fn dispatch(method: &str, params: Value) -> Result<Value, RpcFailure> {
+    use crate::commands;
+
+    match method {
+        "get_current_antigravity_account_info" => {
+            to_result(commands::get_current_antigravity_account_info().await?)
+        }
+        "get_antigravity_accounts" => to_result(commands::get_antigravity_accounts().await?),
+        "save_antigravity_current_account" => {
+            to_result(commands::save_antigravity_current_account().await?)
+        }
+        "list_antigravity_account_versions" => {
+            let p: AccountParams = parse_params(params)?;
+            to_result(commands::list_antigravity_account_versions(p.account_name).await?)
+        }
+        "restore_antigravity_account" => {
+            let p: RestoreParams = parse_params(params)?;
+            to_result(commands::restore_antigravity_account(p.account_name, p.version_id).await?)
+        }
+        "switch_to_antigravity_account" => {
+            let p: SwitchParams = parse_params(params)?;
+            to_result(
+                commands::switch_to_antigravity_account(p.account_name, p.exit_timeout_ms).await?,
+            )
+        }
+        "clear_all_antigravity_data" => to_result(commands::clear_all_antigravity_data().await?),
+        _ => Err(RpcFailure::MethodNotFound),
+    }
+}
@@ -0,0 +1,208 @@
+use std::fs;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{prelude::*, EnvFilter};
+
+// Modules
+// 账户、平台与目录相关模块同时供 antigravity-agent-cli 使用
+pub mod antigravity;
+mod app_settings;
+pub mod best_account;
+mod config_manager;
+mod constants;
+pub mod directories;
+pub mod error;
+pub mod oauth;
+pub mod platform;
+mod proto;
+pub mod quota;
+mod system_tray;
+mod utils;
+mod window;
+
+mod commands;
+mod db_monitor;
+mod deep_link;
+mod ipc_server;
+mod path_utils;
+mod rotation;
+mod setup;
+mod single_instance;
+mod state;
+mod token_expiry;
+
+// Re-export AppState for compatibility with other modules
+pub use state::{AntigravityAccount, AppState, ProfileInfo};
+
+// Use commands
+use crate::commands::*;
+
+/// 初始化双层日志系统（控制台 + 文件）
+fn init_tracing() -> WorkerGuard {
+    // 日志过滤器：默认 info，降低 h2/hyper 噪音（可被 RUST_LOG 覆盖）
+    let env_filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("info,h2=warn,hyper=warn"));
+
+    // 创建日志目录
+    let log_dir = crate::directories::get_log_directory();
+    if let Err(e) = fs::create_dir_all(&log_dir) {
+        eprintln!("警告：无法创建日志目录 {}: {}", log_dir.display(), e);
+    }
+
+    // 创建滚动文件写入器（带脱敏）
+    let file_writer =
+        crate::utils::sanitizing_layer::SanitizingFileWriter::new().expect("无法创建文件写入器");
+    let (non_blocking, guard) = tracing_appender::non_blocking(file_writer);
+
+    // 设置控制台和文件双层输出
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stdout) // 控制台输出，不脱敏
+                .with_target(false)
+                .compact()
+                .with_ansi(true), // 控制台启用颜色
+        )
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(non_blocking) // 文件输出，自动脱敏
+                .with_target(true)
+                .with_ansi(false) // 文件不使用颜色代码
+                .compact(), // 使用紧凑格式而非 JSON，便于脱敏处理
+        )
+        .init();
+
+    guard // 返回 guard 以防止日志缓冲区被过早清理
+}
+
+/// 启动桌面应用
+pub fn run() {
+    // 初始化双层日志系统（控制台 + 文件）
+    let _guard = init_tracing();
+
+    tracing::info!(target: "app::startup", "🚀 启动 Antigravity Agent");
+    tracing::info!(target: "app::startup", "📝 日志系统已初始化（控制台 + 文件）");
+    tracing::info!(target: "app::startup", "📁 日志目录: {}", crate::directories::get_log_directory().display());
+
+    // 记录系统启动信息
+    crate::utils::tracing_config::log_system_info();
+
+    // 已有实例在运行时转发启动参数后退出，避免两个实例同时监控同一数据库
+    let instance = match single_instance::acquire(std::env::args().skip(1).collect()) {
+        Ok(single_instance::Acquire::Primary(guard)) => Some(guard),
+        Ok(single_instance::Acquire::Forwarded) => {
+            tracing::info!(target: "app::startup", "👋 已有实例在运行，退出当前进程");
+            return;
+        }
+        Err(e) => {
+            tracing::error!(target: "app::startup", "⚠️ 单实例检查失败，继续启动: {}", e);
+            None
+        }
+    };
+
+    // 阻塞主线程执行一次账户目录迁移检查
+    match crate::directories::migrate_legacy_accounts_if_needed() {
+        Ok(()) => tracing::info!(target: "app::startup", "📦 账户目录迁移检查完成"),
+        Err(e) => tracing::error!(target: "app::startup", "⚠️ 账户目录迁移检查失败: {}", e),
+    }
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_os::init())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_http::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(AppState::default())
+        .setup(move |app| {
+            if let Some(guard) = instance {
+                single_instance::listen(app.handle().clone(), guard);
+            }
+            setup::init(app)
+        })
+        .invoke_handler(tauri::generate_handler![
+            collect_account_contents,
+            restore_backup_files,
+            delete_backup,
+            clear_all_backups,
+            // 账户基础命令
+            get_antigravity_accounts,
+            get_current_antigravity_account_info,
+            save_antigravity_current_account,
+            restore_antigravity_account,
+            list_antigravity_account_versions,
+            switch_to_antigravity_account,
+            clear_all_antigravity_data,
+            // 账户元数据命令
+            set_account_label,
+            set_account_notes,
+            set_account_tags,
+            set_account_pinned,
+            is_antigravity_running,
+            sign_in_new_antigravity_account,
+            // 平台支持命令
+            get_platform_info,
+            find_antigravity_installations,
+            get_current_paths,
+            // 数据库路径相关
+            detect_antigravity_installation,
+            // 可执行文件路径相关
+            validate_antigravity_executable,
+            detect_antigravity_executable,
+            validate_antigravity_data_dir,
+            save_antigravity_data_dir,
+            clear_antigravity_data_dir,
+            list_antigravity_targets,
+            save_antigravity_target,
+            save_default_launch_profile,
+            remove_antigravity_target,
+            set_active_antigravity_target,
+            save_antigravity_executable,
+            enable_system_tray,
+            disable_system_tray,
+            minimize_to_tray,
+            restore_from_tray,
+            get_system_tray_state,
+            toggle_system_tray,
+            update_tray_menu_command,
+            is_silent_start_enabled,
+            save_silent_start_state,
+            get_all_settings,
+            save_db_watched_keys,
+            save_ipc_server_enabled,
+            get_rotation_plan,
+            save_rotation_plan,
+            rotate_account_now,
+            get_ipc_server_info,
+            save_token_expiry_warning_minutes,
+            // OAuth 命令
+            refresh_antigravity_account_token,
+            login_with_google_oauth,
+            cancel_google_oauth_login,
+            // 配额命令
+            get_accounts_quota,
+            switch_to_best_account,
+            // 数据库监控命令
+            is_database_monitoring_running,
+            start_database_monitoring,
+            stop_database_monitoring,
+            set_database_subscription,
+            decrypt_config_data,
+            encrypt_config_data,
+            write_text_file,
+            write_frontend_log,
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app_handle, _event| {
+            // macOS 通过 Apple Event 传递 URL Scheme 链接
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                for url in urls {
+                    deep_link::handle_url(_app_handle, url.as_str());
+                }
+            }
+        });
+}
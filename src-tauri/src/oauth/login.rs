@@ -0,0 +1,403 @@
+//! 基于 PKCE 的浏览器登录
+//!
+//! 在 127.0.0.1 的随机端口上监听回调，浏览器完成授权后用授权码和 code_verifier 换取令牌
+//! （公有客户端，无需 client_secret），再查询用户邮箱并生成可直接恢复的账户快照：
+//! 已保存过的账户只替换其中的令牌，新账户生成仅包含令牌与邮箱的最小快照，
+//! Antigravity 启动后会补全其余字段。
+
+use base64::Engine;
+use prost::Message;
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use super::OAuthConfig;
+use crate::antigravity::account::{self, AuthTokens, SavedAccount};
+use crate::antigravity::restore;
+use crate::error::AgentError;
+
+/// 等待浏览器回调的超时时间
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// 读取单个回调请求的超时时间
+const CALLBACK_READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// 回调请求头的最大长度
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+const CALLBACK_PATH: &str = "/callback";
+
+/// 正在进行的登录，用于取消
+static ACTIVE_LOGIN: Mutex<Option<oneshot::Sender<()>>> = Mutex::new(None);
+
+/// PKCE code verifier 与 challenge（S256）
+struct Pkce {
+    verifier: String,
+    challenge: String,
+}
+
+impl Pkce {
+    fn generate() -> Self {
+        let mut bytes = [0u8; 64];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        let encoder = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        let verifier = encoder.encode(bytes);
+        let challenge = encoder.encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+}
+
+/// 已开始监听回调、等待用户在浏览器中授权的登录
+pub struct PendingLogin {
+    config: OAuthConfig,
+    listener: TcpListener,
+    redirect_uri: String,
+    state: String,
+    pkce: Pkce,
+    authorization_url: String,
+}
+
+/// 用户信息端点的响应
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    email: String,
+}
+
+/// 浏览器回调的处理结果
+enum Callback {
+    Code(String),
+    /// 用户拒绝授权或授权服务器返回错误
+    Denied(String),
+    /// 与本次登录无关的请求（如 favicon、state 不匹配）
+    Ignored,
+}
+
+impl PendingLogin {
+    /// 监听回调端口并生成授权链接
+    pub async fn start(config: OAuthConfig) -> Result<Self, AgentError> {
+        let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+            .await
+            .map_err(|e| AgentError::io("监听登录回调端口失败", e))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| AgentError::io("获取登录回调端口失败", e))?
+            .port();
+
+        let redirect_uri = format!("http://127.0.0.1:{port}{CALLBACK_PATH}");
+        let state = crate::utils::token::random_hex_token(16);
+        let pkce = Pkce::generate();
+
+        let authorization_url = url::Url::parse_with_params(
+            &config.auth_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", config.client_id.as_str()),
+                ("redirect_uri", redirect_uri.as_str()),
+                ("scope", config.scopes.as_str()),
+                ("state", state.as_str()),
+                ("code_challenge", pkce.challenge.as_str()),
+                ("code_challenge_method", "S256"),
+                // 需要 refresh token 才能在之后刷新
+                ("access_type", "offline"),
+                ("prompt", "consent"),
+            ],
+        )
+        .map_err(|e| {
+            AgentError::InvalidInput(format!("无效的授权端点 {}: {}", config.auth_endpoint, e))
+        })?
+        .to_string();
+
+        tracing::info!(target: "oauth::login", redirect_uri = %redirect_uri, "🔐 等待浏览器完成授权");
+        Ok(Self {
+            config,
+            listener,
+            redirect_uri,
+            state,
+            pkce,
+            authorization_url,
+        })
+    }
+
+    /// 需要在浏览器中打开的授权链接
+    pub fn authorization_url(&self) -> &str {
+        &self.authorization_url
+    }
+
+    /// 等待回调、换取令牌并保存账户快照（超时或被取消时返回错误）
+    pub async fn finish(self) -> Result<SavedAccount, AgentError> {
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        if let Some(previous) = ACTIVE_LOGIN
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(cancel_tx)
+        {
+            // 同一时间只保留一个登录
+            let _ = previous.send(());
+        }
+
+        let code = tokio::select! {
+            result = tokio::time::timeout(LOGIN_TIMEOUT, wait_for_code(&self.listener, &self.state)) => {
+                result.map_err(|_| AgentError::OAuth("等待浏览器授权超时".to_string()))?
+            }
+            _ = cancel_rx => Err(AgentError::OAuth("登录已取消".to_string())),
+        };
+        drop(self.listener);
+        let code = code?;
+
+        let response = super::request_token(
+            &self.config,
+            &[
+                ("grant_type", "authorization_code"),
+                ("code", code.as_str()),
+                ("code_verifier", self.pkce.verifier.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+            ],
+        )
+        .await?;
+        let refresh_token = response
+            .refresh_token
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| AgentError::OAuth("令牌端点未返回 refresh token".to_string()))?;
+
+        let email = fetch_email(&self.config, &response.access_token).await?;
+        let tokens = AuthTokens {
+            access_token: response.access_token,
+            refresh_token: Some(refresh_token),
+            expiry_timestamp: chrono::Utc::now().timestamp() + response.expires_in,
+        };
+        let token_type = response.token_type.unwrap_or_else(|| "Bearer".to_string());
+
+        let agent_state = build_snapshot(&email, &tokens, &token_type)?;
+        let saved = account::save_agent_state(&agent_state)?;
+
+        tracing::info!(target: "oauth::login", email = %email, is_new = saved.is_new, "✅ 登录完成，已保存账户");
+        Ok(saved)
+    }
+}
+
+/// 取消正在进行的登录，返回是否有登录被取消
+pub fn cancel_login() -> bool {
+    let active = ACTIVE_LOGIN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    match active {
+        Some(cancel) => cancel.send(()).is_ok(),
+        None => false,
+    }
+}
+
+/// 开始登录并在默认浏览器中打开授权页面，等待完成
+pub async fn login_with_browser() -> Result<SavedAccount, AgentError> {
+    let pending = PendingLogin::start(OAuthConfig::from_env()?).await?;
+    open_browser(pending.authorization_url())?;
+    pending.finish().await
+}
+
+/// 在默认浏览器中打开链接
+pub fn open_browser(url: &str) -> Result<(), AgentError> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+        // cmd /c start 会把链接中的 & 当作命令分隔符
+        let mut command = std::process::Command::new("rundll32");
+        command
+            .args(["url.dll,FileProtocolHandler", url])
+            .creation_flags(CREATE_NO_WINDOW);
+        command
+    };
+
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = std::process::Command::new("open");
+        command.arg(url);
+        command
+    };
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = {
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(url);
+        command
+    };
+
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| AgentError::io("打开浏览器失败", e))
+}
+
+/// 接受回调连接，直到收到本次登录的授权码或授权错误
+async fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String, AgentError> {
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| AgentError::io("接受登录回调失败", e))?;
+
+        match handle_callback(stream, state).await {
+            Ok(Callback::Code(code)) => return Ok(code),
+            Ok(Callback::Denied(error)) => return Err(AgentError::OAuth(error)),
+            Ok(Callback::Ignored) => {}
+            Err(e) => {
+                tracing::warn!(target: "oauth::login", error = %e, "处理登录回调失败");
+            }
+        }
+    }
+}
+
+async fn handle_callback(mut stream: TcpStream, state: &str) -> std::io::Result<Callback> {
+    let mut head = Vec::with_capacity(1024);
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_REQUEST_HEAD {
+        let n = tokio::time::timeout(CALLBACK_READ_TIMEOUT, stream.read(&mut buf))
+            .await
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "读取请求超时"))??;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+
+    // 请求行：GET /callback?code=...&state=... HTTP/1.1
+    let head = String::from_utf8_lossy(&head);
+    let target = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("/");
+    let url = url::Url::parse(&format!("http://127.0.0.1{target}"))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+    if url.path() != CALLBACK_PATH {
+        respond(&mut stream, "404 Not Found", "未找到页面").await?;
+        return Ok(Callback::Ignored);
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    if param("state").as_deref() != Some(state) {
+        tracing::warn!(target: "oauth::login", "登录回调的 state 不匹配，已忽略");
+        respond(
+            &mut stream,
+            "400 Bad Request",
+            "登录请求无效，请返回应用重新登录。",
+        )
+        .await?;
+        return Ok(Callback::Ignored);
+    }
+
+    if let Some(error) = param("error") {
+        let message = match param("error_description") {
+            Some(description) => format!("{}: {}", error, description),
+            None => error,
+        };
+        respond(
+            &mut stream,
+            "200 OK",
+            &format!("授权失败（{}），可以关闭此页面。", message),
+        )
+        .await?;
+        return Ok(Callback::Denied(message));
+    }
+
+    match param("code").filter(|code| !code.is_empty()) {
+        Some(code) => {
+            respond(
+                &mut stream,
+                "200 OK",
+                "登录成功，可以关闭此页面并返回 Antigravity Agent。",
+            )
+            .await?;
+            Ok(Callback::Code(code))
+        }
+        None => {
+            respond(&mut stream, "400 Bad Request", "回调缺少授权码。").await?;
+            Ok(Callback::Ignored)
+        }
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, message: &str) -> std::io::Result<()> {
+    let body = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Antigravity Agent</title></head>\
+         <body style=\"font-family: sans-serif; text-align: center; padding-top: 4em;\"><p>{message}</p></body></html>"
+    );
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// 用 access token 查询登录账户的邮箱
+async fn fetch_email(config: &OAuthConfig, access_token: &str) -> Result<String, AgentError> {
+    let response = super::http_client()?
+        .get(&config.userinfo_endpoint)
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .map_err(|e| {
+            AgentError::Network(format!("请求 {} 失败: {}", config.userinfo_endpoint, e))
+        })?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AgentError::Network(format!("读取用户信息失败: {}", e)))?;
+    if !status.is_success() {
+        return Err(AgentError::OAuth(format!("用户信息端点返回 {}", status)));
+    }
+
+    let info: UserInfo = serde_json::from_str(&body)
+        .map_err(|e| AgentError::OAuth(format!("无法解析用户信息: {}", e)))?;
+    // 邮箱用于拼接账户文件名
+    if info.email.is_empty() || info.email.contains(['/', '\\']) || info.email.contains("..") {
+        return Err(AgentError::OAuth(format!("无效的邮箱: {}", info.email)));
+    }
+    Ok(info.email)
+}
+
+/// 生成账户快照：已保存的账户替换令牌，否则生成最小快照
+fn build_snapshot(
+    email: &str,
+    tokens: &AuthTokens,
+    token_type: &str,
+) -> Result<String, AgentError> {
+    let account_file = crate::directories::get_account_file(email)?;
+    if account_file.exists() {
+        let existing = restore::read_account_agent_state(&account_file)?;
+        return account::replace_auth_tokens(&existing, tokens);
+    }
+
+    let session = crate::proto::SessionResponse {
+        auth: Some(crate::proto::AuthInfo {
+            access_token: tokens.access_token.clone(),
+            r#type: token_type.to_string(),
+            id_token: tokens.refresh_token.clone().unwrap_or_default(),
+            meta: Some(crate::proto::AuthMetadata {
+                expiry_timestamp: tokens.expiry_timestamp,
+            }),
+        }),
+        context: Some(crate::proto::UserContext {
+            email: email.to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    Ok(base64::engine::general_purpose::STANDARD.encode(session.encode_to_vec()))
+}
@@ -0,0 +1,425 @@
+//! Google OAuth 模块
+//!
+//! 使用已保存账户中的 refresh token（AuthInfo.id_token）换取新的 access token，
+//! 把新令牌写回账户快照的 jetskiStateSync.agentManagerInitState；`login` 子模块提供
+//! 基于 PKCE 的浏览器登录。
+//!
+//! OAuth 客户端配置按以下顺序读取：运行时环境变量 → 构建时环境变量。
+//! - `ANTIGRAVITY_AGENT_OAUTH_CLIENT_ID`（必需）
+//! - `ANTIGRAVITY_AGENT_OAUTH_CLIENT_SECRET`（PKCE 登录时可省略）
+//! - `ANTIGRAVITY_AGENT_OAUTH_TOKEN_ENDPOINT`
+//! - `ANTIGRAVITY_AGENT_OAUTH_AUTH_ENDPOINT`
+//! - `ANTIGRAVITY_AGENT_OAUTH_USERINFO_ENDPOINT`
+//! - `ANTIGRAVITY_AGENT_OAUTH_SCOPES`（空格分隔）
+//!
+//! 各端点默认指向 Google，可改为本地模拟服务进行测试。
+
+pub mod login;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tauri_plugin_http::reqwest;
+
+use crate::antigravity::account::{self, AuthTokens};
+use crate::antigravity::restore;
+use crate::antigravity::token_status::TokenStatus;
+use crate::error::AgentError;
+
+/// Google OAuth 令牌端点
+pub const GOOGLE_TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
+/// Google OAuth 授权端点
+pub const GOOGLE_AUTH_ENDPOINT: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+/// Google 用户信息端点
+pub const GOOGLE_USERINFO_ENDPOINT: &str = "https://www.googleapis.com/oauth2/v2/userinfo";
+/// 默认申请的权限
+pub const DEFAULT_SCOPES: &str =
+    "openid email profile https://www.googleapis.com/auth/cloud-platform";
+
+/// 请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const CLIENT_ID_ENV: &str = "ANTIGRAVITY_AGENT_OAUTH_CLIENT_ID";
+const CLIENT_SECRET_ENV: &str = "ANTIGRAVITY_AGENT_OAUTH_CLIENT_SECRET";
+const TOKEN_ENDPOINT_ENV: &str = "ANTIGRAVITY_AGENT_OAUTH_TOKEN_ENDPOINT";
+const AUTH_ENDPOINT_ENV: &str = "ANTIGRAVITY_AGENT_OAUTH_AUTH_ENDPOINT";
+const USERINFO_ENDPOINT_ENV: &str = "ANTIGRAVITY_AGENT_OAUTH_USERINFO_ENDPOINT";
+const SCOPES_ENV: &str = "ANTIGRAVITY_AGENT_OAUTH_SCOPES";
+
+/// OAuth 客户端配置
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    pub client_id: String,
+    /// 桌面客户端的 secret 无法真正保密，Google 仍要求提供
+    pub client_secret: Option<String>,
+    pub token_endpoint: String,
+    pub auth_endpoint: String,
+    pub userinfo_endpoint: String,
+    /// 空格分隔的权限列表
+    pub scopes: String,
+}
+
+/// 运行时环境变量优先，其次是构建时嵌入的值
+pub(crate) fn config_value(name: &str, build_time: Option<&'static str>) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .or_else(|| build_time.map(str::to_string))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+impl OAuthConfig {
+    /// 从环境变量读取配置
+    pub fn from_env() -> Result<Self, AgentError> {
+        let client_id = config_value(
+            CLIENT_ID_ENV,
+            option_env!("ANTIGRAVITY_AGENT_OAUTH_CLIENT_ID"),
+        )
+        .ok_or_else(|| AgentError::OAuth(format!("未配置 OAuth client_id（{}）", CLIENT_ID_ENV)))?;
+
+        Ok(Self {
+            client_id,
+            client_secret: config_value(
+                CLIENT_SECRET_ENV,
+                option_env!("ANTIGRAVITY_AGENT_OAUTH_CLIENT_SECRET"),
+            ),
+            token_endpoint: config_value(
+                TOKEN_ENDPOINT_ENV,
+                option_env!("ANTIGRAVITY_AGENT_OAUTH_TOKEN_ENDPOINT"),
+            )
+            .unwrap_or_else(|| GOOGLE_TOKEN_ENDPOINT.to_string()),
+            auth_endpoint: config_value(
+                AUTH_ENDPOINT_ENV,
+                option_env!("ANTIGRAVITY_AGENT_OAUTH_AUTH_ENDPOINT"),
+            )
+            .unwrap_or_else(|| GOOGLE_AUTH_ENDPOINT.to_string()),
+            userinfo_endpoint: config_value(
+                USERINFO_ENDPOINT_ENV,
+                option_env!("ANTIGRAVITY_AGENT_OAUTH_USERINFO_ENDPOINT"),
+            )
+            .unwrap_or_else(|| GOOGLE_USERINFO_ENDPOINT.to_string()),
+            scopes: config_value(SCOPES_ENV, option_env!("ANTIGRAVITY_AGENT_OAUTH_SCOPES"))
+                .unwrap_or_else(|| DEFAULT_SCOPES.to_string()),
+        })
+    }
+}
+
+/// 令牌端点的成功响应
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    /// 有效期（秒）
+    pub expires_in: i64,
+    /// 仅在 refresh token 轮换时返回
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub id_token: Option<String>,
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub token_type: Option<String>,
+}
+
+/// 令牌端点的错误响应
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+pub(crate) fn http_client() -> Result<reqwest::Client, AgentError> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| AgentError::Network(format!("创建 HTTP 客户端失败: {}", e)))
+}
+
+/// 向令牌端点发送表单请求
+async fn request_token(
+    config: &OAuthConfig,
+    params: &[(&str, &str)],
+) -> Result<TokenResponse, AgentError> {
+    let body = {
+        let mut form = url::form_urlencoded::Serializer::new(String::new());
+        form.append_pair("client_id", &config.client_id);
+        if let Some(secret) = &config.client_secret {
+            form.append_pair("client_secret", secret);
+        }
+        for (key, value) in params {
+            form.append_pair(key, value);
+        }
+        form.finish()
+    };
+
+    let response = http_client()?
+        .post(&config.token_endpoint)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| AgentError::Network(format!("请求 {} 失败: {}", config.token_endpoint, e)))?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AgentError::Network(format!("读取令牌响应失败: {}", e)))?;
+
+    if !status.is_success() {
+        return Err(match serde_json::from_str::<TokenErrorResponse>(&body) {
+            Ok(error) => AgentError::OAuth(match error.error_description {
+                Some(description) => format!("{}: {}", error.error, description),
+                None => error.error,
+            }),
+            Err(_) => AgentError::Network(format!("令牌端点返回 {}", status)),
+        });
+    }
+
+    serde_json::from_str(&body).map_err(|e| AgentError::OAuth(format!("无法解析令牌响应: {}", e)))
+}
+
+/// 用 refresh token 换取新的 access token
+pub async fn refresh_access_token(
+    config: &OAuthConfig,
+    refresh_token: &str,
+) -> Result<TokenResponse, AgentError> {
+    request_token(
+        config,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ],
+    )
+    .await
+}
+
+/// 令牌刷新结果
+///
+/// 会返回给前端，不包含令牌本身；需要令牌时重新读取账户文件
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshedAccount {
+    pub email: String,
+    /// 新的过期时间（Unix 秒）
+    #[serde(rename = "expiryTimestamp")]
+    pub expiry_timestamp: i64,
+    /// 令牌端点返回了新的 refresh token
+    #[serde(rename = "refreshTokenRotated")]
+    pub refresh_token_rotated: bool,
+    #[serde(rename = "tokenStatus")]
+    pub token_status: TokenStatus,
+}
+
+/// 读取账户目录中的已保存账户，用其中的 refresh token 换取新令牌，返回原始快照与新令牌
+async fn refresh_account_tokens(
+    config: &OAuthConfig,
+    accounts_dir: &Path,
+    email: &str,
+) -> Result<(String, AuthTokens), AgentError> {
+    crate::directories::validate_account_name(email)?;
+    let account_file = accounts_dir.join(format!("{email}.json"));
+    if !account_file.exists() {
+        return Err(AgentError::AccountNotFound(email.to_string()));
+    }
+
+    let agent_state = restore::read_account_agent_state(&account_file)?;
+    let refresh_token = account::decode_session(&agent_state)?
+        .auth
+        .map(|auth| auth.id_token)
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| AgentError::OAuth(format!("账户 {} 中没有 refresh token", email)))?;
+
+    tracing::info!(target: "oauth", email = %email, endpoint = %config.token_endpoint, "🔑 开始刷新令牌");
+    let response = refresh_access_token(config, &refresh_token).await?;
+
+    let tokens = AuthTokens {
+        access_token: response.access_token,
+        refresh_token: response.refresh_token.filter(|token| !token.is_empty()),
+        expiry_timestamp: Utc::now().timestamp() + response.expires_in,
+    };
+    Ok((agent_state, tokens))
+}
+
+fn refreshed(email: &str, tokens: &AuthTokens) -> RefreshedAccount {
+    RefreshedAccount {
+        email: email.to_string(),
+        expiry_timestamp: tokens.expiry_timestamp,
+        refresh_token_rotated: tokens.refresh_token.is_some(),
+        token_status: TokenStatus::from_expiry(Some(tokens.expiry_timestamp)),
+    }
+}
+
+/// 刷新已保存账户的 access token，并写回账户文件（同时记录历史版本）
+pub async fn refresh_saved_account(email: &str) -> Result<RefreshedAccount, AgentError> {
+    let config = OAuthConfig::from_env()?;
+    let accounts_dir = crate::directories::get_accounts_directory();
+    let (agent_state, tokens) = refresh_account_tokens(&config, &accounts_dir, email).await?;
+    let updated = account::replace_auth_tokens(&agent_state, &tokens)?;
+    account::save_agent_state(&updated)?;
+
+    tracing::info!(target: "oauth", email = %email, expiry = tokens.expiry_timestamp, "✅ 令牌已刷新并写回账户");
+    Ok(refreshed(email, &tokens))
+}
+
+/// 刷新已保存账户的 access token，只在内存中使用，不写回账户文件也不记录历史版本
+///
+/// 令牌端点返回了新的 refresh token 时旧的会失效，此时仅更新账户文件中的令牌
+pub async fn refresh_account_in_memory(email: &str) -> Result<AuthTokens, AgentError> {
+    let config = OAuthConfig::from_env()?;
+    let accounts_dir = crate::directories::get_accounts_directory();
+    let (agent_state, tokens) = refresh_account_tokens(&config, &accounts_dir, email).await?;
+    if tokens.refresh_token.is_some() {
+        let updated = account::replace_auth_tokens(&agent_state, &tokens)?;
+        account::write_account_tokens(email, &updated)?;
+        tracing::info!(target: "oauth", email = %email, "🔄 refresh token 已轮换，已更新账户文件");
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use prost::Message;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use crate::proto::{AuthInfo, AuthMetadata, SessionResponse, UserContext};
+
+    const EMAIL: &str = "user@example.com";
+
+    fn agent_state() -> String {
+        let session = SessionResponse {
+            auth: Some(AuthInfo {
+                access_token: "old-access".to_string(),
+                r#type: "Bearer".to_string(),
+                id_token: "old-refresh".to_string(),
+                meta: Some(AuthMetadata {
+                    expiry_timestamp: 1_700_000_000,
+                }),
+            }),
+            context: Some(UserContext {
+                email: EMAIL.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        base64::engine::general_purpose::STANDARD.encode(session.encode_to_vec())
+    }
+
+    /// 只处理一次请求的令牌端点，返回收到的请求体
+    async fn mock_token_endpoint(
+        response: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/token", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text[..header_end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())
+                                .flatten()
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            );
+            stream.write_all(reply.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+
+            let text = String::from_utf8_lossy(&request).to_string();
+            text.split_once("\r\n\r\n")
+                .map(|(_, body)| body.to_string())
+                .unwrap_or_default()
+        });
+
+        (endpoint, handle)
+    }
+
+    fn test_config(token_endpoint: String) -> OAuthConfig {
+        OAuthConfig {
+            client_id: "test-client".to_string(),
+            client_secret: None,
+            token_endpoint,
+            auth_endpoint: GOOGLE_AUTH_ENDPOINT.to_string(),
+            userinfo_endpoint: GOOGLE_USERINFO_ENDPOINT.to_string(),
+            scopes: DEFAULT_SCOPES.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_account_tokens_exchanges_saved_refresh_token() {
+        let accounts_dir =
+            std::env::temp_dir().join(format!("oauth-refresh-test-{}", std::process::id()));
+        std::fs::create_dir_all(&accounts_dir).unwrap();
+        let account_file = accounts_dir.join(format!("{EMAIL}.json"));
+        let content = serde_json::json!({ crate::constants::database::AGENT_STATE: agent_state() });
+        std::fs::write(&account_file, content.to_string()).unwrap();
+
+        let (endpoint, server) = mock_token_endpoint(
+            r#"{"access_token":"new-access","expires_in":3600,"token_type":"Bearer"}"#,
+        )
+        .await;
+
+        let before = Utc::now().timestamp();
+        let (saved, tokens) = refresh_account_tokens(&test_config(endpoint), &accounts_dir, EMAIL)
+            .await
+            .unwrap();
+        assert_eq!(saved, agent_state());
+        assert_eq!(tokens.access_token, "new-access");
+        assert!(tokens.refresh_token.is_none());
+        assert!(tokens.expiry_timestamp >= before + 3600);
+
+        let request = server.await.unwrap();
+        let params: std::collections::HashMap<String, String> =
+            url::form_urlencoded::parse(request.as_bytes())
+                .into_owned()
+                .collect();
+        assert_eq!(
+            params.get("grant_type").map(String::as_str),
+            Some("refresh_token")
+        );
+        assert_eq!(
+            params.get("refresh_token").map(String::as_str),
+            Some("old-refresh")
+        );
+        assert_eq!(
+            params.get("client_id").map(String::as_str),
+            Some("test-client")
+        );
+
+        let updated = account::replace_auth_tokens(&saved, &tokens).unwrap();
+        let auth = account::decode_session(&updated).unwrap().auth.unwrap();
+        assert_eq!(auth.access_token, "new-access");
+        assert_eq!(auth.id_token, "old-refresh");
+        assert_eq!(auth.meta.unwrap().expiry_timestamp, tokens.expiry_timestamp);
+
+        std::fs::remove_dir_all(&accounts_dir).unwrap();
+    }
+}
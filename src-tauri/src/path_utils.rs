@@ -209,6 +209,35 @@ fn antigravity_data_dir_impl() -> Option<PathBuf> {
         })
 }
 
+/// 从 AppImage 文件名中解析版本号（第一段由数字和点组成的部分），没有版本号时为空
+#[cfg(target_os = "linux")]
+fn appimage_version(path: &Path) -> Vec<u64> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let Some(start) = name.find(|c: char| c.is_ascii_digit()) else {
+        return Vec::new();
+    };
+    name[start..]
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// 版本号较大的排在前面（按数值比较，1.10 高于 1.9），没有版本号的排在最后
+#[cfg(target_os = "linux")]
+fn sort_appimages(paths: &mut [PathBuf]) {
+    paths.sort_by(|a, b| {
+        appimage_version(b)
+            .cmp(&appimage_version(a))
+            .then_with(|| b.cmp(a))
+    });
+}
+
 #[cfg(target_os = "linux")]
 fn antigravity_executable_paths_impl() -> Vec<PathBuf> {
     let mut paths = Vec::new();
@@ -227,22 +256,65 @@ fn antigravity_executable_paths_impl() -> Vec<PathBuf> {
     // Snap 包
     paths.push(PathBuf::from("/snap/bin/antigravity"));
 
-    // AppImage 和 Flatpak
+    // AppImage（文件名通常带版本号，如 Antigravity-1.0.0-x86_64.AppImage）
     if let Some(home) = home_dir() {
-        paths.push(home.join("Applications").join("Antigravity.AppImage"));
+        let applications = home.join("Applications");
+        paths.push(applications.join("Antigravity.AppImage"));
+
+        let mut versioned: Vec<PathBuf> = std::fs::read_dir(&applications)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        let name = path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_lowercase())
+                            .unwrap_or_default();
+                        name.contains("antigravity")
+                            && name.ends_with(".appimage")
+                            && name != "antigravity.appimage"
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        sort_appimages(&mut versioned);
+        paths.extend(versioned);
     }
 
-    paths.push(PathBuf::from("/var/lib/flatpak/exports/bin/antigravity"));
+    // Flatpak 导出的命令以应用 ID 命名（如 com.google.Antigravity）
+    let mut flatpak_bin_dirs = vec![PathBuf::from("/var/lib/flatpak/exports/bin")];
     if let Some(home) = home_dir() {
-        paths.push(
+        flatpak_bin_dirs.push(
             home.join(".local")
                 .join("share")
                 .join("flatpak")
                 .join("exports")
-                .join("bin")
-                .join("antigravity"),
+                .join("bin"),
         );
     }
+    for bin_dir in flatpak_bin_dirs {
+        let mut exported: Vec<PathBuf> = std::fs::read_dir(&bin_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.file_name().is_some_and(|name| {
+                            name.to_string_lossy()
+                                .to_lowercase()
+                                .contains("antigravity")
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if exported.is_empty() {
+            exported.push(bin_dir.join("antigravity"));
+        }
+        exported.sort();
+        paths.extend(exported);
+    }
 
     paths
 }
@@ -281,3 +353,47 @@ fn antigravity_executable_paths_impl() -> Vec<PathBuf> {
 fn sanitize_user_path_impl(path: &Path) -> String {
     path.to_string_lossy().to_string()
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appimage_version_parses_numeric_parts() {
+        assert_eq!(
+            appimage_version(Path::new("/apps/Antigravity-1.10.2-x86_64.AppImage")),
+            vec![1, 10, 2]
+        );
+        assert_eq!(
+            appimage_version(Path::new("antigravity_2.0.AppImage")),
+            vec![2, 0]
+        );
+        assert!(appimage_version(Path::new("Antigravity-latest.AppImage")).is_empty());
+    }
+
+    #[test]
+    fn appimages_sort_by_numeric_version() {
+        let mut paths: Vec<PathBuf> = [
+            "Antigravity-1.9.0-x86_64.AppImage",
+            "Antigravity-latest.AppImage",
+            "Antigravity-1.10.0-x86_64.AppImage",
+            "Antigravity-1.10.0.1-x86_64.AppImage",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        sort_appimages(&mut paths);
+        assert_eq!(
+            paths,
+            [
+                "Antigravity-1.10.0.1-x86_64.AppImage",
+                "Antigravity-1.10.0-x86_64.AppImage",
+                "Antigravity-1.9.0-x86_64.AppImage",
+                "Antigravity-latest.AppImage",
+            ]
+            .iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>()
+        );
+    }
+}
@@ -2,8 +2,30 @@ use crate::path_utils::AppPaths;
 use std::path::PathBuf;
 
 /// 获取Antigravity应用数据目录（跨平台）
+/// 优先级：活动目标 > 用户自定义数据目录 > 自动检测
 pub fn get_antigravity_data_dir() -> Option<PathBuf> {
-    AppPaths::antigravity_data_dir()
+    match crate::antigravity::targets::active_target() {
+        Ok(Some(target)) => Some(target.global_storage_dir()),
+        Ok(None) => get_custom_antigravity_data_dir().or_else(AppPaths::antigravity_data_dir),
+        Err(e) => {
+            tracing::warn!("⚠️ 无法确定活动目标: {}", e);
+            None
+        }
+    }
+}
+
+/// 获取用户自定义数据目录对应的 globalStorage 目录（未配置时返回 None）
+pub fn get_custom_antigravity_data_dir() -> Option<PathBuf> {
+    let custom = crate::antigravity::path_config::get_custom_data_dir()
+        .ok()
+        .flatten()?;
+    let custom = PathBuf::from(custom);
+
+    // 数据库暂时不存在时仍使用自定义目录，不回退到自动检测
+    Some(
+        crate::antigravity::path_config::resolve_global_storage_dir(&custom)
+            .unwrap_or_else(|| custom.join("User").join("globalStorage")),
+    )
 }
 
 /// 获取Antigravity状态数据库文件路径
@@ -1,79 +1,546 @@
-/// 关闭Antigravity进程 - 使用sysinfo库实现跨平台统一处理
-pub fn kill_antigravity_processes() -> Result<String, String> {
-    tracing::info!("🔍 开始搜索并关闭 Antigravity 进程");
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessStatus, System};
 
-    // 使用sysinfo库获取所有进程
-    let mut system = sysinfo::System::new_all();
-    system.refresh_all();
+use crate::antigravity::targets::{self, AntigravityTarget};
+use crate::error::AgentError;
 
-    let mut killed_processes = Vec::new();
+/// 等待进程退出的默认超时时间
+pub const DEFAULT_EXIT_TIMEOUT: Duration = Duration::from_secs(10);
 
-    // 定义需要关闭的进程模式（按优先级排序）
-    let process_patterns = get_antigravity_process_patterns();
+/// 轮询进程状态的间隔
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(200);
 
-    for (pid, process) in system.processes() {
-        let process_name = process.name();
-        let process_cmd = process.cmd().join(" ");
-
-        // 检查进程名或命令行是否匹配任何模式
-        if matches_antigravity_process(process_name, &process_cmd, &process_patterns) {
-            tracing::info!("🎯 找到目标进程: {} (PID: {})", process_name, pid);
-            tracing::info!("📝 命令行: {}", process_cmd);
-
-            // 尝试终止进程
-            if process.kill() {
-                killed_processes.push(format!("{} (PID: {})", process_name, pid));
-                tracing::info!("✅ 成功终止进程: {} (PID: {})", process_name, pid);
-            } else {
-                tracing::warn!("⚠️ 终止进程失败: {} (PID: {})", process_name, pid);
+/// 强制终止后再等待的时间
+const FORCE_KILL_GRACE: Duration = Duration::from_secs(3);
+
+/// 进程退出等待结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessExitReport {
+    /// 使用的超时时间
+    #[serde(rename = "timeoutMs")]
+    pub timeout_ms: u64,
+    /// 实际等待时间
+    #[serde(rename = "waitedMs")]
+    pub waited_ms: u64,
+    /// 是否在超时内未能全部退出
+    #[serde(rename = "timedOut")]
+    pub timed_out: bool,
+    /// 超时后被强制终止的 PID
+    #[serde(rename = "forceKilled")]
+    pub force_killed: Vec<u32>,
+    /// 强制终止后仍存活的 PID
+    pub remaining: Vec<u32>,
+}
+
+impl ProcessExitReport {
+    /// 所有进程是否均已退出
+    pub fn all_exited(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    /// 简短描述，用于拼接命令结果
+    pub fn summary(&self) -> String {
+        if !self.all_exited() {
+            format!(
+                "进程未能退出（等待 {}ms，剩余 PID: {:?}）",
+                self.waited_ms, self.remaining
+            )
+        } else if self.timed_out {
+            format!(
+                "进程在 {}ms 内未退出，已强制终止 {} 个",
+                self.timeout_ms,
+                self.force_killed.len()
+            )
+        } else {
+            format!("进程已退出（等待 {}ms）", self.waited_ms)
+        }
+    }
+}
+
+/// 轮询等待指定进程退出，超时后升级为强制终止（SIGKILL）
+pub async fn wait_for_processes_exit(pids: &[u32], timeout: Duration) -> ProcessExitReport {
+    let start = Instant::now();
+    let mut system = System::new();
+
+    let mut remaining = alive_pids(&mut system, pids);
+    while !remaining.is_empty() && start.elapsed() < timeout {
+        tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        remaining = alive_pids(&mut system, &remaining);
+    }
+
+    let timed_out = !remaining.is_empty();
+    let mut force_killed = Vec::new();
+
+    if timed_out {
+        tracing::warn!(
+            timeout_ms = timeout.as_millis() as u64,
+            remaining = ?remaining,
+            "⏱️ 等待进程退出超时，升级为强制终止"
+        );
 
-                // 尝试多次终止（如果第一次失败）
+        for pid in &remaining {
+            if let Some(process) = system.process(Pid::from_u32(*pid)) {
                 if process.kill() {
-                    killed_processes.push(format!("{} (PID: {} - 强制)", process_name, pid));
-                    tracing::info!("✅ 强制终止进程: {} (PID: {})", process_name, pid);
+                    force_killed.push(*pid);
                 } else {
-                    tracing::error!("❌ 强制终止也失败: {} (PID: {})", process_name, pid);
+                    tracing::error!("❌ 强制终止失败 (PID: {})", pid);
                 }
             }
         }
+
+        let deadline = Instant::now() + FORCE_KILL_GRACE;
+        while !remaining.is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+            remaining = alive_pids(&mut system, &remaining);
+        }
     }
 
-    if killed_processes.is_empty() {
-        tracing::info!("ℹ️ 未找到匹配的 Antigravity 进程");
-        tracing::info!("🔍 搜索的进程模式: {:?}", process_patterns);
-        Err("未找到Antigravity进程".to_string())
-    } else {
-        let success_msg = format!("已成功关闭Antigravity进程: {}", killed_processes.join(", "));
-        tracing::info!("🎉 {}", success_msg);
-        Ok(success_msg)
+    let report = ProcessExitReport {
+        timeout_ms: timeout.as_millis() as u64,
+        waited_ms: start.elapsed().as_millis() as u64,
+        timed_out,
+        force_killed,
+        remaining,
+    };
+    tracing::info!("🏁 {}", report.summary());
+    report
+}
+
+/// 返回仍然存活的 PID（僵尸进程视为已退出）
+fn alive_pids(system: &mut System, pids: &[u32]) -> Vec<u32> {
+    system.refresh_processes();
+    pids.iter()
+        .copied()
+        .filter(|pid| {
+            system
+                .process(Pid::from_u32(*pid))
+                .is_some_and(|p| p.status() != ProcessStatus::Zombie)
+        })
+        .collect()
+}
+
+/// 发送优雅终止信号（Unix: SIGTERM，Windows: 不带 /F 的 taskkill）
+fn terminate_gracefully(pid: u32, process: &sysinfo::Process) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+        let _ = process;
+        std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string()])
+            .creation_flags(CREATE_NO_WINDOW)
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = pid;
+        // 平台不支持该信号时退化为直接终止
+        process
+            .kill_with(sysinfo::Signal::Term)
+            .unwrap_or_else(|| process.kill())
     }
 }
 
-/// 检查 Antigravity 进程是否正在运行（使用 sysinfo）
-pub fn is_antigravity_running() -> bool {
-    tracing::debug!("🔍 检查 Antigravity 进程是否运行");
+/// Antigravity 进程角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessRole {
+    /// 主进程（父进程不是 Antigravity 进程）
+    Main,
+    /// Electron Helper 等子进程
+    Helper,
+}
+
+/// 单个进程的关闭结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownOutcome {
+    /// 主进程收到终止信号后正常退出
+    Terminated,
+    /// 子进程随主进程一起退出
+    Exited,
+    /// 超时后被强制终止
+    ForceKilled,
+    /// 强制终止后仍然存活
+    Failed,
+}
+
+/// 单个进程的关闭记录
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessShutdown {
+    pub pid: u32,
+    pub name: String,
+    pub role: ProcessRole,
+    pub outcome: ShutdownOutcome,
+}
+
+/// 分阶段关闭 Antigravity 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ShutdownReport {
+    pub processes: Vec<ProcessShutdown>,
+    /// 等待退出的详细结果
+    pub exit: ProcessExitReport,
+}
+
+impl ShutdownReport {
+    /// 所有进程是否均已退出
+    pub fn all_exited(&self) -> bool {
+        self.exit.all_exited()
+    }
+
+    /// 简短描述，用于拼接命令结果
+    pub fn summary(&self) -> String {
+        let count = |outcome: ShutdownOutcome| {
+            self.processes
+                .iter()
+                .filter(|p| p.outcome == outcome)
+                .count()
+        };
+
+        format!(
+            "已关闭 Antigravity 进程 {} 个（正常退出 {}，强制终止 {}，失败 {}）; {}",
+            self.processes.len(),
+            count(ShutdownOutcome::Terminated) + count(ShutdownOutcome::Exited),
+            count(ShutdownOutcome::ForceKilled),
+            count(ShutdownOutcome::Failed),
+            self.exit.summary()
+        )
+    }
+}
+
+/// 进程匹配范围
+///
+/// 选择了活动目标时只匹配该目标的进程（`--user-data-dir` 指向目标数据目录，或未指定
+/// `--user-data-dir` 且可执行文件属于目标）；未选择目标时按内置模式匹配默认安装。
+/// 两种情况都会排除其他已配置目标的进程及其子进程，避免切换一个目标时关闭并行运行的其他安装。
+#[derive(Debug, Clone, Default)]
+pub struct ProcessScope {
+    /// 只匹配该目标的进程，None 表示默认安装
+    target: Option<TargetProcesses>,
+    /// 其他已配置的目标
+    others: Vec<TargetProcesses>,
+}
+
+/// 一个目标的进程识别信息
+#[derive(Debug, Clone)]
+struct TargetProcesses {
+    data_dir: PathBuf,
+    executable: Option<PathBuf>,
+}
+
+impl TargetProcesses {
+    fn new(target: &AntigravityTarget) -> Self {
+        Self {
+            data_dir: target.data_dir.clone(),
+            // 进程的可执行文件是解析过符号链接的路径
+            executable: target
+                .executable_path
+                .as_ref()
+                .map(|exe| std::fs::canonicalize(exe).unwrap_or_else(|_| exe.clone())),
+        }
+    }
+
+    /// 进程是否属于该目标
+    fn owns(&self, process: &Process) -> bool {
+        match user_data_dir_arg(process.cmd()) {
+            Some(dir) => same_path(&dir, &self.data_dir),
+            None => self.executable.as_ref().is_some_and(|exe| {
+                process.exe().is_some_and(|path| path.starts_with(exe))
+                    || process
+                        .cmd()
+                        .first()
+                        .is_some_and(|arg0| Path::new(arg0).starts_with(exe))
+            }),
+        }
+    }
+}
+
+impl ProcessScope {
+    /// 按目标配置构建：`active` 为 None 时匹配默认安装
+    pub fn new(active: Option<&AntigravityTarget>, targets: &[AntigravityTarget]) -> Self {
+        Self {
+            target: active.map(TargetProcesses::new),
+            others: targets
+                .iter()
+                .filter(|t| active.is_none_or(|active| active.name != t.name))
+                .map(TargetProcesses::new)
+                .collect(),
+        }
+    }
+
+    /// 当前活动目标对应的范围
+    pub fn current() -> Result<Self, AgentError> {
+        let config = targets::load_targets()?;
+        let active = targets::active_target()?;
+        Ok(Self::new(active.as_ref(), &config.targets))
+    }
+
+    fn includes(&self, process: &Process, patterns: &[ProcessPattern]) -> bool {
+        match &self.target {
+            Some(target) => target.owns(process),
+            None => matches_antigravity_process(process.name(), &process.cmd().join(" "), patterns),
+        }
+    }
 
-    let mut system = sysinfo::System::new_all();
-    system.refresh_all();
+    /// 范围内的进程（不区分主进程与 Helper）
+    fn matching_pids(&self, system: &System) -> HashSet<Pid> {
+        let patterns = get_antigravity_process_patterns();
 
-    let process_patterns = get_antigravity_process_patterns();
+        let excluded: HashSet<Pid> = system
+            .processes()
+            .iter()
+            .filter(|(_, process)| self.others.iter().any(|other| other.owns(process)))
+            .map(|(pid, _)| *pid)
+            .collect();
 
+        system
+            .processes()
+            .iter()
+            .filter(|(pid, process)| {
+                !excluded.contains(pid)
+                    && self.includes(process, &patterns)
+                    && !has_ancestor_in(system, **pid, &excluded)
+            })
+            .map(|(pid, _)| *pid)
+            .collect()
+    }
+}
+
+/// 命令行中 `--user-data-dir` 的值（支持 `--user-data-dir=DIR` 与 `--user-data-dir DIR`）
+fn user_data_dir_arg(cmd: &[String]) -> Option<PathBuf> {
+    let mut args = cmd.iter();
+    while let Some(arg) = args.next() {
+        if let Some(dir) = arg.strip_prefix("--user-data-dir=") {
+            return Some(PathBuf::from(dir.trim_matches('"')));
+        }
+        if arg == "--user-data-dir" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+/// 比较两个路径（能解析时比较解析后的路径，Windows 上忽略大小写）
+fn same_path(a: &Path, b: &Path) -> bool {
+    let normalize =
+        |path: &Path| std::fs::canonicalize(path).unwrap_or_else(|_| path.components().collect());
+    let (a, b) = (normalize(a), normalize(b));
+    if cfg!(windows) {
+        a.to_string_lossy()
+            .eq_ignore_ascii_case(&b.to_string_lossy())
+    } else {
+        a == b
+    }
+}
+
+/// 按进程树区分主进程与 Helper 进程
+///
+/// 主进程：范围内且父进程不在范围内的进程
+/// Helper：范围内的非主进程，以及主进程的所有后代进程
+fn scan_antigravity_process_tree(
+    system: &System,
+    scope: &ProcessScope,
+) -> Vec<(Pid, String, ProcessRole)> {
+    let matched = scope.matching_pids(system);
+
+    let main_pids: HashSet<Pid> = matched
+        .iter()
+        .copied()
+        .filter(|pid| {
+            system
+                .process(*pid)
+                .and_then(|p| p.parent())
+                .is_none_or(|parent| !matched.contains(&parent))
+        })
+        .collect();
+
+    let mut tree = Vec::new();
     for (pid, process) in system.processes() {
-        let process_name = process.name();
-        let process_cmd = process.cmd().join(" ");
+        let role = if main_pids.contains(pid) {
+            ProcessRole::Main
+        } else if matched.contains(pid) || has_ancestor_in(system, *pid, &main_pids) {
+            ProcessRole::Helper
+        } else {
+            continue;
+        };
+        tree.push((*pid, process.name().to_string(), role));
+    }
+
+    tree
+}
+
+/// 沿父进程链查找是否存在指定祖先
+fn has_ancestor_in(system: &System, pid: Pid, ancestors: &HashSet<Pid>) -> bool {
+    let mut current = system.process(pid).and_then(|p| p.parent());
+    // 限制深度，防止异常的进程表形成环
+    for _ in 0..64 {
+        match current {
+            Some(parent) if ancestors.contains(&parent) => return true,
+            Some(parent) => current = system.process(parent).and_then(|p| p.parent()),
+            None => return false,
+        }
+    }
+    false
+}
+
+/// 分阶段关闭 Antigravity 进程 - 使用sysinfo库实现跨平台统一处理
+///
+/// 1. 仅向主进程发送优雅终止信号（SIGTERM），让其自行关闭 Helper 并落盘 state.vscdb
+/// 2. 等待主进程与 Helper 全部退出，最长 `timeout`
+/// 3. 仍存活的进程强制终止（SIGKILL）
+///
+/// 只关闭 `scope` 范围内的进程；没有匹配的进程时返回 `AgentError::ProcessNotRunning`
+pub async fn kill_antigravity_processes(
+    scope: &ProcessScope,
+    timeout: Duration,
+) -> Result<ShutdownReport, AgentError> {
+    tracing::info!("🔍 开始搜索并关闭 Antigravity 进程");
+
+    let mut system = System::new();
+    system.refresh_processes();
+
+    let tree = scan_antigravity_process_tree(&system, scope);
+    if tree.is_empty() {
+        tracing::info!("ℹ️ 未找到匹配的 Antigravity 进程");
+        tracing::info!("🔍 搜索范围: {:?}", scope);
+        return Err(AgentError::ProcessNotRunning);
+    }
+
+    for (pid, name, role) in &tree {
+        if *role != ProcessRole::Main {
+            continue;
+        }
 
-        if matches_antigravity_process(process_name, &process_cmd, &process_patterns) {
-            tracing::debug!(
-                "✅ 发现运行中的 Antigravity 进程: {} (PID: {})",
-                process_name,
+        let Some(process) = system.process(*pid) else {
+            continue;
+        };
+        tracing::info!("🎯 找到主进程: {} (PID: {})", name, pid);
+        tracing::info!("📝 命令行: {}", process.cmd().join(" "));
+
+        if terminate_gracefully(pid.as_u32(), process) {
+            tracing::info!("✅ 已发送终止信号: {} (PID: {})", name, pid);
+        } else {
+            tracing::warn!(
+                "⚠️ 发送终止信号失败，等待超时后强制终止: {} (PID: {})",
+                name,
                 pid
             );
-            return true;
         }
     }
 
-    tracing::debug!("ℹ️ 未发现运行中的 Antigravity 进程");
-    false
+    let pids: Vec<u32> = tree.iter().map(|(pid, _, _)| pid.as_u32()).collect();
+    let exit = wait_for_processes_exit(&pids, timeout).await;
+
+    let processes = tree
+        .into_iter()
+        .map(|(pid, name, role)| {
+            let pid = pid.as_u32();
+            let outcome = if exit.remaining.contains(&pid) {
+                ShutdownOutcome::Failed
+            } else if exit.force_killed.contains(&pid) {
+                ShutdownOutcome::ForceKilled
+            } else if role == ProcessRole::Main {
+                ShutdownOutcome::Terminated
+            } else {
+                ShutdownOutcome::Exited
+            };
+            ProcessShutdown {
+                pid,
+                name,
+                role,
+                outcome,
+            }
+        })
+        .collect();
+
+    let report = ShutdownReport { processes, exit };
+    tracing::info!("🎉 {}", report.summary());
+    Ok(report)
+}
+
+/// 检查 `scope` 范围内的 Antigravity 进程是否正在运行
+pub fn is_antigravity_running(scope: &ProcessScope) -> bool {
+    tracing::debug!("🔍 检查 Antigravity 进程是否运行");
+
+    let running = !find_antigravity_pids(scope).is_empty();
+    if running {
+        tracing::debug!("✅ 发现运行中的 Antigravity 进程");
+    } else {
+        tracing::debug!("ℹ️ 未发现运行中的 Antigravity 进程");
+    }
+    running
+}
+
+/// 查找 `scope` 范围内的 Antigravity 进程 PID（僵尸进程除外）
+pub fn find_antigravity_pids(scope: &ProcessScope) -> Vec<u32> {
+    let mut system = System::new();
+    system.refresh_processes();
+
+    scope
+        .matching_pids(&system)
+        .into_iter()
+        .filter(|pid| {
+            system
+                .process(*pid)
+                .is_some_and(|p| p.status() != ProcessStatus::Zombie)
+        })
+        .map(|pid| pid.as_u32())
+        .collect()
+}
+
+/// 当前所有进程的 PID（用于区分启动前已存在的进程）
+pub fn running_pids() -> HashSet<u32> {
+    let mut system = System::new();
+    system.refresh_processes();
+    system.processes().keys().map(|pid| pid.as_u32()).collect()
+}
+
+/// 查找一次启动产生的进程（僵尸进程除外）
+///
+/// 包括启动的子进程 `root` 及其后代，以及不在 `existing` 中、可执行文件位于 `executable`
+/// 下的进程及其后代（`open`、启动脚本等启动器退出后，实际进程不再是子进程的后代）
+pub fn find_launched_pids(
+    root: u32,
+    executable: Option<&Path>,
+    existing: &HashSet<u32>,
+) -> Vec<u32> {
+    let mut system = System::new();
+    system.refresh_processes();
+
+    let mut roots = HashSet::from([Pid::from_u32(root)]);
+    if let Some(exe) = executable {
+        let exe = std::fs::canonicalize(exe).unwrap_or_else(|_| exe.to_path_buf());
+        roots.extend(
+            system
+                .processes()
+                .iter()
+                .filter(|(pid, process)| {
+                    !existing.contains(&pid.as_u32())
+                        && (process.exe().is_some_and(|path| path.starts_with(&exe))
+                            || process
+                                .cmd()
+                                .first()
+                                .is_some_and(|arg0| Path::new(arg0).starts_with(&exe)))
+                })
+                .map(|(pid, _)| *pid),
+        );
+    }
+
+    system
+        .processes()
+        .iter()
+        .filter(|(pid, process)| {
+            process.status() != ProcessStatus::Zombie
+                && (roots.contains(pid) || has_ancestor_in(&system, **pid, &roots))
+        })
+        .map(|(pid, _)| pid.as_u32())
+        .collect()
 }
 
 /// 获取 Antigravity 进程匹配模式
@@ -144,3 +611,40 @@ pub enum ProcessPattern {
     ExactName(&'static str),   // 精确匹配进程名
     CmdContains(&'static str), // 命令行包含指定文本
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &[&str]) -> Vec<String> {
+        cmd.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn user_data_dir_arg_supports_both_forms() {
+        assert_eq!(
+            user_data_dir_arg(&args(&["antigravity", "--user-data-dir=/data/a"])),
+            Some(PathBuf::from("/data/a"))
+        );
+        assert_eq!(
+            user_data_dir_arg(&args(&["antigravity", "--user-data-dir", "/data/b", "."])),
+            Some(PathBuf::from("/data/b"))
+        );
+        assert_eq!(
+            user_data_dir_arg(&args(&["antigravity", "--type=renderer"])),
+            None
+        );
+    }
+
+    #[test]
+    fn same_path_ignores_trailing_separator() {
+        assert!(same_path(
+            Path::new("/no/such/dir/a/"),
+            Path::new("/no/such/dir/a")
+        ));
+        assert!(!same_path(
+            Path::new("/no/such/dir/a"),
+            Path::new("/no/such/dir/b")
+        ));
+    }
+}
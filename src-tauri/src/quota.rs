@@ -0,0 +1,560 @@
+//! 账户配额模块
+//!
+//! 对所有已保存账户并发调用 Cloud Code 的 loadCodeAssist / fetchAvailableModels，
+//! 获取套餐与各模型的剩余配额，结果缓存在 ~/.antigravity-agent/quota_cache.json。
+//! 请求受并发数与最小间隔限制；access token 过期或被拒绝时先刷新令牌再重试一次，
+//! 刷新得到的令牌只保存在内存中，不写回账户文件。
+//!
+//! 服务地址默认使用沙盒环境，可通过 `ANTIGRAVITY_AGENT_CLOUDCODE_BASE_URL`
+//! （运行时或构建时）替换为本地模拟服务。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+use crate::antigravity::{account, restore};
+use crate::error::AgentError;
+use crate::oauth;
+
+/// 默认的 Cloud Code 服务地址
+pub const DEFAULT_BASE_URL: &str = "https://daily-cloudcode-pa.sandbox.googleapis.com";
+const BASE_URL_ENV: &str = "ANTIGRAVITY_AGENT_CLOUDCODE_BASE_URL";
+
+/// 同时查询的账户数
+const MAX_CONCURRENT_ACCOUNTS: usize = 3;
+/// 两次请求之间的最小间隔
+const REQUEST_SPACING: Duration = Duration::from_millis(300);
+/// 后台刷新间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// 启动后首次刷新前的等待时间
+const INITIAL_DELAY: Duration = Duration::from_secs(60);
+/// 缓存文件格式版本
+const CACHE_VERSION: u32 = 1;
+
+/// 串行化缓存的读-改-写
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+/// 同一时间只进行一次全量刷新
+static REFRESH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+/// 查询配额时在内存中刷新的 access token（邮箱 → 令牌与过期时间），不写回账户文件
+static ACCESS_TOKENS: Mutex<BTreeMap<String, (String, i64)>> = Mutex::new(BTreeMap::new());
+
+/// 单个模型的配额
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelQuota {
+    /// 模型 id（如 gemini-3-pro-high）
+    pub id: String,
+    #[serde(rename = "displayName", default)]
+    pub display_name: Option<String>,
+    /// 剩余配额比例（0 ~ 1）
+    #[serde(rename = "remainingFraction", default)]
+    pub remaining_fraction: Option<f64>,
+    /// 配额重置时间
+    #[serde(rename = "resetTime", default)]
+    pub reset_time: Option<String>,
+}
+
+/// 单个账户的配额
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountQuota {
+    pub email: String,
+    /// 当前套餐（currentTier.id）
+    #[serde(default)]
+    pub tier: Option<String>,
+    #[serde(rename = "tierName", default)]
+    pub tier_name: Option<String>,
+    /// Cloud AI Companion 项目
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub models: Vec<ModelQuota>,
+    /// 最近一次成功获取的时间
+    #[serde(rename = "fetchedAt", default)]
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// 最近一次查询的时间
+    #[serde(rename = "checkedAt")]
+    pub checked_at: DateTime<Utc>,
+    /// 最近一次查询失败的原因（成功时为 None，保留上次成功的数据）
+    #[serde(rename = "lastError", default)]
+    pub last_error: Option<String>,
+}
+
+impl AccountQuota {
+    /// 各模型中最低的剩余配额比例
+    pub fn lowest_remaining(&self) -> Option<f64> {
+        self.models
+            .iter()
+            .filter_map(|m| m.remaining_fraction)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+}
+
+/// quota_cache.json 内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QuotaCache {
+    version: u32,
+    #[serde(default)]
+    accounts: BTreeMap<String, AccountQuota>,
+}
+
+impl Default for QuotaCache {
+    fn default() -> Self {
+        Self {
+            version: CACHE_VERSION,
+            accounts: BTreeMap::new(),
+        }
+    }
+}
+
+fn read_cache() -> QuotaCache {
+    let path = crate::directories::get_quota_cache_file();
+    if !path.exists() {
+        return QuotaCache::default();
+    }
+
+    match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(cache) => cache,
+        Err(e) => {
+            tracing::warn!(target: "quota", error = %e, "配额缓存无法解析，将重新生成");
+            QuotaCache::default()
+        }
+    }
+}
+
+fn write_cache(cache: &QuotaCache) -> Result<(), AgentError> {
+    let path = crate::directories::get_quota_cache_file();
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(cache)?)
+        .map_err(|e| AgentError::io("写入配额缓存失败", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| AgentError::io("替换配额缓存失败", e))
+}
+
+/// 读取缓存的配额（只包含仍然保存着的账户）
+pub fn cached_quotas() -> Vec<AccountQuota> {
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let accounts_dir = crate::directories::get_accounts_directory();
+    read_cache()
+        .accounts
+        .into_values()
+        .filter(|quota| accounts_dir.join(format!("{}.json", quota.email)).exists())
+        .collect()
+}
+
+/// 读取单个账户缓存的配额
+pub fn cached_quota(email: &str) -> Option<AccountQuota> {
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    read_cache().accounts.remove(email)
+}
+
+/// 当前生效的服务地址
+pub fn base_url() -> String {
+    oauth::config_value(
+        BASE_URL_ENV,
+        option_env!("ANTIGRAVITY_AGENT_CLOUDCODE_BASE_URL"),
+    )
+    .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+    .trim_end_matches('/')
+    .to_string()
+}
+
+/// 保证相邻请求之间至少间隔 `REQUEST_SPACING`
+struct RateLimiter {
+    next: tokio::sync::Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new() -> Self {
+        Self {
+            next: tokio::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            tokio::time::sleep_until(*next).await;
+        }
+        *next = (*next).max(now) + REQUEST_SPACING;
+    }
+}
+
+/// 请求失败的原因
+enum RequestError {
+    /// access token 无效或已过期
+    Unauthorized,
+    Other(AgentError),
+}
+
+impl From<AgentError> for RequestError {
+    fn from(e: AgentError) -> Self {
+        Self::Other(e)
+    }
+}
+
+async fn post(
+    limiter: &RateLimiter,
+    base_url: &str,
+    path: &str,
+    access_token: &str,
+    body: Value,
+) -> Result<Value, RequestError> {
+    limiter.wait().await;
+
+    let url = format!("{base_url}{path}");
+    let response = oauth::http_client()?
+        .post(&url)
+        .bearer_auth(access_token)
+        .header("User-Agent", "antigravity/windows/amd64")
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|e| AgentError::Network(format!("请求 {} 失败: {}", url, e)))?;
+
+    let status = response.status();
+    if status == 401 {
+        return Err(RequestError::Unauthorized);
+    }
+    let text = response
+        .text()
+        .await
+        .map_err(|e| AgentError::Network(format!("读取 {} 响应失败: {}", url, e)))?;
+    if !status.is_success() {
+        return Err(AgentError::Network(format!("{} 返回 {}: {}", path, status, text)).into());
+    }
+
+    serde_json::from_str(&text)
+        .map_err(|e| AgentError::Network(format!("无法解析 {} 响应: {}", path, e)).into())
+}
+
+/// 套餐信息与模型配额（不含缓存字段）
+struct FetchedQuota {
+    tier: Option<String>,
+    tier_name: Option<String>,
+    project: Option<String>,
+    models: Vec<ModelQuota>,
+}
+
+async fn fetch_with_token(
+    limiter: &RateLimiter,
+    base_url: &str,
+    access_token: &str,
+) -> Result<FetchedQuota, RequestError> {
+    let code_assist = post(
+        limiter,
+        base_url,
+        "/v1internal:loadCodeAssist",
+        access_token,
+        serde_json::json!({ "metadata": { "ideType": "ANTIGRAVITY" } }),
+    )
+    .await?;
+
+    let project = code_assist
+        .get("cloudaicompanionProject")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let tier = code_assist.get("currentTier");
+    let tier_field = |key: &str| {
+        tier.and_then(|t| t.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    let models_response = post(
+        limiter,
+        base_url,
+        "/v1internal:fetchAvailableModels",
+        access_token,
+        serde_json::json!({ "project": project.clone().unwrap_or_default() }),
+    )
+    .await?;
+
+    let mut models: Vec<ModelQuota> = models_response
+        .get("models")
+        .and_then(Value::as_object)
+        .map(|models| {
+            models
+                .iter()
+                .map(|(id, model)| {
+                    let quota = model.get("quotaInfo");
+                    ModelQuota {
+                        id: id.clone(),
+                        display_name: model
+                            .get("displayName")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                        remaining_fraction: quota
+                            .and_then(|q| q.get("remainingFraction"))
+                            .and_then(Value::as_f64),
+                        reset_time: quota
+                            .and_then(|q| q.get("resetTime"))
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(FetchedQuota {
+        tier: tier_field("id"),
+        tier_name: tier_field("name"),
+        project,
+        models,
+    })
+}
+
+/// 令牌将在一分钟内过期
+fn is_expiring(expiry_timestamp: i64) -> bool {
+    expiry_timestamp <= Utc::now().timestamp() + 60
+}
+
+/// 内存中尚未过期的 access token 及其过期时间
+fn cached_access_token(email: &str) -> Option<(String, i64)> {
+    let mut tokens = ACCESS_TOKENS.lock().unwrap_or_else(|e| e.into_inner());
+    match tokens.get(email) {
+        Some((token, expiry)) if !is_expiring(*expiry) => Some((token.clone(), *expiry)),
+        Some(_) => {
+            tokens.remove(email);
+            None
+        }
+        None => None,
+    }
+}
+
+/// 记录在内存中刷新的 access token
+pub(crate) fn remember_access_token(email: &str, access_token: &str, expiry_timestamp: i64) {
+    ACCESS_TOKENS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(
+            email.to_string(),
+            (access_token.to_string(), expiry_timestamp),
+        );
+}
+
+/// 查询配额时在内存中刷新的令牌的过期时间（Unix 秒），没有或即将过期时为 None
+///
+/// 账户文件中的令牌可能早已过期，按配额选择账户时以此为准
+pub(crate) fn refreshed_token_expiry(email: &str) -> Option<i64> {
+    cached_access_token(email).map(|(_, expiry)| expiry)
+}
+
+/// 查询单个账户；令牌过期或被拒绝时刷新后重试一次
+async fn fetch_account(
+    limiter: &RateLimiter,
+    base_url: &str,
+    email: &str,
+) -> Result<FetchedQuota, AgentError> {
+    let account_file = crate::directories::get_account_file(email)?;
+    let agent_state = restore::read_account_agent_state(&account_file)?;
+    let auth = account::decode_session(&agent_state)?
+        .auth
+        .unwrap_or_default();
+
+    let expired = auth
+        .meta
+        .map(|m| m.expiry_timestamp)
+        .filter(|ts| *ts > 0)
+        .is_some_and(is_expiring);
+
+    // 账户文件中的令牌已过期时，优先使用上次在内存中刷新的令牌
+    let access_token = if expired || auth.access_token.is_empty() {
+        cached_access_token(email).map(|(token, _)| token)
+    } else {
+        Some(auth.access_token)
+    };
+
+    if let Some(access_token) = access_token {
+        match fetch_with_token(limiter, base_url, &access_token).await {
+            Err(RequestError::Unauthorized) => {
+                tracing::info!(target: "quota", email = %email, "access token 被拒绝，刷新后重试");
+            }
+            Err(RequestError::Other(e)) => return Err(e),
+            Ok(quota) => return Ok(quota),
+        }
+    }
+
+    // 只在内存中刷新，避免每次后台刷新都写入账户文件并挤掉历史版本
+    let refreshed = oauth::refresh_account_in_memory(email).await?;
+    remember_access_token(email, &refreshed.access_token, refreshed.expiry_timestamp);
+    let access_token = refreshed.access_token;
+    fetch_with_token(limiter, base_url, &access_token)
+        .await
+        .map_err(|e| match e {
+            RequestError::Unauthorized => {
+                AgentError::OAuth("刷新后的 access token 仍被拒绝".to_string())
+            }
+            RequestError::Other(e) => e,
+        })
+}
+
+/// 已保存账户的邮箱
+pub(crate) fn saved_account_emails() -> Result<Vec<String>, AgentError> {
+    let mut emails: Vec<String> = account::list_saved_accounts()?
+        .into_iter()
+        .map(|entry| entry.summary.email)
+        .collect();
+    emails.sort();
+    Ok(emails)
+}
+
+/// 并发查询所有已保存账户的配额并写入缓存
+///
+/// 单个账户失败不会中断其他账户，失败原因记录在该账户的 `lastError` 中
+pub async fn refresh_all() -> Result<Vec<AccountQuota>, AgentError> {
+    let _refresh = REFRESH_LOCK.lock().await;
+
+    let emails = saved_account_emails()?;
+    let base_url = Arc::new(base_url());
+    let limiter = Arc::new(RateLimiter::new());
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_ACCOUNTS));
+    tracing::info!(target: "quota", count = emails.len(), base_url = %base_url, "📊 开始刷新账户配额");
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for email in emails {
+        let base_url = base_url.clone();
+        let limiter = limiter.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = fetch_account(&limiter, &base_url, &email).await;
+            (email, result)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) => tracing::error!(target: "quota", error = %e, "配额查询任务异常退出"),
+        }
+    }
+
+    let now = Utc::now();
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cache = read_cache();
+    for (email, result) in results {
+        let previous = cache.accounts.remove(&email);
+        let quota = match result {
+            Ok(fetched) => AccountQuota {
+                email: email.clone(),
+                tier: fetched.tier,
+                tier_name: fetched.tier_name,
+                project: fetched.project,
+                models: fetched.models,
+                fetched_at: Some(now),
+                checked_at: now,
+                last_error: None,
+            },
+            Err(e) => {
+                tracing::warn!(target: "quota", email = %email, error = %e, "获取账户配额失败");
+                match previous {
+                    Some(previous) => AccountQuota {
+                        checked_at: now,
+                        last_error: Some(e.to_string()),
+                        ..previous
+                    },
+                    None => AccountQuota {
+                        email: email.clone(),
+                        tier: None,
+                        tier_name: None,
+                        project: None,
+                        models: Vec::new(),
+                        fetched_at: None,
+                        checked_at: now,
+                        last_error: Some(e.to_string()),
+                    },
+                }
+            }
+        };
+        cache.accounts.insert(email, quota);
+    }
+
+    // 已删除的账户不再保留
+    let accounts_dir = crate::directories::get_accounts_directory();
+    cache
+        .accounts
+        .retain(|email, _| accounts_dir.join(format!("{email}.json")).exists());
+    cache.version = CACHE_VERSION;
+    write_cache(&cache)?;
+
+    let failed = cache
+        .accounts
+        .values()
+        .filter(|q| q.checked_at == now && q.last_error.is_some())
+        .count();
+    tracing::info!(target: "quota", total = cache.accounts.len(), failed, "✅ 账户配额刷新完成");
+    Ok(cache.accounts.into_values().collect())
+}
+
+/// 定期刷新所有账户配额
+///
+/// 每次刷新后推送 accounts-quota-updated 事件并重建托盘菜单（显示剩余配额）
+pub struct QuotaRefresher {
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl QuotaRefresher {
+    pub fn new() -> Self {
+        Self {
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 启动后台刷新（启动后稍等片刻再进行首次刷新）
+    pub fn start(&self, app_handle: AppHandle) {
+        let mut task = self.task.lock().unwrap_or_else(|e| e.into_inner());
+        if task.is_some() {
+            return;
+        }
+
+        tracing::info!(target: "quota", "📊 启动账户配额定期刷新");
+        *task = Some(tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(INITIAL_DELAY).await;
+            let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                refresh_and_notify(&app_handle).await;
+            }
+        }));
+    }
+}
+
+impl Default for QuotaRefresher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 刷新所有账户配额，并通知前端与托盘
+pub async fn refresh_and_notify(app_handle: &AppHandle) -> Option<Vec<AccountQuota>> {
+    let quotas = match refresh_all().await {
+        Ok(quotas) => quotas,
+        Err(e) => {
+            tracing::warn!(target: "quota", error = %e, "刷新账户配额失败");
+            return None;
+        }
+    };
+
+    if let Err(e) = app_handle.emit("accounts-quota-updated", &quotas) {
+        tracing::error!(target: "quota", error = %e, "推送配额更新事件失败");
+    }
+    if let Err(e) = crate::system_tray::refresh_tray_menu(app_handle) {
+        tracing::warn!(target: "quota", error = %e, "配额更新后刷新托盘菜单失败");
+    }
+    Some(quotas)
+}
@@ -0,0 +1,659 @@
+//! 账户自动轮换模块
+//!
+//! 按 `AppSettings.rotation` 中的计划（固定间隔或每天的指定本地时间）依次切换到参与轮换的账户。
+//! 切换沿用 `switch_saved_account` 的关闭 → 恢复 → 启动流程，令牌已过期的账户会被跳过。
+//! 每次轮换推送 account-rotated 事件，并更新托盘提示。
+
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::antigravity::token_status::{self, TokenState, TokenStatus};
+use crate::app_settings::AppSettingsManager;
+use crate::error::AgentError;
+
+/// 检查是否到期的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// 固定间隔的最小值（分钟）
+const MIN_INTERVAL_MINUTES: u64 = 5;
+
+/// 轮换时间表
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RotationSchedule {
+    /// 每隔固定分钟数轮换一次
+    Interval { minutes: u64 },
+    /// 每天在指定的本地时间（HH:MM）轮换
+    DailyTimes { times: Vec<String> },
+}
+
+impl Default for RotationSchedule {
+    fn default() -> Self {
+        Self::Interval { minutes: 8 * 60 }
+    }
+}
+
+/// 轮换计划（保存在 AppSettings 中）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationPlan {
+    /// 是否启用自动轮换
+    #[serde(default)]
+    pub enabled: bool,
+    /// 参与轮换的账户邮箱（按轮换顺序）
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    #[serde(default)]
+    pub schedule: RotationSchedule,
+    /// 上次轮换时间（Unix 秒），启用计划时重置为当前时间
+    #[serde(default)]
+    pub last_rotation_at: Option<i64>,
+    /// 上次轮换到的账户
+    #[serde(default)]
+    pub last_account: Option<String>,
+}
+
+impl RotationPlan {
+    /// 校验并整理计划（去除空白与重复账户）
+    pub fn normalize(mut self) -> Result<Self, AgentError> {
+        let mut accounts: Vec<String> = Vec::with_capacity(self.accounts.len());
+        for account in self.accounts {
+            let account = account.trim();
+            if !account.is_empty() && !accounts.iter().any(|a| a == account) {
+                accounts.push(account.to_string());
+            }
+        }
+        self.accounts = accounts;
+
+        match &mut self.schedule {
+            RotationSchedule::Interval { minutes } => {
+                if *minutes < MIN_INTERVAL_MINUTES {
+                    return Err(AgentError::InvalidInput(format!(
+                        "轮换间隔不能小于 {} 分钟",
+                        MIN_INTERVAL_MINUTES
+                    )));
+                }
+            }
+            RotationSchedule::DailyTimes { times } => {
+                let mut parsed = Vec::with_capacity(times.len());
+                for time in times.iter() {
+                    let time = parse_time(time)?;
+                    if !parsed.contains(&time) {
+                        parsed.push(time);
+                    }
+                }
+                if parsed.is_empty() {
+                    return Err(AgentError::InvalidInput("至少需要一个轮换时间".to_string()));
+                }
+                parsed.sort();
+                *times = parsed
+                    .iter()
+                    .map(|t| t.format("%H:%M").to_string())
+                    .collect();
+            }
+        }
+
+        if self.enabled && self.accounts.len() < 2 {
+            return Err(AgentError::InvalidInput(
+                "至少需要两个账户才能启用轮换".to_string(),
+            ));
+        }
+        Ok(self)
+    }
+
+    /// 下一次计划轮换的时间
+    pub fn next_rotation_at<Tz: TimeZone>(&self, now: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let tz = now.timezone();
+        match &self.schedule {
+            RotationSchedule::Interval { minutes } => {
+                let last = self
+                    .last_rotation_at
+                    .and_then(|ts| tz.timestamp_opt(ts, 0).single())
+                    .unwrap_or(now);
+                Some(last + chrono::Duration::minutes(*minutes as i64))
+            }
+            RotationSchedule::DailyTimes { times } => {
+                let today = now.date_naive();
+                (0..=1)
+                    .flat_map(|day| {
+                        let date = today + chrono::Duration::days(day);
+                        let tz = tz.clone();
+                        times.iter().filter_map(move |t| {
+                            let time = parse_time(t).ok()?;
+                            // 夏令时回拨时取第一次出现的时间，跳过的时间当天不轮换
+                            tz.from_local_datetime(&date.and_time(time)).earliest()
+                        })
+                    })
+                    .filter(|at| *at > now)
+                    .min()
+            }
+        }
+    }
+
+    /// 自上次轮换后是否已到达计划时间
+    fn is_due<Tz: TimeZone>(&self, now: DateTime<Tz>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let tz = now.timezone();
+        let Some(last) = self
+            .last_rotation_at
+            .and_then(|ts| tz.timestamp_opt(ts, 0).single())
+        else {
+            return false;
+        };
+
+        match &self.schedule {
+            RotationSchedule::Interval { minutes } => {
+                now >= last + chrono::Duration::minutes(*minutes as i64)
+            }
+            // 最近一个已经过去的计划时间晚于上次轮换
+            RotationSchedule::DailyTimes { times } => {
+                let today = now.date_naive();
+                (0..=1).any(|days_ago| {
+                    let date = today - chrono::Duration::days(days_ago);
+                    times.iter().any(|t| {
+                        parse_time(t)
+                            .ok()
+                            .and_then(|time| {
+                                tz.from_local_datetime(&date.and_time(time)).earliest()
+                            })
+                            .is_some_and(|at| at <= now && at > last)
+                    })
+                })
+            }
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, AgentError> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| AgentError::InvalidInput(format!("无效的时间（需为 HH:MM）: {}", time)))
+}
+
+/// 被跳过的账户及原因
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedAccount {
+    pub email: String,
+    pub reason: String,
+}
+
+/// account-rotated 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct RotationEvent {
+    /// 轮换前的账户
+    pub from: Option<String>,
+    /// 轮换到的账户，没有可用账户时为 None
+    pub to: Option<String>,
+    pub success: bool,
+    pub message: String,
+    pub skipped: Vec<SkippedAccount>,
+    /// 是否由计划触发（否则为手动触发）
+    pub scheduled: bool,
+    #[serde(rename = "rotatedAt")]
+    pub rotated_at: DateTime<Utc>,
+}
+
+/// 自动轮换调度器
+pub struct RotationScheduler {
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl RotationScheduler {
+    pub fn new() -> Self {
+        Self {
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 启动后台检查（计划在每次检查时重新读取，修改设置无需重启）
+    pub fn start(&self, app_handle: AppHandle) {
+        let mut task = self.task.lock().unwrap_or_else(|e| e.into_inner());
+        if task.is_some() {
+            return;
+        }
+
+        tracing::info!(target: "rotation", "🔄 启动账户轮换调度器");
+        *task = Some(tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let plan = app_handle
+                    .state::<AppSettingsManager>()
+                    .get_settings()
+                    .rotation;
+                if !plan.is_due(Local::now()) {
+                    continue;
+                }
+
+                let scheduler = app_handle.state::<RotationScheduler>();
+                scheduler.rotate(&app_handle, true).await;
+            }
+        }));
+    }
+
+    /// 轮换到下一个可用账户
+    pub async fn rotate(&self, app_handle: &AppHandle, scheduled: bool) -> RotationEvent {
+        let settings_manager = app_handle.state::<AppSettingsManager>();
+        let plan = settings_manager.get_settings().rotation;
+
+        let current = crate::antigravity::account::read_current_agent_state()
+            .and_then(|state| crate::antigravity::account::summarize_agent_state(&state))
+            .ok()
+            .map(|summary| summary.email)
+            .filter(|email| !email.is_empty())
+            .or_else(|| plan.last_account.clone());
+
+        let (candidate, skipped) = pick_next_account(&plan.accounts, current.as_deref());
+        let event = match candidate {
+            Some(email) => {
+                tracing::info!(target: "rotation", from = ?current, to = %email, "🔄 开始轮换账户");
+                let result = crate::antigravity::switch::switch_saved_account(
+                    &email,
+                    crate::platform::DEFAULT_EXIT_TIMEOUT,
+                )
+                .await;
+                RotationEvent {
+                    from: current,
+                    to: Some(email),
+                    success: result.success,
+                    message: result.message,
+                    skipped,
+                    scheduled,
+                    rotated_at: Utc::now(),
+                }
+            }
+            None => RotationEvent {
+                from: current,
+                to: None,
+                success: false,
+                message: "没有可轮换的账户（其余账户不存在或令牌已过期）".to_string(),
+                skipped,
+                scheduled,
+                rotated_at: Utc::now(),
+            },
+        };
+
+        // 无论成功与否都记录本次轮换，避免失败后每次检查都重试
+        let rotated_at = event.rotated_at.timestamp();
+        let last_account = event.to.clone().filter(|_| event.success);
+        if let Err(e) = settings_manager.update_settings(|settings| {
+            settings.rotation.last_rotation_at = Some(rotated_at);
+            if last_account.is_some() {
+                settings.rotation.last_account = last_account;
+            }
+        }) {
+            tracing::warn!(target: "rotation", error = %e, "保存轮换状态失败");
+        }
+
+        if event.success {
+            tracing::info!(target: "rotation", "✅ {}", event.message);
+        } else {
+            tracing::warn!(target: "rotation", "⚠️ 账户轮换失败: {}", event.message);
+        }
+
+        let notification = match (&event.to, event.success) {
+            (Some(email), true) => format!("已轮换到 {}", email),
+            (_, _) => format!("账户轮换失败: {}", event.message),
+        };
+        crate::system_tray::show_notification(app_handle, "账户轮换", &notification);
+
+        if let Err(e) = app_handle.emit("account-rotated", &event) {
+            tracing::error!(target: "rotation", error = %e, "推送账户轮换事件失败");
+        }
+        event
+    }
+}
+
+impl Default for RotationScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 从当前账户之后开始，按顺序找到第一个账户文件存在且令牌未过期的账户
+fn pick_next_account(
+    accounts: &[String],
+    current: Option<&str>,
+) -> (Option<String>, Vec<SkippedAccount>) {
+    pick_next_account_with(accounts, current, token_status::saved_account_status)
+}
+
+/// `pick_next_account` 的实现，`status_of` 读取账户的令牌状态
+fn pick_next_account_with(
+    accounts: &[String],
+    current: Option<&str>,
+    status_of: impl Fn(&str) -> Result<TokenStatus, AgentError>,
+) -> (Option<String>, Vec<SkippedAccount>) {
+    let start = current
+        .and_then(|email| accounts.iter().position(|a| a == email))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    let mut skipped = Vec::new();
+    for offset in 0..accounts.len() {
+        let email = &accounts[(start + offset) % accounts.len()];
+        if Some(email.as_str()) == current {
+            continue;
+        }
+
+        let reason = match status_of(email) {
+            Err(e) => Some(format!("无法读取账户: {}", e)),
+            Ok(status) if status.state == TokenState::Expired => Some("令牌已过期".to_string()),
+            Ok(_) => None,
+        };
+
+        match reason {
+            Some(reason) => {
+                tracing::info!(target: "rotation", email = %email, reason = %reason, "跳过账户");
+                skipped.push(SkippedAccount {
+                    email: email.clone(),
+                    reason,
+                });
+            }
+            None => return (Some(email.clone()), skipped),
+        }
+    }
+
+    (None, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, LocalResult, NaiveDate, NaiveDateTime};
+
+    /// 测试用时区：标准时间 UTC-5，2026-03-08 02:00 至 2026-11-01 02:00（本地）为夏令时 UTC-4
+    #[derive(Debug, Clone, Copy)]
+    struct DstZone;
+
+    const STANDARD_OFFSET: i32 = -5 * 3600;
+    const DAYLIGHT_OFFSET: i32 = -4 * 3600;
+
+    fn utc_datetime(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    impl TimeZone for DstZone {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            DstZone
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            // 夏令时偏移在前，即回拨时先出现的时间
+            let offsets: Vec<FixedOffset> = [DAYLIGHT_OFFSET, STANDARD_OFFSET]
+                .into_iter()
+                .map(|secs| FixedOffset::east_opt(secs).unwrap())
+                .filter(|offset| {
+                    let utc = *local - chrono::Duration::seconds(offset.local_minus_utc() as i64);
+                    self.offset_from_utc_datetime(&utc) == *offset
+                })
+                .collect();
+            match offsets.as_slice() {
+                [] => LocalResult::None,
+                [offset] => LocalResult::Single(*offset),
+                [first, second, ..] => LocalResult::Ambiguous(*first, *second),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let daylight =
+                *utc >= utc_datetime(2026, 3, 8, 7, 0) && *utc < utc_datetime(2026, 11, 1, 6, 0);
+            FixedOffset::east_opt(if daylight {
+                DAYLIGHT_OFFSET
+            } else {
+                STANDARD_OFFSET
+            })
+            .unwrap()
+        }
+    }
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<DstZone> {
+        DstZone
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .earliest()
+            .unwrap()
+    }
+
+    fn daily_plan(times: &[&str], last_rotation_at: Option<i64>) -> RotationPlan {
+        RotationPlan {
+            enabled: true,
+            accounts: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+            schedule: RotationSchedule::DailyTimes {
+                times: times.iter().map(|t| t.to_string()).collect(),
+            },
+            last_rotation_at,
+            last_account: None,
+        }
+    }
+
+    fn interval_plan(minutes: u64, last_rotation_at: Option<i64>) -> RotationPlan {
+        RotationPlan {
+            schedule: RotationSchedule::Interval { minutes },
+            ..daily_plan(&[], last_rotation_at)
+        }
+    }
+
+    #[test]
+    fn normalize_trims_accounts_and_sorts_times() {
+        let plan = RotationPlan {
+            enabled: true,
+            accounts: vec![
+                " a@example.com ".to_string(),
+                "b@example.com".to_string(),
+                "".to_string(),
+                "a@example.com".to_string(),
+            ],
+            schedule: RotationSchedule::DailyTimes {
+                times: vec![
+                    "18:30".to_string(),
+                    " 08:00".to_string(),
+                    "18:30".to_string(),
+                ],
+            },
+            ..Default::default()
+        }
+        .normalize()
+        .unwrap();
+
+        assert_eq!(plan.accounts, vec!["a@example.com", "b@example.com"]);
+        assert_eq!(
+            plan.schedule,
+            RotationSchedule::DailyTimes {
+                times: vec!["08:00".to_string(), "18:30".to_string()]
+            }
+        );
+    }
+
+    #[test]
+    fn normalize_rejects_invalid_plans() {
+        assert!(interval_plan(MIN_INTERVAL_MINUTES - 1, None)
+            .normalize()
+            .is_err());
+        assert!(interval_plan(MIN_INTERVAL_MINUTES, None)
+            .normalize()
+            .is_ok());
+        assert!(daily_plan(&[], None).normalize().is_err());
+        assert!(daily_plan(&["25:00"], None).normalize().is_err());
+
+        let single_account = RotationPlan {
+            accounts: vec!["a@example.com".to_string(), " a@example.com".to_string()],
+            ..interval_plan(60, None)
+        };
+        assert!(single_account.clone().normalize().is_err());
+        // 未启用时允许少于两个账户
+        assert!(RotationPlan {
+            enabled: false,
+            ..single_account
+        }
+        .normalize()
+        .is_ok());
+    }
+
+    #[test]
+    fn plan_without_last_rotation_is_never_due() {
+        let now = local(2026, 6, 1, 12, 0);
+        assert!(!interval_plan(5, None).is_due(now));
+        assert!(!daily_plan(&["00:00", "11:00"], None).is_due(now));
+    }
+
+    #[test]
+    fn disabled_plan_is_never_due() {
+        let now = local(2026, 6, 1, 12, 0);
+        let plan = RotationPlan {
+            enabled: false,
+            ..interval_plan(5, Some(now.timestamp() - 3600))
+        };
+        assert!(!plan.is_due(now));
+        assert_eq!(plan.next_rotation_at(now), None);
+    }
+
+    #[test]
+    fn interval_plan_is_due_after_interval() {
+        let last = local(2026, 6, 1, 12, 0);
+        let plan = interval_plan(60, Some(last.timestamp()));
+
+        assert!(!plan.is_due(local(2026, 6, 1, 12, 59)));
+        assert!(plan.is_due(local(2026, 6, 1, 13, 0)));
+        assert_eq!(
+            plan.next_rotation_at(local(2026, 6, 1, 12, 30)),
+            Some(local(2026, 6, 1, 13, 0))
+        );
+        // 没有上次轮换时间时从现在开始计算
+        assert_eq!(
+            interval_plan(60, None).next_rotation_at(last),
+            Some(local(2026, 6, 1, 13, 0))
+        );
+    }
+
+    #[test]
+    fn daily_plan_is_due_once_per_time() {
+        let last = local(2026, 6, 1, 7, 0);
+        let plan = daily_plan(&["08:00", "18:00"], Some(last.timestamp()));
+
+        assert!(!plan.is_due(local(2026, 6, 1, 7, 59)));
+        assert!(plan.is_due(local(2026, 6, 1, 8, 0)));
+
+        // 08:00 轮换后，到 18:00 前不再到期
+        let plan = daily_plan(
+            &["08:00", "18:00"],
+            Some(local(2026, 6, 1, 8, 0).timestamp()),
+        );
+        assert!(!plan.is_due(local(2026, 6, 1, 17, 59)));
+        assert!(plan.is_due(local(2026, 6, 1, 18, 1)));
+        // 前一天错过的时间在第二天仍会补上
+        assert!(plan.is_due(local(2026, 6, 2, 0, 30)));
+    }
+
+    #[test]
+    fn daily_plan_next_rotation_wraps_to_tomorrow() {
+        let plan = daily_plan(&["08:00", "18:00"], None);
+        assert_eq!(
+            plan.next_rotation_at(local(2026, 6, 1, 9, 0)),
+            Some(local(2026, 6, 1, 18, 0))
+        );
+        assert_eq!(
+            plan.next_rotation_at(local(2026, 6, 1, 18, 0)),
+            Some(local(2026, 6, 2, 8, 0))
+        );
+    }
+
+    #[test]
+    fn daily_time_skipped_by_dst_moves_to_next_day() {
+        // 2026-03-08 02:30 不存在（02:00 直接跳到 03:00）
+        let plan = daily_plan(&["02:30"], None);
+        assert_eq!(
+            plan.next_rotation_at(local(2026, 3, 8, 0, 0)),
+            Some(local(2026, 3, 9, 2, 30))
+        );
+    }
+
+    #[test]
+    fn daily_time_repeated_by_dst_rotates_once() {
+        // 2026-11-01 01:30 出现两次，取第一次（夏令时）
+        let plan = daily_plan(&["01:30"], None);
+        let first = DstZone.from_utc_datetime(&utc_datetime(2026, 11, 1, 5, 30));
+        assert_eq!(plan.next_rotation_at(local(2026, 11, 1, 0, 0)), Some(first));
+
+        // 第一次轮换后，第二次出现的 01:30 不再到期
+        let plan = daily_plan(&["01:30"], Some(first.timestamp()));
+        let second = DstZone.from_utc_datetime(&utc_datetime(2026, 11, 1, 6, 40));
+        assert!(!plan.is_due(second));
+    }
+
+    fn accounts(emails: &[&str]) -> Vec<String> {
+        emails.iter().map(|e| e.to_string()).collect()
+    }
+
+    /// expired 中的账户令牌已过期，missing 中的账户无法读取，其余有效
+    fn status_of<'a>(
+        expired: &'a [&'a str],
+        missing: &'a [&'a str],
+    ) -> impl Fn(&str) -> Result<TokenStatus, AgentError> + 'a {
+        move |email| {
+            if missing.contains(&email) {
+                return Err(AgentError::AccountNotFound(email.to_string()));
+            }
+            let expiry = if expired.contains(&email) { 50 } else { 10_000 };
+            Ok(TokenStatus::evaluate(Some(expiry), 100, 60))
+        }
+    }
+
+    #[test]
+    fn pick_next_account_wraps_around() {
+        let list = accounts(&["a", "b", "c"]);
+        let (next, skipped) = pick_next_account_with(&list, Some("c"), status_of(&[], &[]));
+        assert_eq!(next.as_deref(), Some("a"));
+        assert!(skipped.is_empty());
+
+        let (next, _) = pick_next_account_with(&list, Some("a"), status_of(&[], &[]));
+        assert_eq!(next.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn pick_next_account_skips_expired_and_unreadable_accounts() {
+        let list = accounts(&["a", "b", "c", "d"]);
+        let (next, skipped) = pick_next_account_with(&list, Some("b"), status_of(&["c"], &["d"]));
+        assert_eq!(next.as_deref(), Some("a"));
+        let skipped: Vec<&str> = skipped.iter().map(|s| s.email.as_str()).collect();
+        assert_eq!(skipped, vec!["c", "d"]);
+    }
+
+    #[test]
+    fn pick_next_account_starts_from_first_for_unknown_current() {
+        let list = accounts(&["a", "b"]);
+        let (next, _) = pick_next_account_with(&list, Some("x"), status_of(&[], &[]));
+        assert_eq!(next.as_deref(), Some("a"));
+        let (next, _) = pick_next_account_with(&list, None, status_of(&[], &[]));
+        assert_eq!(next.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn pick_next_account_never_returns_current() {
+        let list = accounts(&["a", "b"]);
+        let (next, skipped) = pick_next_account_with(&list, Some("a"), status_of(&["b"], &[]));
+        assert_eq!(next, None);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].email, "b");
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{app_settings, db_monitor, system_tray, window};
+use crate::{
+    app_settings, db_monitor, ipc_server, quota, rotation, system_tray, token_expiry, window,
+};
 use std::sync::Arc;
 use tauri::{App, Manager};
 
@@ -27,8 +29,12 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
     let db_monitor = Arc::new(db_monitor::DatabaseMonitor::new(app.handle().clone()));
     app.manage(db_monitor.clone());
 
-    // 数据库监控将在前端通过命令启动，避免在 setup 中使用 tokio::spawn
-    tracing::debug!(target: "app::setup::db_monitor", "数据库监控将根据前端设置自动启动");
+    // 在后端启动监控，窗口隐藏在托盘时也能自动保存新登录的账户
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = db_monitor.start_monitoring().await {
+            tracing::error!(target: "app::setup::db_monitor", error = %e, "启动数据库监控失败");
+        }
+    });
 
     tracing::info!(target: "app::setup::db_monitor", "数据库监控器初始化完成");
 
@@ -56,6 +62,30 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
         tracing::info!(target: "app::setup::tray", "系统托盘已禁用，跳过创建");
     }
 
+    // 账户自动轮换（计划在设置中启用）
+    app.manage(rotation::RotationScheduler::new());
+    app.state::<rotation::RotationScheduler>()
+        .start(app.handle().clone());
+
+    // 令牌过期提醒
+    crate::antigravity::token_status::set_warning_minutes(settings.token_expiry_warning_minutes);
+    app.manage(token_expiry::TokenExpiryMonitor::new());
+    app.state::<token_expiry::TokenExpiryMonitor>()
+        .start(app.handle().clone());
+
+    // 账户配额定期刷新
+    app.manage(quota::QuotaRefresher::new());
+    app.state::<quota::QuotaRefresher>()
+        .start(app.handle().clone());
+
+    // 本地控制 API（需在设置中启用）
+    app.manage(ipc_server::IpcServer::new());
+    if settings.ipc_server_enabled {
+        if let Err(e) = app.state::<ipc_server::IpcServer>().start() {
+            tracing::error!(target: "app::setup::ipc", error = %e, "启动本地控制 API 失败");
+        }
+    }
+
     // 双重检查：如果静默启动但未启用系统托盘，这是不允许的
     if settings.silent_start_enabled && !settings.system_tray_enabled {
         tracing::warn!(
@@ -106,6 +136,15 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
         tracing::debug!(target: "app::setup::silent_start", "静默启动未启用，正常显示窗口");
     }
 
+    // 注册 URL Scheme，并处理启动时携带的链接或 --switch 参数
+    tauri::async_runtime::spawn_blocking(|| {
+        if let Err(e) = crate::deep_link::register_url_scheme() {
+            tracing::warn!(target: "app::setup::deep_link", error = %e, "注册 URL Scheme 失败");
+        }
+    });
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    crate::single_instance::handle_startup_args(app.handle(), &args);
+
     tracing::info!(target: "app::setup", "应用程序设置完成");
     Ok(())
 }
@@ -0,0 +1,240 @@
+//! 单实例模块
+//!
+//! 第一个启动的实例在 ~/.antigravity-agent/instance.lock 上持有独占文件锁，并在本地回环地址
+//! 监听端口（端口与令牌写入 instance.json）。之后再次启动时把命令行参数转发给正在运行的实例，
+//! 由其恢复主窗口并处理参数（如 `--switch user@x.com`），自身直接退出。
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 连接正在运行的实例的重试次数（对方可能刚拿到锁、尚未写入 instance.json）
+const CONNECT_ATTEMPTS: u32 = 10;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(200);
+const IO_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// instance.json 内容
+#[derive(Debug, Serialize, Deserialize)]
+struct InstanceInfo {
+    pid: u32,
+    port: u16,
+    /// 防止其他本地进程伪造转发请求
+    token: String,
+}
+
+/// 转发给正在运行实例的消息（单行 JSON）
+#[derive(Debug, Serialize, Deserialize)]
+struct ForwardedArgs {
+    token: String,
+    args: Vec<String>,
+}
+
+/// 当前进程持有的单实例锁，需要在应用运行期间保持存活
+pub struct InstanceGuard {
+    _lock: File,
+    listener: TcpListener,
+    token: String,
+}
+
+/// 单实例检查结果
+pub enum Acquire {
+    /// 当前进程是唯一实例
+    Primary(InstanceGuard),
+    /// 已有实例在运行，参数已转发
+    Forwarded,
+}
+
+fn lock_file_path() -> std::path::PathBuf {
+    crate::directories::get_config_directory().join("instance.lock")
+}
+
+fn info_file_path() -> std::path::PathBuf {
+    crate::directories::get_config_directory().join("instance.json")
+}
+
+/// 获取单实例锁；已有实例在运行时把 `args` 转发给它
+pub fn acquire(args: Vec<String>) -> io::Result<Acquire> {
+    let lock = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_file_path())?;
+
+    match lock.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            forward(args)?;
+            return Ok(Acquire::Forwarded);
+        }
+        Err(TryLockError::Error(e)) => return Err(e),
+    }
+
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))?;
+    let token = crate::utils::token::random_hex_token(16);
+
+    // 端口信息单独存放：Windows 上被独占锁定的文件无法被其他进程读取
+    let info = InstanceInfo {
+        pid: std::process::id(),
+        port: listener.local_addr()?.port(),
+        token: token.clone(),
+    };
+    fs::write(info_file_path(), serde_json::to_string_pretty(&info)?)?;
+
+    tracing::info!(target: "app::single_instance", port = info.port, "🔒 已获取单实例锁");
+    Ok(Acquire::Primary(InstanceGuard {
+        _lock: lock,
+        listener,
+        token,
+    }))
+}
+
+/// 把参数发送给正在运行的实例并等待确认
+fn forward(args: Vec<String>) -> io::Result<()> {
+    let mut last_error = io::Error::other("未找到正在运行的实例");
+
+    for _ in 0..CONNECT_ATTEMPTS {
+        match try_forward(&args) {
+            Ok(()) => {
+                tracing::info!(target: "app::single_instance", "📨 已将启动参数转发给正在运行的实例");
+                return Ok(());
+            }
+            Err(e) => last_error = e,
+        }
+        std::thread::sleep(CONNECT_RETRY_DELAY);
+    }
+
+    Err(last_error)
+}
+
+fn try_forward(args: &[String]) -> io::Result<()> {
+    let info: InstanceInfo = serde_json::from_str(&fs::read_to_string(info_file_path())?)?;
+
+    let mut stream = TcpStream::connect_timeout(
+        &SocketAddr::from((Ipv4Addr::LOCALHOST, info.port)),
+        IO_TIMEOUT,
+    )?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+
+    let message = ForwardedArgs {
+        token: info.token,
+        args: args.to_vec(),
+    };
+    writeln!(stream, "{}", serde_json::to_string(&message)?)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    if reply.trim() == "ok" {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "正在运行的实例拒绝了请求: {}",
+            reply.trim()
+        )))
+    }
+}
+
+/// 在后台线程接收其他实例转发的参数
+pub fn listen(app_handle: AppHandle, guard: InstanceGuard) {
+    std::thread::spawn(move || {
+        for stream in guard.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::warn!(target: "app::single_instance", error = %e, "接受转发连接失败");
+                    continue;
+                }
+            };
+
+            if let Err(e) = handle_connection(&app_handle, stream, &guard.token) {
+                tracing::warn!(target: "app::single_instance", error = %e, "处理转发请求失败");
+            }
+        }
+    });
+}
+
+fn handle_connection(app_handle: &AppHandle, mut stream: TcpStream, token: &str) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let message: ForwardedArgs = match serde_json::from_str(&line) {
+        Ok(message) => message,
+        Err(e) => {
+            writeln!(stream, "invalid")?;
+            return Err(e.into());
+        }
+    };
+    if message.token != token {
+        writeln!(stream, "unauthorized")?;
+        return Err(io::Error::other("令牌不匹配"));
+    }
+    writeln!(stream, "ok")?;
+
+    tracing::info!(target: "app::single_instance", args = ?message.args, "收到其他实例转发的启动参数");
+    handle_args(app_handle, &message.args);
+    Ok(())
+}
+
+/// 恢复主窗口并处理转发的参数
+fn handle_args(app_handle: &AppHandle, args: &[String]) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    if let Some(link) = crate::deep_link::find_in_args(args) {
+        crate::deep_link::handle_url(app_handle, link);
+    } else if let Some(email) = switch_target(args) {
+        tracing::info!(target: "app::single_instance", "请求切换到账户: {email}");
+        // 与托盘菜单切换走同一流程
+        if let Err(e) = app_handle.emit("tray-switch-account", email) {
+            tracing::error!(target: "app::single_instance", error = %e, "发射账户切换事件失败");
+        }
+    }
+}
+
+/// 处理主实例自身的启动参数（链接或 `--switch <email>`）
+///
+/// 启动时前端尚未监听 tray-switch-account 事件，`--switch` 直接在后台切换并通过系统通知告知结果
+pub fn handle_startup_args(app_handle: &AppHandle, args: &[String]) {
+    if let Some(link) = crate::deep_link::find_in_args(args) {
+        crate::deep_link::handle_url(app_handle, link);
+    } else if let Some(email) = switch_target(args) {
+        tracing::info!(target: "app::single_instance", "启动参数请求切换到账户: {email}");
+        let app = app_handle.clone();
+        let email = email.to_string();
+        tauri::async_runtime::spawn(async move {
+            let result = crate::antigravity::switch::switch_saved_account(
+                &email,
+                crate::platform::DEFAULT_EXIT_TIMEOUT,
+            )
+            .await;
+            let message = if result.success {
+                format!("已切换到账户: {}", email)
+            } else {
+                format!("切换账户失败: {}", result.message)
+            };
+            crate::system_tray::show_notification(&app, "账户切换", &message);
+        });
+    }
+}
+
+/// 解析 `--switch <email>` 或 `--switch=<email>`
+fn switch_target(args: &[String]) -> Option<&str> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--switch" {
+            return iter.next().map(String::as_str);
+        }
+        if let Some(email) = arg.strip_prefix("--switch=") {
+            return Some(email);
+        }
+    }
+    None
+}
@@ -1,4 +1,5 @@
 use crate::directories;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -12,17 +13,51 @@ pub struct ProfileInfo {
     pub last_updated: String,
 }
 
-// Antigravity 账户信息结构
-#[derive(Debug, Serialize, Deserialize)]
+// Antigravity 账户元数据（保存在账户索引中，按邮箱索引）
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AntigravityAccount {
-    pub id: String,
-    pub name: String,
     pub email: String,
-    pub api_key: String,
-    pub profile_url: String,   // Base64 编码的头像
-    pub user_settings: String, // 编码后的用户设置
-    pub created_at: String,
-    pub last_switched: String,
+    /// 自定义显示名称
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// 账户文件最近一次保存的时间
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(rename = "lastSwitchedAt", default)]
+    pub last_switched_at: Option<DateTime<Utc>>,
+    #[serde(rename = "switchCount", default)]
+    pub switch_count: u32,
+}
+
+impl AntigravityAccount {
+    pub fn new(email: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            email: email.to_string(),
+            label: None,
+            tags: Vec::new(),
+            notes: None,
+            pinned: false,
+            created_at: now,
+            updated_at: now,
+            last_switched_at: None,
+            switch_count: 0,
+        }
+    }
+
+    /// 最近一次活动时间（保存或切换），用于列表排序
+    pub fn last_active_at(&self) -> DateTime<Utc> {
+        self.last_switched_at
+            .map_or(self.updated_at, |t| t.max(self.updated_at))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
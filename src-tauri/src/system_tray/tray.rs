@@ -3,9 +3,14 @@
 //! 使用 Tauri 2.9 内置的 tray API 实现后端控制托盘
 
 use crate::app_settings::AppSettingsManager;
+use std::sync::Mutex;
 use tauri::menu::{Menu, MenuBuilder, MenuItem};
 use tauri::tray::{TrayIcon, TrayIconBuilder};
 use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// 最近一次显示在托盘中的账户列表（配额更新后用于重建菜单）
+static LAST_ACCOUNTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
 
 /// 创建系统托盘（返回托盘实例）
 pub fn create_tray_with_return(app: &AppHandle) -> Result<TrayIcon, String> {
@@ -79,6 +84,8 @@ fn handle_tray_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
 
 /// 更新托盘菜单（添加账户列表）
 pub fn update_tray_menu(app: &AppHandle, accounts: Vec<String>) -> Result<(), String> {
+    *LAST_ACCOUNTS.lock().unwrap_or_else(|e| e.into_inner()) = accounts.clone();
+
     // 检查托盘是否应该启用
     let settings_manager = app.state::<AppSettingsManager>();
     let settings = settings_manager.get_settings();
@@ -105,13 +112,29 @@ pub fn update_tray_menu(app: &AppHandle, accounts: Vec<String>) -> Result<(), St
     if !accounts.is_empty() {
         menu_builder = menu_builder.separator();
 
+        // 设置了显示名称的账户显示名称，否则显示打码后的邮箱
+        let index = crate::antigravity::account_index::load();
+        // 有缓存配额的账户附带最低剩余配额
+        let quotas = crate::quota::cached_quotas();
+
         for account in &accounts {
-            let masked_email = mask_email(account);
+            let mut display_name = index
+                .accounts
+                .get(account)
+                .and_then(|meta| meta.label.clone())
+                .unwrap_or_else(|| mask_email(account));
+            if let Some(remaining) = quotas
+                .iter()
+                .find(|quota| &quota.email == account)
+                .and_then(|quota| quota.lowest_remaining())
+            {
+                display_name = format!("{}（剩余 {:.0}%）", display_name, remaining * 100.0);
+            }
             menu_builder = menu_builder.item(
                 &MenuItem::with_id(
                     app,
                     format!("account_{}", account),
-                    &masked_email,
+                    &display_name,
                     true,
                     None::<&str>,
                 )
@@ -138,6 +161,48 @@ pub fn update_tray_menu(app: &AppHandle, accounts: Vec<String>) -> Result<(), St
     Ok(())
 }
 
+/// 用最近一次的账户列表重建托盘菜单（例如配额更新后）
+pub fn refresh_tray_menu(app: &AppHandle) -> Result<(), String> {
+    let accounts = LAST_ACCOUNTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    if accounts.is_empty() {
+        return Ok(());
+    }
+    update_tray_menu(app, accounts)
+}
+
+/// 通过托盘提示显示最近一条通知（托盘未创建时只记录日志）
+pub fn notify(app: &AppHandle, message: &str) {
+    tracing::info!("🔔 托盘通知: {message}");
+
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    let tooltip = format!("Antigravity Agent\n{message}");
+    if let Err(e) = tray.set_tooltip(Some(&tooltip)) {
+        tracing::warn!("设置托盘提示失败: {e}");
+    }
+}
+
+/// 发送系统通知，并同步更新托盘提示
+///
+/// 通知发送失败（如系统禁用了通知）时只记录日志
+pub fn show_notification(app: &AppHandle, title: &str, message: &str) {
+    notify(app, message);
+
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(message)
+        .show()
+    {
+        tracing::warn!("发送系统通知失败: {e}");
+    }
+}
+
 /// 邮箱打码函数
 fn mask_email(email: &str) -> String {
     let parts: Vec<&str> = email.split('@').collect();
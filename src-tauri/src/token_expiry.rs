@@ -0,0 +1,118 @@
+//! 令牌过期提醒模块
+//!
+//! 后台定期检查所有已保存账户的令牌状态，账户令牌进入“即将过期”或“已过期”时推送
+//! token-expiry-alert 事件并更新托盘提示。同一账户的同一过期时间、同一状态只提醒一次，
+//! 账户快照更新（令牌刷新）后重新计算。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter};
+
+use crate::antigravity::token_status::{self, AccountTokenStatus, TokenState};
+
+/// 检查间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// token-expiry-alert 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenExpiryAlert {
+    /// 本次新进入即将过期或已过期状态的账户
+    pub accounts: Vec<AccountTokenStatus>,
+    /// 提前提醒的分钟数
+    #[serde(rename = "warningMinutes")]
+    pub warning_minutes: u64,
+}
+
+/// 令牌过期检查器
+pub struct TokenExpiryMonitor {
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl TokenExpiryMonitor {
+    pub fn new() -> Self {
+        Self {
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 启动后台检查（启动后立即检查一次）
+    pub fn start(&self, app_handle: AppHandle) {
+        let mut task = self.task.lock().unwrap_or_else(|e| e.into_inner());
+        if task.is_some() {
+            return;
+        }
+
+        tracing::info!(target: "token_expiry", "⏰ 启动令牌过期检查");
+        *task = Some(tauri::async_runtime::spawn(async move {
+            // 邮箱 -> 已提醒的（状态, 过期时间）
+            let mut notified: HashMap<String, (TokenState, Option<i64>)> = HashMap::new();
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                check(&app_handle, &mut notified);
+            }
+        }));
+    }
+}
+
+impl Default for TokenExpiryMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check(app_handle: &AppHandle, notified: &mut HashMap<String, (TokenState, Option<i64>)>) {
+    let statuses = match token_status::saved_account_statuses() {
+        Ok(statuses) => statuses,
+        Err(e) => {
+            tracing::warn!(target: "token_expiry", error = %e, "读取账户令牌状态失败");
+            return;
+        }
+    };
+
+    // 已删除的账户不再跟踪
+    notified.retain(|email, _| statuses.iter().any(|s| &s.email == email));
+
+    let accounts: Vec<AccountTokenStatus> = statuses
+        .into_iter()
+        .filter(|account| {
+            let key = (account.status.state, account.status.expiry_timestamp);
+            if !account.status.needs_attention() {
+                notified.remove(&account.email);
+                return false;
+            }
+            notified.insert(account.email.clone(), key) != Some(key)
+        })
+        .collect();
+
+    if accounts.is_empty() {
+        return;
+    }
+
+    let expired = accounts
+        .iter()
+        .filter(|a| a.status.state == TokenState::Expired)
+        .count();
+    let expiring = accounts.len() - expired;
+    tracing::info!(target: "token_expiry", expiring, expired, "⚠️ 检测到令牌即将过期或已过期的账户");
+
+    let message = match (expiring, expired) {
+        (1, 0) => format!("{} 的令牌即将过期", accounts[0].email),
+        (0, 1) => format!("{} 的令牌已过期", accounts[0].email),
+        (expiring, 0) => format!("{} 个账户的令牌即将过期", expiring),
+        (0, expired) => format!("{} 个账户的令牌已过期", expired),
+        (expiring, expired) => format!("{} 个账户的令牌即将过期，{} 个已过期", expiring, expired),
+    };
+    crate::system_tray::show_notification(app_handle, "令牌过期提醒", &message);
+
+    let alert = TokenExpiryAlert {
+        accounts,
+        warning_minutes: token_status::warning_minutes(),
+    };
+    if let Err(e) = app_handle.emit("token-expiry-alert", &alert) {
+        tracing::error!(target: "token_expiry", error = %e, "推送令牌过期提醒失败");
+    }
+}
@@ -2,5 +2,7 @@
 
 pub mod log_decorator;
 pub mod log_sanitizer;
+pub mod protobuf;
 pub mod sanitizing_layer;
+pub mod token;
 pub mod tracing_config;
@@ -0,0 +1,201 @@
+//! Protobuf 字段级读写
+//!
+//! prost 解码时会丢弃 .proto 中未声明的字段，而 jetskiStateSync 中仍有大量未解析的字段，
+//! 直接重新编码整个 SessionResponse 会丢失数据。这里在原始字节上只替换指定字段，
+//! 其余字段按原样保留。
+
+use prost::encoding::{decode_key, decode_varint, encode_key, encode_varint, WireType};
+
+use crate::error::AgentError;
+
+/// 消息中的一个字段（字节范围包含 key）
+struct Field {
+    tag: u32,
+    start: usize,
+    value_start: usize,
+    end: usize,
+}
+
+fn parse_fields(message: &[u8]) -> Result<Vec<Field>, AgentError> {
+    let decode_error = |e: prost::DecodeError| AgentError::ProtoDecode(e.to_string());
+
+    let mut fields = Vec::new();
+    let mut buf = message;
+    while !buf.is_empty() {
+        let start = message.len() - buf.len();
+        let (tag, wire_type) = decode_key(&mut buf).map_err(decode_error)?;
+        let value_start = message.len() - buf.len();
+
+        let value_len = match wire_type {
+            WireType::Varint => {
+                decode_varint(&mut buf).map_err(decode_error)?;
+                0
+            }
+            WireType::SixtyFourBit => 8,
+            WireType::ThirtyTwoBit => 4,
+            WireType::LengthDelimited => decode_varint(&mut buf).map_err(decode_error)? as usize,
+            WireType::StartGroup | WireType::EndGroup => {
+                return Err(AgentError::ProtoDecode(format!(
+                    "不支持的 group 字段: {}",
+                    tag
+                )));
+            }
+        };
+        if value_len > buf.len() {
+            return Err(AgentError::ProtoDecode(format!("字段 {} 长度越界", tag)));
+        }
+        buf = &buf[value_len..];
+
+        fields.push(Field {
+            tag,
+            start,
+            value_start,
+            end: message.len() - buf.len(),
+        });
+    }
+    Ok(fields)
+}
+
+/// 读取长度分隔字段（嵌套消息、字符串、bytes）的内容，字段重复时取最后一个
+pub fn get_bytes_field(message: &[u8], tag: u32) -> Result<Option<&[u8]>, AgentError> {
+    let field = parse_fields(message)?
+        .into_iter()
+        .rev()
+        .find(|f| f.tag == tag);
+
+    Ok(field.map(|f| {
+        let mut value = &message[f.value_start..f.end];
+        // 跳过长度前缀
+        let _ = decode_varint(&mut value);
+        value
+    }))
+}
+
+/// 设置长度分隔字段：替换第一次出现的位置并删除其余重复项，不存在时追加到末尾
+pub fn set_bytes_field(message: &[u8], tag: u32, value: &[u8]) -> Result<Vec<u8>, AgentError> {
+    let mut encoded = Vec::with_capacity(value.len() + 8);
+    encode_key(tag, WireType::LengthDelimited, &mut encoded);
+    encode_varint(value.len() as u64, &mut encoded);
+    encoded.extend_from_slice(value);
+    replace_field(message, tag, &encoded)
+}
+
+/// 设置 varint 字段（int32 / int64 / uint64 / bool / enum）
+pub fn set_varint_field(message: &[u8], tag: u32, value: u64) -> Result<Vec<u8>, AgentError> {
+    let mut encoded = Vec::with_capacity(16);
+    encode_key(tag, WireType::Varint, &mut encoded);
+    encode_varint(value, &mut encoded);
+    replace_field(message, tag, &encoded)
+}
+
+fn replace_field(message: &[u8], tag: u32, encoded: &[u8]) -> Result<Vec<u8>, AgentError> {
+    let fields = parse_fields(message)?;
+
+    let mut output = Vec::with_capacity(message.len() + encoded.len());
+    let mut written = false;
+    for field in &fields {
+        if field.tag != tag {
+            output.extend_from_slice(&message[field.start..field.end]);
+        } else if !written {
+            output.extend_from_slice(encoded);
+            written = true;
+        }
+    }
+    if !written {
+        output.extend_from_slice(encoded);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes_field(tag: u32, value: &[u8]) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encode_key(tag, WireType::LengthDelimited, &mut encoded);
+        encode_varint(value.len() as u64, &mut encoded);
+        encoded.extend_from_slice(value);
+        encoded
+    }
+
+    fn varint_field(tag: u32, value: u64) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encode_key(tag, WireType::Varint, &mut encoded);
+        encode_varint(value, &mut encoded);
+        encoded
+    }
+
+    fn fixed_field(tag: u32, wire_type: WireType, value: &[u8]) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encode_key(tag, wire_type, &mut encoded);
+        encoded.extend_from_slice(value);
+        encoded
+    }
+
+    #[test]
+    fn set_bytes_field_keeps_unknown_fields_in_place() {
+        let message = [
+            varint_field(2, 150),
+            bytes_field(1, b"old"),
+            fixed_field(90, WireType::ThirtyTwoBit, &[1, 2, 3, 4]),
+            fixed_field(91, WireType::SixtyFourBit, &[1, 2, 3, 4, 5, 6, 7, 8]),
+            bytes_field(500, b"unknown"),
+        ]
+        .concat();
+
+        let updated = set_bytes_field(&message, 1, b"new").unwrap();
+
+        let expected = [
+            varint_field(2, 150),
+            bytes_field(1, b"new"),
+            fixed_field(90, WireType::ThirtyTwoBit, &[1, 2, 3, 4]),
+            fixed_field(91, WireType::SixtyFourBit, &[1, 2, 3, 4, 5, 6, 7, 8]),
+            bytes_field(500, b"unknown"),
+        ]
+        .concat();
+        assert_eq!(updated, expected);
+    }
+
+    #[test]
+    fn repeated_tag_is_collapsed_into_first_position() {
+        let message = [
+            bytes_field(1, b"first"),
+            varint_field(2, 7),
+            bytes_field(1, b"second"),
+        ]
+        .concat();
+
+        // 读取时与 prost 一致，取最后一个
+        assert_eq!(get_bytes_field(&message, 1).unwrap(), Some(&b"second"[..]));
+
+        let updated = set_bytes_field(&message, 1, b"new").unwrap();
+        assert_eq!(
+            updated,
+            [bytes_field(1, b"new"), varint_field(2, 7)].concat()
+        );
+    }
+
+    #[test]
+    fn missing_field_is_appended() {
+        let message = varint_field(2, 7);
+        assert_eq!(get_bytes_field(&message, 4).unwrap(), None);
+
+        let updated = set_varint_field(&message, 1, 1_700_000_000).unwrap();
+        assert_eq!(
+            updated,
+            [varint_field(2, 7), varint_field(1, 1_700_000_000)].concat()
+        );
+
+        let from_empty = set_bytes_field(&[], 4, b"meta").unwrap();
+        assert_eq!(from_empty, bytes_field(4, b"meta"));
+    }
+
+    #[test]
+    fn truncated_field_is_rejected() {
+        let mut message = bytes_field(1, b"value");
+        message.truncate(message.len() - 2);
+        assert!(parse_fields(&message).is_err());
+        assert!(set_bytes_field(&message, 2, b"x").is_err());
+    }
+}
@@ -0,0 +1,10 @@
+//! 随机令牌生成
+
+use rand::RngCore;
+
+/// 生成 `len` 字节的随机令牌（十六进制字符串）
+pub fn random_hex_token(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
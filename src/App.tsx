@@ -1,13 +1,17 @@
 import React, {useEffect, useState} from 'react';
 import {useDevToolsShortcut} from './hooks/use-devTools-shortcut.ts';
 import {useAntigravityAccount} from './modules/use-antigravity-account.ts';
-import {DATABASE_EVENTS, useDbMonitoringStore} from './modules/db-monitoring-store';
+import {useDbMonitoringStore} from './modules/db-monitoring-store';
+import {listen} from '@tauri-apps/api/event';
+import type {AccountCapturedEvent, DeepLinkResult, TokenExpiryAlert} from './commands/types/account.types.ts';
+import {logger} from './lib/logger.ts';
 import {useAntigravityIsRunning} from './hooks/use-antigravity-is-running.ts';
-import {Toaster} from 'react-hot-toast';
+import toast, {Toaster} from 'react-hot-toast';
 import AppDock from './components/app/AppDock.tsx';
 import {AppContent} from "@/components/app/AppContent.tsx";
 import {AppLoader} from "@/components/app/AppLoader.tsx";
 import {PlatformCommands} from "@/commands/PlatformCommands.ts";
+import type {RotationEvent} from "@/commands/types/settings.types.ts";
 
 function App() {
   // ========== 应用状态 ==========
@@ -26,12 +30,56 @@ function App() {
     // 初始化监控（自动启动）
     dbMonitoringActions.start();
 
-    // 添加事件监听器
-    const unlisten = dbMonitoringActions.addListener(DATABASE_EVENTS.DATA_CHANGED, antigravityAccount.insertOrUpdateCurrentAccount);
+    // 新登录的账户由后端监控自动保存，这里只同步列表
+    const unlisten = listen<AccountCapturedEvent>('account-captured', (event) => {
+      logger.info('后端已自动保存账户', {
+        module: 'App',
+        email: event.payload.email,
+        reason: event.payload.reason
+      });
+      antigravityAccount.syncCurrentAccount();
+    });
+
+    // 通过 antigravity-agent:// 链接触发的操作由后端执行，这里提示结果并刷新列表
+    const unlistenDeepLink = listen<DeepLinkResult>('deep-link-handled', (event) => {
+      const {action, success, message} = event.payload;
+      logger.info('链接操作已处理', {module: 'App', action, success});
+      if (success) {
+        toast.success(message);
+        antigravityAccount.syncCurrentAccount();
+      } else {
+        toast.error(message);
+      }
+    });
+
+    // 自动轮换由后端调度，这里提示结果并刷新列表
+    const unlistenRotation = listen<RotationEvent>('account-rotated', (event) => {
+      const {from, to, success, message} = event.payload;
+      logger.info('账户已轮换', {module: 'App', from, to, success});
+      if (success) {
+        toast.success(message);
+        antigravityAccount.syncCurrentAccount();
+      } else {
+        toast.error(message);
+      }
+    });
+
+    // 已保存账户的令牌即将过期或已过期时提醒
+    const unlistenTokenExpiry = listen<TokenExpiryAlert>('token-expiry-alert', (event) => {
+      for (const account of event.payload.accounts) {
+        logger.info('账户令牌即将过期', {module: 'App', email: account.email, state: account.state});
+        toast(account.state === 'expired'
+          ? `${account.email} 的令牌已过期`
+          : `${account.email} 的令牌即将过期`);
+      }
+    });
 
     // 组件卸载时移除监听器
     return () => {
-      unlisten()
+      unlisten.then(f => f())
+      unlistenDeepLink.then(f => f())
+      unlistenRotation.then(f => f())
+      unlistenTokenExpiry.then(f => f())
       dbMonitoringActions.stop()
     };
   }, []);
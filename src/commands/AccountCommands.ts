@@ -1,5 +1,5 @@
-import { invoke } from '@tauri-apps/api/core';
-import {AntigravityAccount} from "@/commands/types/account.types.ts";
+import {invoke} from './invoke.ts';
+import {AccountMetadata, AccountQuota, AntigravityAccount, BestAccountSwitch, RefreshedAccount, SavedAccount, SnapshotVersion, SwitchResult} from "@/commands/types/account.types.ts";
 
 /**
  * Antigravity 账户管理命令
@@ -30,12 +30,101 @@ export class AccountCommands {
   }
 
   /**
-   * 切换到指定账户（完整流程：关闭进程 → 恢复数据 → 重启）
+   * 切换到指定账户（完整流程：快照 → 关闭进程 → 恢复数据 → 重启，失败自动回滚）
    * @param accountName 账户名（邮箱）
-   * @returns 切换结果消息
+   * @param exitTimeoutMs 等待 Antigravity 进程退出的超时时间（毫秒），不传则使用默认值
+   * @returns 切换结果，失败时包含失败阶段与是否已回滚
    */
-  static async switchToAntigravityAccount(accountName: string): Promise<string> {
-    return invoke('switch_to_antigravity_account', { accountName: accountName });
+  static async switchToAntigravityAccount(accountName: string, exitTimeoutMs?: number): Promise<SwitchResult> {
+    return invoke('switch_to_antigravity_account', { accountName: accountName, exitTimeoutMs });
+  }
+
+  /**
+   * 切换到指定模型剩余配额最多的已保存账户（跳过令牌已过期、配额已用尽的账户）
+   * @param model 模型 id 或显示名称，如 gemini-3-pro-high
+   * @param refresh 为 true 时先重新查询所有账户的配额
+   * @param exitTimeoutMs 等待 Antigravity 进程退出的超时时间（毫秒），不传则使用默认值
+   * @returns 排名、被跳过的账户、选中原因与切换结果
+   */
+  static async switchToBestAccount(model: string, refresh = false, exitTimeoutMs?: number): Promise<BestAccountSwitch> {
+    return invoke('switch_to_best_account', { model, refresh, exitTimeoutMs });
+  }
+
+  /**
+   * 将账户恢复到 Antigravity（不重启进程）
+   * @param accountName 账户名（邮箱）
+   * @param versionId 历史版本 id，指定时先将账户回滚到该版本
+   * @returns 恢复结果消息
+   */
+  static async restoreAntigravityAccount(accountName: string, versionId?: string): Promise<string> {
+    return invoke('restore_antigravity_account', { accountName, versionId });
+  }
+
+  /**
+   * 列出账户的历史快照版本（最新的在前）
+   */
+  static async listAccountVersions(accountName: string): Promise<SnapshotVersion[]> {
+    return invoke('list_antigravity_account_versions', { accountName });
+  }
+
+  /**
+   * 设置账户显示名称，传 null 或空字符串恢复显示邮箱
+   */
+  static async setAccountLabel(email: string, label: string | null): Promise<AccountMetadata> {
+    return invoke('set_account_label', { email, label });
+  }
+
+  /**
+   * 设置账户备注
+   */
+  static async setAccountNotes(email: string, notes: string | null): Promise<AccountMetadata> {
+    return invoke('set_account_notes', { email, notes });
+  }
+
+  /**
+   * 设置账户标签（后端会去除空白并去重）
+   */
+  static async setAccountTags(email: string, tags: string[]): Promise<AccountMetadata> {
+    return invoke('set_account_tags', { email, tags });
+  }
+
+  /**
+   * 设置账户是否置顶
+   */
+  static async setAccountPinned(email: string, pinned: boolean): Promise<AccountMetadata> {
+    return invoke('set_account_pinned', { email, pinned });
+  }
+
+  /**
+   * 刷新已保存账户的 access token，新令牌会写回账户文件
+   * @param accountName 账户名（邮箱）
+   */
+  static async refreshAccountToken(accountName: string): Promise<RefreshedAccount> {
+    return invoke('refresh_antigravity_account_token', { accountName });
+  }
+
+  /**
+   * 在浏览器中登录 Google 账户（PKCE），完成后保存为账户快照
+   * 等待用户授权，最长 5 分钟
+   */
+  static async loginWithGoogleOAuth(): Promise<SavedAccount> {
+    return invoke('login_with_google_oauth');
+  }
+
+  /**
+   * 取消正在进行的浏览器登录
+   * @returns 是否有登录被取消
+   */
+  static async cancelGoogleOAuthLogin(): Promise<boolean> {
+    return invoke('cancel_google_oauth_login');
+  }
+
+  /**
+   * 获取所有已保存账户的配额（后端每 15 分钟刷新一次，结果推送 accounts-quota-updated 事件）
+   * @param refresh 为 true 时立即重新查询，否则返回缓存
+   */
+  static async getAccountsQuota(refresh = false): Promise<AccountQuota[]> {
+    return invoke('get_accounts_quota', { refresh });
   }
 
   /**
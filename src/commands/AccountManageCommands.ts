@@ -1,4 +1,4 @@
-import {invoke} from '@tauri-apps/api/core';
+import {invoke} from './invoke.ts';
 import type {BackupData, RestoreResult} from './types/account-manage.types.ts';
 
 /**
@@ -30,7 +30,7 @@ export class AccountManageCommands {
     return invoke('decrypt_config_data', { encryptedData: encryptedData, password });
   }
 
-  static signInNewAntigravityAccount(): Promise<string> {
-    return invoke('sign_in_new_antigravity_account');
+  static signInNewAntigravityAccount(exitTimeoutMs?: number): Promise<string> {
+    return invoke('sign_in_new_antigravity_account', { exitTimeoutMs });
   }
 }
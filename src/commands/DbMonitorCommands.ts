@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core';
+import {invoke} from './invoke.ts';
 
 /**
  * 数据库监控命令
@@ -27,4 +27,12 @@ export class DbMonitorCommands {
   static async stop(): Promise<string> {
     return invoke('stop_database_monitoring');
   }
+
+  /**
+   * 设置 database-changed 事件只推送哪些键
+   * @param keys ItemTable 键列表，传 null 恢复为全部监控键
+   */
+  static async subscribe(keys: string[] | null): Promise<void> {
+    return invoke('set_database_subscription', { keys });
+  }
 }
@@ -1,5 +1,12 @@
-import { invoke } from '@tauri-apps/api/core';
-import type { PlatformInfo, DetectionResult, PathConfig } from './types/platform.types';
+import {invoke} from './invoke.ts';
+import type {
+  PlatformInfo,
+  DetectionResult,
+  PathConfig,
+  AntigravityTarget,
+  LaunchProfile,
+  TargetsConfig,
+} from './types/platform.types';
 
 /**
  * 平台工具命令
@@ -55,6 +62,32 @@ export class PlatformCommands {
     return invoke('save_antigravity_executable', { path });
   }
 
+  /**
+   * 验证 Antigravity 数据目录（需包含带 ItemTable 表的 state.vscdb）
+   * @param path 目录路径
+   * @returns 是否有效
+   */
+  static async validateDataDir(path: string): Promise<boolean> {
+    return invoke('validate_antigravity_data_dir', { path });
+  }
+
+  /**
+   * 保存用户自定义的 Antigravity 数据目录
+   * @param path 目录路径（--user-data-dir 或便携版 data 目录）
+   * @returns 保存结果消息
+   */
+  static async saveAntigravityDataDir(path: string): Promise<string> {
+    return invoke('save_antigravity_data_dir', { path });
+  }
+
+  /**
+   * 清除自定义数据目录，恢复自动检测
+   * @returns 结果消息
+   */
+  static async clearAntigravityDataDir(): Promise<string> {
+    return invoke('clear_antigravity_data_dir');
+  }
+
   /**
    * 获取当前配置的路径
    * @returns 路径配置
@@ -62,4 +95,43 @@ export class PlatformCommands {
   static async getCurrentPaths(): Promise<PathConfig> {
     return invoke('get_current_paths');
   }
+
+  /**
+   * 获取已配置的目标及活动目标
+   */
+  static async listTargets(): Promise<TargetsConfig> {
+    return invoke('list_antigravity_targets');
+  }
+
+  /**
+   * 新增或更新目标
+   * @param target 目标配置
+   */
+  static async saveTarget(target: AntigravityTarget): Promise<TargetsConfig> {
+    return invoke('save_antigravity_target', { target });
+  }
+
+  /**
+   * 保存未选择目标时使用的启动配置
+   * @param profile 启动配置
+   */
+  static async saveDefaultLaunchProfile(profile: LaunchProfile): Promise<TargetsConfig> {
+    return invoke('save_default_launch_profile', { profile });
+  }
+
+  /**
+   * 删除目标
+   * @param name 目标名称
+   */
+  static async removeTarget(name: string): Promise<TargetsConfig> {
+    return invoke('remove_antigravity_target', { name });
+  }
+
+  /**
+   * 设置活动目标
+   * @param name 目标名称，null 表示使用自动检测的默认安装
+   */
+  static async setActiveTarget(name: string | null): Promise<TargetsConfig> {
+    return invoke('set_active_antigravity_target', { name });
+  }
 }
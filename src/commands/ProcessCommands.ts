@@ -1,4 +1,4 @@
-import {invoke} from '@tauri-apps/api/core';
+import {invoke} from './invoke.ts';
 
 /**
  * 进程管理命令
@@ -1,5 +1,11 @@
-import { invoke } from '@tauri-apps/api/core';
-import type { AppSettings } from './types/settings.types';
+import {invoke} from './invoke.ts';
+import type {
+  AppSettings,
+  IpcServerInfo,
+  RotationEvent,
+  RotationPlan,
+  RotationStatus,
+} from './types/settings.types';
 
 /**
  * 设置管理命令
@@ -30,4 +36,59 @@ export class SettingsCommands {
   static async getAll(): Promise<AppSettings> {
     return invoke('get_all_settings');
   }
+
+  /**
+   * 保存数据库监控关注的键
+   * @param keys ItemTable 键列表
+   * @returns 实际生效的键列表（始终包含 agent 状态键）
+   */
+  static async saveDbWatchedKeys(keys: string[]): Promise<string[]> {
+    return invoke('save_db_watched_keys', { keys });
+  }
+
+  /**
+   * 启用或停用本地控制 API
+   * @param enabled 是否启用
+   * @returns 连接信息（socket / 命名管道与令牌文件路径）
+   */
+  static async saveIpcServerEnabled(enabled: boolean): Promise<IpcServerInfo> {
+    return invoke('save_ipc_server_enabled', { enabled });
+  }
+
+  /**
+   * 获取本地控制 API 的连接信息
+   */
+  static async getIpcServerInfo(): Promise<IpcServerInfo> {
+    return invoke('get_ipc_server_info');
+  }
+
+  /**
+   * 保存令牌过期提醒的提前分钟数
+   * @param minutes 1 分钟到 7 天
+   */
+  static async saveTokenExpiryWarningMinutes(minutes: number): Promise<number> {
+    return invoke('save_token_expiry_warning_minutes', { minutes });
+  }
+
+  /**
+   * 获取自动轮换计划及下一次轮换时间
+   */
+  static async getRotationPlan(): Promise<RotationStatus> {
+    return invoke('get_rotation_plan');
+  }
+
+  /**
+   * 保存自动轮换计划
+   * @param plan 轮换计划（上次轮换时间与账户由后端维护）
+   */
+  static async saveRotationPlan(plan: RotationPlan): Promise<RotationStatus> {
+    return invoke('save_rotation_plan', { plan });
+  }
+
+  /**
+   * 立即轮换到下一个可用账户
+   */
+  static async rotateNow(): Promise<RotationEvent> {
+    return invoke('rotate_account_now');
+  }
 }
@@ -0,0 +1,38 @@
+import {invoke as tauriInvoke, type InvokeArgs} from '@tauri-apps/api/core';
+import type {AgentErrorCode, AgentErrorPayload} from './types/error.types.ts';
+
+/**
+ * 后端命令错误，保留错误码与细节，message 可直接用于提示
+ */
+export class AgentError extends Error {
+  readonly code: AgentErrorCode;
+  readonly details: string | null;
+
+  constructor(payload: AgentErrorPayload) {
+    super(payload.details ? `${payload.message}: ${payload.details}` : payload.message);
+    this.name = 'AgentError';
+    this.code = payload.code;
+    this.details = payload.details;
+  }
+}
+
+function isAgentErrorPayload(value: unknown): value is AgentErrorPayload {
+  return typeof value === 'object'
+    && value !== null
+    && typeof (value as AgentErrorPayload).code === 'string'
+    && typeof (value as AgentErrorPayload).message === 'string';
+}
+
+/**
+ * 调用后端命令，将 {code, message, details} 结构的错误转换为 AgentError
+ */
+export async function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  try {
+    return await tauriInvoke<T>(cmd, args);
+  } catch (error) {
+    if (isAgentErrorPayload(error)) {
+      throw new AgentError(error);
+    }
+    throw error;
+  }
+}
@@ -1,3 +1,4 @@
+import type {AgentErrorPayload} from './error.types.ts';
 
 // Antigravity 当前用户信息类型
 export interface AntigravityAccount {
@@ -10,6 +11,115 @@ export interface AntigravityAccount {
   flags_f5_base64: string
   history: any[]
   user_id_raw_base64: string
+  // 账户索引中的元数据（仅已保存账户列表包含）
+  metadata?: AccountMetadata
+  // 令牌状态（仅已保存账户列表包含）
+  tokenStatus?: TokenStatus
+}
+
+// 令牌状态：有效 / 即将过期 / 已过期 / 无过期时间
+export type TokenState = 'valid' | 'expiring_soon' | 'expired' | 'unknown';
+
+// 账户令牌状态
+export interface TokenStatus {
+  state: TokenState
+  // AuthMetadata.expiry_timestamp（Unix 秒）
+  expiryTimestamp: number | null
+  // 距离过期的秒数，已过期时为负数
+  expiresInSeconds: number | null
+}
+
+// token-expiry-alert 事件内容
+export interface TokenExpiryAlert {
+  // 本次新进入即将过期或已过期状态的账户
+  accounts: (TokenStatus & { email: string })[]
+  warningMinutes: number
+}
+
+// 后端自动保存账户的原因
+export type CaptureReason = 'new_account' | 'account_changed' | 'token_refreshed';
+
+// account-captured 事件内容
+export interface AccountCapturedEvent {
+  email: string
+  reason: CaptureReason
+  accountFile: string
+  expiryTimestamp: number | null
+  capturedAt: string
+}
+
+// antigravity-agent:// 链接的处理结果（deep-link-handled 事件）
+export interface DeepLinkResult {
+  // switch / capture / sign-in，链接无效时为 invalid
+  action: string
+  success: boolean
+  message: string
+}
+
+// 账户历史快照版本
+export interface SnapshotVersion {
+  id: string
+  capturedAt: string
+  // AuthMetadata.expiry_timestamp（Unix 秒）
+  expiryTimestamp: number | null
+}
+
+// 保存账户快照的结果
+export interface SavedAccount {
+  email: string
+  accountFile: string
+  // 保存前账户文件不存在
+  isNew: boolean
+  // 账户文件内容与本次保存相同（未写入）
+  unchanged: boolean
+  expiryTimestamp: number | null
+}
+
+// 令牌刷新结果
+export interface RefreshedAccount {
+  email: string
+  // 新的过期时间（Unix 秒）
+  expiryTimestamp: number
+  // 令牌端点返回了新的 refresh token
+  refreshTokenRotated: boolean
+  tokenStatus: TokenStatus
+}
+
+// 单个模型的配额
+export interface ModelQuota {
+  id: string
+  displayName: string | null
+  // 剩余配额比例（0 ~ 1）
+  remainingFraction: number | null
+  resetTime: string | null
+}
+
+// 账户配额（后端缓存）
+export interface AccountQuota {
+  email: string
+  tier: string | null
+  tierName: string | null
+  project: string | null
+  models: ModelQuota[]
+  // 最近一次成功获取的时间
+  fetchedAt: string | null
+  // 最近一次查询的时间
+  checkedAt: string
+  // 最近一次查询失败的原因
+  lastError: string | null
+}
+
+// 账户元数据
+export interface AccountMetadata {
+  email: string
+  label: string | null
+  tags: string[]
+  notes: string | null
+  pinned: boolean
+  createdAt: string
+  updatedAt: string
+  lastSwitchedAt: string | null
+  switchCount: number
 }
 
 interface Auth {
@@ -57,3 +167,64 @@ interface Plan {
   upgrade_msg: string
   upgrade_url: string
 }
+
+// 账户切换阶段
+export type SwitchStage = 'prepare' | 'snapshot' | 'stop_process' | 'restore' | 'start'
+
+// 等待 Antigravity 进程退出的结果
+export interface ProcessExitReport {
+  timeoutMs: number
+  waitedMs: number
+  timedOut: boolean
+  forceKilled: number[]
+  remaining: number[]
+}
+
+// 单个 Antigravity 进程的关闭结果
+export interface ProcessShutdown {
+  pid: number
+  name: string
+  role: 'main' | 'helper'
+  outcome: 'terminated' | 'exited' | 'force_killed' | 'failed'
+}
+
+// 分阶段关闭 Antigravity 的结果
+export interface ShutdownReport {
+  processes: ProcessShutdown[]
+  exit: ProcessExitReport
+}
+
+// 账户切换结果
+export interface SwitchResult {
+  account: string
+  success: boolean
+  failedStage: SwitchStage | null
+  error: AgentErrorPayload | null
+  rolledBack: boolean
+  shutdown: ShutdownReport | null
+  message: string
+}
+
+// 按配额排名的候选账户
+export interface RankedAccount {
+  email: string
+  // 该模型的剩余配额比例（0 ~ 1），没有配额数据时为 null
+  remainingFraction: number | null
+  resetTime: string | null
+  // 账户快照的 ModelConfig.items 中包含该模型
+  modelListed: boolean
+  tokenStatus: TokenStatus
+}
+
+// 切换到配额最多的账户的结果
+export interface BestAccountSwitch {
+  model: string
+  from: string | null
+  chosen: RankedAccount | null
+  // 选中该账户（或没有可用账户）的原因
+  reason: string
+  ranking: RankedAccount[]
+  skipped: { email: string, reason: string }[]
+  // 没有可用账户时为 null
+  switchResult: SwitchResult | null
+}
@@ -0,0 +1,43 @@
+/**
+ * 数据库监控相关类型定义
+ */
+
+/** 单个键的变化类型 */
+export type ChangeKind = 'added' | 'removed' | 'changed';
+
+/**
+ * 单个 ItemTable 键的变化
+ */
+export interface KeyChange {
+  key: string;
+  kind: ChangeKind;
+  oldValue: unknown | null;
+  newValue: unknown | null;
+}
+
+/**
+ * jetskiStateSync.agentManagerInitState 解码后的语义差异
+ */
+export interface AgentStateDiff {
+  oldEmail: string | null;
+  newEmail: string | null;
+  emailChanged: boolean;
+  /** 同一账户的访问令牌发生变化 */
+  tokenRefreshed: boolean;
+  oldPlan: string | null;
+  newPlan: string | null;
+  planChanged: boolean;
+  modelsAdded: string[];
+  modelsRemoved: string[];
+}
+
+/**
+ * database-changed 事件内容
+ */
+export interface DataDiff {
+  hasChanges: boolean;
+  changes: KeyChange[];
+  /** agent 状态键变化且能解码时的语义差异 */
+  agentState: AgentStateDiff | null;
+  summary: string;
+}
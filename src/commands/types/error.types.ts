@@ -0,0 +1,24 @@
+// 后端 AgentError 的错误码（与 src-tauri/src/error.rs 保持一致）
+export type AgentErrorCode =
+  | 'NOT_INSTALLED'
+  | 'DATABASE_MISSING'
+  | 'DATABASE_LOCKED'
+  | 'DATABASE'
+  | 'PROTO_DECODE'
+  | 'ACCOUNT_NOT_FOUND'
+  | 'PROCESS_NOT_RUNNING'
+  | 'PROCESS_KILL_FAILED'
+  | 'LAUNCH_FAILED'
+  | 'CRYPTO'
+  | 'NETWORK'
+  | 'OAUTH'
+  | 'IO'
+  | 'INVALID_INPUT'
+  | 'INTERNAL';
+
+// 命令失败时后端返回的错误结构
+export interface AgentErrorPayload {
+  code: AgentErrorCode;
+  message: string;
+  details: string | null;
+}
@@ -51,4 +51,63 @@ export interface DetectionResult {
 export interface PathConfig {
   /** 可执行文件路径 */
   executablePath?: string | null;
+
+  /** 自定义数据目录 */
+  dataDir?: string | null;
+}
+
+/**
+ * Antigravity 目标（一组数据目录与可执行文件）
+ */
+export interface AntigravityTarget {
+  /** 目标名称（唯一） */
+  name: string;
+
+  /** 用户数据目录（--user-data-dir） */
+  dataDir: string;
+
+  /** 可执行文件路径，未设置时自动检测 */
+  executablePath?: string | null;
+
+  /** 启动该目标时使用的配置 */
+  launchProfile?: LaunchProfile;
+}
+
+/**
+ * Linux 上使用的显示协议
+ */
+export type DisplayServer = 'auto' | 'wayland' | 'x11';
+
+/**
+ * 启动配置，每次启动 Antigravity（包括切换账户后）都会应用
+ */
+export interface LaunchProfile {
+  /** 额外的命令行参数 */
+  args: string[];
+
+  /** 额外的环境变量 */
+  env: Record<string, string>;
+
+  /** 启动后打开的工作区文件夹 */
+  workspaceFolder?: string | null;
+
+  /** 进程的工作目录 */
+  workingDir?: string | null;
+
+  /** 显示协议（仅 Linux） */
+  displayServer: DisplayServer;
+}
+
+/**
+ * 目标配置
+ */
+export interface TargetsConfig {
+  /** 活动目标名称，null 表示使用自动检测的默认安装 */
+  active: string | null;
+
+  /** 已配置的目标 */
+  targets: AntigravityTarget[];
+
+  /** 未选择目标时使用的启动配置 */
+  defaultLaunchProfile: LaunchProfile;
 }
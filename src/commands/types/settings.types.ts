@@ -11,4 +11,89 @@ export interface AppSettings {
 
   /** 静默启动是否启用 */
   silent_start_enabled: boolean;
+
+  /** 数据库监控关注的 ItemTable 键 */
+  db_watched_keys: string[];
+
+  /** 本地控制 API 是否启用 */
+  ipc_server_enabled: boolean;
+
+  /** 自动轮换计划 */
+  rotation: RotationPlan;
+
+  /** 令牌在多少分钟内过期时提醒 */
+  token_expiry_warning_minutes: number;
+}
+
+/**
+ * 本地控制 API 连接信息
+ */
+export interface IpcServerInfo {
+  /** 服务是否在运行 */
+  running: boolean;
+
+  /** Unix socket 路径或 Windows 命名管道名称 */
+  endpoint: string;
+
+  /** 认证令牌文件路径 */
+  tokenFile: string;
+}
+
+/**
+ * 轮换时间表：固定间隔（分钟）或每天的指定本地时间（HH:MM）
+ */
+export type RotationSchedule =
+  | { type: 'interval'; minutes: number }
+  | { type: 'daily_times'; times: string[] };
+
+/**
+ * 自动轮换计划
+ */
+export interface RotationPlan {
+  /** 是否启用自动轮换 */
+  enabled: boolean;
+
+  /** 参与轮换的账户邮箱（按轮换顺序） */
+  accounts: string[];
+
+  schedule: RotationSchedule;
+
+  /** 上次轮换时间（Unix 秒），由后端维护 */
+  last_rotation_at?: number | null;
+
+  /** 上次轮换到的账户，由后端维护 */
+  last_account?: string | null;
+}
+
+/**
+ * 轮换计划及下一次轮换时间
+ */
+export interface RotationStatus {
+  plan: RotationPlan;
+
+  /** 下一次轮换时间（ISO 8601），未启用时为 null */
+  nextRotationAt: string | null;
+}
+
+/**
+ * account-rotated 事件内容
+ */
+export interface RotationEvent {
+  /** 轮换前的账户 */
+  from: string | null;
+
+  /** 轮换到的账户，没有可用账户时为 null */
+  to: string | null;
+
+  success: boolean;
+
+  message: string;
+
+  /** 被跳过的账户及原因 */
+  skipped: { email: string; reason: string }[];
+
+  /** 是否由计划触发 */
+  scheduled: boolean;
+
+  rotatedAt: string;
 }
@@ -3,13 +3,12 @@ import {listen, UnlistenFn} from '@tauri-apps/api/event';
 import {EventEmitter} from 'events';
 import {logger} from '../lib/logger.ts';
 import {DbMonitorCommands} from "@/commands/DbMonitorCommands.ts";
+import type {DataDiff} from "@/commands/types/db-monitor.types.ts";
 
 // 数据库变化事件数据接口
 export interface DatabaseChangeEvent {
     timestamp: number;
-    old_data?: any;
-    new_data?: any;
-    diff?: any;
+    diff: DataDiff;
     originalEvent?: any;
 }
 
@@ -43,6 +42,9 @@ interface DbMonitoringActions {
   // 停止监听（清理资源）
   stop: () => Promise<void>;
 
+  // 只接收指定键的变化（null 表示全部监控键）
+  subscribe: (keys: string[] | null) => Promise<void>;
+
   // 添加事件监听器
   addListener: <T extends keyof DatabaseEventMap>(
     event: T,
@@ -68,14 +70,12 @@ export const useDbMonitoringStore = create<DbMonitoringActions>()(
               eventId: event.id || 'unknown'
             });
 
-            // 解析事件数据：newData, oldData, diff
-            const { newData, oldData, diff } = event.payload;
+            // 事件内容即逐键差异（已按订阅过滤）
+            const diff: DataDiff = event.payload;
 
             // 发射内部数据库变化事件
             databaseEventEmitter.emit(DATABASE_EVENTS.DATA_CHANGED, {
               timestamp: Date.now(),
-              newData,
-              oldData,
               diff,
               originalEvent: event
             });
@@ -120,6 +120,14 @@ export const useDbMonitoringStore = create<DbMonitoringActions>()(
         }
       },
 
+      subscribe: async (keys: string[] | null): Promise<void> => {
+        await DbMonitorCommands.subscribe(keys);
+        logger.info('数据库变化订阅已更新', {
+          module: 'DbMonitoringStore',
+          keys
+        });
+      },
+
       addListener: <T extends keyof DatabaseEventMap>(
         event: T,
         listener: DatabaseEventListener<T>
@@ -44,10 +44,14 @@ export const useAccountAdditionData = create<State & Actions>((setState, getStat
         return
       }
       try {
-        // 刷新 access token
-        const refreshTokenResponse = await CloudCodeAPI.refreshAccessToken(antigravityAccount.auth.id_token);
-        // 更新一下内存里面的 access token, 这里就不写入本地了
-        antigravityAccount.auth.access_token = refreshTokenResponse.access_token;
+        // 由后端刷新 access token 并写回账户文件，刷新结果不含令牌，重新读取账户文件获取
+        await AccountCommands.refreshAccountToken(antigravityAccount.context.email);
+        const accounts = await AccountCommands.getAntigravityAccounts();
+        const refreshedAccount = accounts.find(account => account.context.email === antigravityAccount.context.email);
+        if (!refreshedAccount) {
+          return
+        }
+        antigravityAccount.auth = refreshedAccount.auth;
       } catch {
         // 刷新失败（例如缺少 OAuth client 配置/refresh token 无效），终止本次附加数据拉取
         return
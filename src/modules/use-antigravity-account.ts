@@ -1,7 +1,7 @@
 import {create} from 'zustand';
 import {logger} from '../lib/logger.ts';
 import {AccountCommands} from '@/commands/AccountCommands.ts';
-import type {AntigravityAccount} from '@/commands/types/account.types.ts';
+import type {AccountMetadata, AntigravityAccount} from '@/commands/types/account.types.ts';
 import {AccountManageCommands} from "@/commands/AccountManageCommands.ts";
 
 // 常量定义
@@ -18,7 +18,9 @@ export interface AntigravityAccountActions {
   // 基础操作
   delete: (email: string) => Promise<void>;
   insertOrUpdateCurrentAccount: () => Promise<void>;
+  syncCurrentAccount: () => Promise<void>;
   switchToAccount: (email: string) => Promise<void>;
+  updateMetadata: (email: string, patch: Partial<Pick<AccountMetadata, 'label' | 'notes' | 'tags' | 'pinned'>>) => Promise<void>;
 
   // 批量操作
   clearAllAccounts: () => Promise<void>;
@@ -81,10 +83,29 @@ export const useAntigravityAccount = create<AntigravityAccountState & Antigravit
     }
   },
 
+  syncCurrentAccount: async (): Promise<void> => {
+    try {
+      // 只读取，不保存（保存已由后端完成）
+      const [currentInfo, accounts] = await Promise.all([
+        AccountCommands.getCurrentAntigravityAccount(),
+        AccountCommands.getAntigravityAccounts(),
+      ]);
+      set({ accounts, currentAuthInfo: currentInfo });
+    } catch (error) {
+      logger.error('同步当前用户失败', {
+        module: 'UserManagement',
+        error: error instanceof Error ? error.message : String(error)
+      });
+    }
+  },
+
   switchToAccount: async (email: string): Promise<void> => {
     try {
       // 调用后端切换用户命令
-      await AccountCommands.switchToAntigravityAccount(email);
+      const result = await AccountCommands.switchToAntigravityAccount(email);
+      if (!result.success) {
+        throw new Error(result.message);
+      }
     } catch (error) {
       logger.error('切换用户失败', {
         module: 'UserManagement',
@@ -95,6 +116,34 @@ export const useAntigravityAccount = create<AntigravityAccountState & Antigravit
     }
   },
 
+  updateMetadata: async (email, patch): Promise<void> => {
+    try {
+      if (patch.label !== undefined) {
+        await AccountCommands.setAccountLabel(email, patch.label);
+      }
+      if (patch.notes !== undefined) {
+        await AccountCommands.setAccountNotes(email, patch.notes);
+      }
+      if (patch.tags !== undefined) {
+        await AccountCommands.setAccountTags(email, patch.tags);
+      }
+      if (patch.pinned !== undefined) {
+        await AccountCommands.setAccountPinned(email, patch.pinned);
+      }
+
+      // 重新获取列表（排序与托盘显示名称都依赖元数据）
+      const accounts = await AccountCommands.getAntigravityAccounts();
+      set({ accounts });
+    } catch (error) {
+      logger.error('更新账户元数据失败', {
+        module: 'UserManagement',
+        email,
+        error: error instanceof Error ? error.message : String(error)
+      });
+      throw error;
+    }
+  },
+
   // ============ 批量操作 ============
 
   clearAllAccounts: async (): Promise<void> => {
@@ -16,23 +16,6 @@ const HTTP_CONFIG: HTTPConfig = {
   }
 };
 
-const GOOGLE_OAUTH_TOKEN_ENDPOINT = 'https://oauth2.googleapis.com/token';
-
-function getGoogleOAuthClientCredentials(): { clientId: string; clientSecret?: string } {
-  const clientId = import.meta.env.VITE_GOOGLE_OAUTH_CLIENT_ID as string | undefined;
-  const clientSecret = import.meta.env.VITE_GOOGLE_OAUTH_CLIENT_SECRET as string | undefined;
-
-  if (!clientId) {
-    throw new Error('Missing VITE_GOOGLE_OAUTH_CLIENT_ID (Google OAuth client_id)');
-  }
-
-  return {
-    clientId,
-    clientSecret: clientSecret || undefined,
-  };
-}
-
-
 const post = async <T>(endpoint: string, data: any, options?: RequestInit): Promise<T> => {
 
   const requestConfig: RequestInit = {
@@ -102,40 +85,6 @@ export namespace CloudCodeAPI {
     return response;
   }
 
-  export async function refreshAccessToken(
-    refresh_token: string,
-  ) {
-    const { clientId, clientSecret } = getGoogleOAuthClientCredentials();
-
-    const body = new URLSearchParams({
-      client_id: clientId,
-      grant_type: 'refresh_token',
-      refresh_token,
-    });
-
-    if (clientSecret) {
-      body.set('client_secret', clientSecret);
-    }
-
-    const response = await fetch(
-      GOOGLE_OAUTH_TOKEN_ENDPOINT,
-      {
-        method: 'POST',
-        headers: {
-          'content-type': 'application/x-www-form-urlencoded',
-        },
-        body: body.toString(),
-      },
-    );
-    const json = await response.json() as unknown as CloudCodeAPITypes.RefreshAccessTokenResponse | CloudCodeAPITypes.ErrorResponse;
-
-    if ("error" in json) {
-      return Promise.reject(json);
-    }
-
-    return json;
-  }
-
   export async function userinfo(
     access_token: string,
   ) {
@@ -72,6 +72,7 @@ const seedMocks = (
     getAccounts: async () => accounts,
     delete: async () => {},
     insertOrUpdateCurrentAccount: async () => {},
+    syncCurrentAccount: async () => {},
     switchToAccount: async () => {},
     clearAllAccounts: async () => {},
   });
@@ -84,6 +85,7 @@ const seedMocks = (
   useDbMonitoringStore.setState({
     start: async () => {},
     stop: async () => {},
+    subscribe: async () => {},
     addListener: () => () => {},
   });
 